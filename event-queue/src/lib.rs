@@ -1,7 +1,8 @@
 //! Queue events.
+use num_traits::SaturatingAdd;
 use std::cmp::Ordering;
 use std::collections::vec_deque::{Drain, VecDeque};
-use std::iter::FusedIterator;
+use std::iter::{FusedIterator, Rev};
 use std::ops::{Deref, Index, IndexMut, SubAssign};
 
 /// A queue for timed events.
@@ -62,6 +63,16 @@ impl<E> HandleEventCollision<E> for AlwaysRemoveOld {
     }
 }
 
+impl<'a, E, H> HandleEventCollision<E> for &'a H
+where
+    H: HandleEventCollision<E> + ?Sized,
+{
+    #[inline(always)]
+    fn decide_on_collision(&self, old_event: &E, new_event: &E) -> EventCollisionHandling {
+        (**self).decide_on_collision(old_event, new_event)
+    }
+}
+
 impl<T, E> Index<usize> for EventQueue<T, E> {
     type Output = (T, E);
 
@@ -99,11 +110,32 @@ impl<T, E> EventQueue<T, E> {
     /// Queue a new event.
     /// When the buffer is full, an element may be removed from the queue to make some room.
     /// This element is returned.
-    pub fn queue_event<H>(
+    ///
+    /// See also [`queue_event_indexed`] if you need to know where the new event was inserted.
+    ///
+    /// [`queue_event_indexed`]: ./struct.EventQueue.html#method.queue_event_indexed
+    pub fn queue_event<H>(&mut self, new_event: (T, E), collision_decider: H) -> Option<(T, E)>
+    where
+        H: HandleEventCollision<E>,
+        T: Ord,
+    {
+        self.queue_event_indexed(new_event, collision_decider).1
+    }
+
+    /// Queue a new event and return the index at which it has been inserted.
+    ///
+    /// The first element of the returned tuple is the index of the newly queued event
+    /// in the queue, or `None` when the new event was ignored.
+    /// The second element of the returned tuple is the same as what [`queue_event`] returns:
+    /// when the buffer is full, an element may be removed from the queue to make some room
+    /// and this element is returned.
+    ///
+    /// [`queue_event`]: ./struct.EventQueue.html#method.queue_event
+    pub fn queue_event_indexed<H>(
         &mut self,
         (new_time, new_event): (T, E),
         collision_decider: H,
-    ) -> Option<(T, E)>
+    ) -> (Option<usize>, Option<(T, E)>)
     where
         H: HandleEventCollision<E>,
         T: Ord,
@@ -122,7 +154,7 @@ impl<T, E> EventQueue<T, E> {
             if new_time > self.queue[0].0 {
                 result = self.queue.pop_front();
             } else {
-                return Some((new_time, new_event));
+                return (None, Some((new_time, new_event)));
             }
         } else {
             result = None;
@@ -139,7 +171,7 @@ impl<T, E> EventQueue<T, E> {
                 Ordering::Equal => {
                     match collision_decider.decide_on_collision(&read_event.1, &new_event) {
                         EventCollisionHandling::IgnoreNew => {
-                            return Some((new_time, new_event));
+                            return (None, Some((new_time, new_event)));
                         }
                         EventCollisionHandling::InsertNewBeforeOld => {
                             break;
@@ -149,7 +181,7 @@ impl<T, E> EventQueue<T, E> {
                         }
                         EventCollisionHandling::RemoveOld => {
                             std::mem::swap(&mut read_event.1, &mut new_event);
-                            return Some((new_time, new_event));
+                            return (Some(insert_index), Some((new_time, new_event)));
                         }
                     }
                 }
@@ -160,7 +192,54 @@ impl<T, E> EventQueue<T, E> {
         }
         self.queue.insert(insert_index, (new_time, new_event));
 
-        result
+        (Some(insert_index), result)
+    }
+
+    /// Queue a new event, treating every previously queued event that is at most `window`
+    /// frames before or after the new event as a collision.
+    ///
+    /// The collision decider is consulted for the first (earliest) colliding event only.
+    /// When it decides to insert the new event before or after the old event, the new event
+    /// is still inserted in time order; the decision only matters for events with the same timestamp.
+    /// When it decides to remove the old event, the old event is removed and returned.
+    /// When there is no collision, this behaves as [`queue_event`].
+    ///
+    /// With a `window` of `0`, only events with the same timestamp collide.
+    ///
+    /// [`queue_event`]: ./struct.EventQueue.html#method.queue_event
+    pub fn queue_event_within<H>(
+        &mut self,
+        (new_time, new_event): (T, E),
+        window: T,
+        collision_decider: H,
+    ) -> Option<(T, E)>
+    where
+        H: HandleEventCollision<E>,
+        T: Ord + Copy + SaturatingAdd,
+    {
+        let collision_index = self.queue.iter().position(|(old_time, _)| {
+            old_time.saturating_add(&window) >= new_time
+                && new_time.saturating_add(&window) >= *old_time
+        });
+        let collision_index = match collision_index {
+            Some(index) => index,
+            None => return self.queue_event((new_time, new_event), AlwaysInsertNewAfterOld),
+        };
+        match collision_decider.decide_on_collision(&self.queue[collision_index].1, &new_event) {
+            EventCollisionHandling::IgnoreNew => Some((new_time, new_event)),
+            EventCollisionHandling::InsertNewBeforeOld => {
+                self.queue_event((new_time, new_event), AlwaysInsertNewBeforeOld)
+            }
+            EventCollisionHandling::InsertNewAfterOld => {
+                self.queue_event((new_time, new_event), AlwaysInsertNewAfterOld)
+            }
+            EventCollisionHandling::RemoveOld => {
+                let old_event = self.queue.remove(collision_index);
+                // Removing the old event made some room, so nothing else is removed.
+                self.queue_event((new_time, new_event), AlwaysInsertNewAfterOld);
+                old_event
+            }
+        }
     }
 
     /// Remove all events before, but not on, this threshold.
@@ -233,11 +312,22 @@ impl<T, E> EventQueue<T, E> {
     }
 
     /// Create an iterator that drains all elements.
+    ///
+    /// The events are yielded in time order (events that were queued with the same timestamp
+    /// are yielded in the order that is the result of the collision handling when they were queued).
     pub fn drain_all(&mut self) -> DrainingIter<T, E> {
         DrainingIter {
             inner: self.queue.drain(0..),
         }
     }
+
+    /// Create an iterator that drains all elements in reverse time order, so the latest event
+    /// is yielded first.
+    ///
+    /// This is equivalent to `self.drain_all().rev()`.
+    pub fn drain_all_rev(&mut self) -> Rev<DrainingIter<T, E>> {
+        self.drain_all().rev()
+    }
 }
 
 impl<E, T> Deref for EventQueue<T, E> {
@@ -362,6 +452,100 @@ fn eventqueue_queue_event_with_always_insert_new_before_old() {
     assert_eq!(queue.queue, expected_buffer);
 }
 
+#[test]
+fn eventqueue_queue_event_indexed_returns_insert_index() {
+    let initial_buffer = vec![(4, 16), (6, 36), (7, 49)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_indexed((5, 25), AlwaysInsertNewAfterOld);
+
+    assert_eq!(result, (Some(1), None));
+    assert_eq!(queue.queue, vec![(4, 16), (5, 25), (6, 36), (7, 49)]);
+}
+
+#[test]
+fn eventqueue_queue_event_indexed_returns_insert_index_after_removing_first_when_full() {
+    let initial_buffer = vec![(4, 16), (6, 36), (7, 49)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    // Check our assumption:
+    assert_eq!(queue.queue.capacity(), queue.queue.len());
+
+    let result = queue.queue_event_indexed((8, 64), AlwaysInsertNewAfterOld);
+
+    assert_eq!(result, (Some(2), Some((4, 16))));
+    assert_eq!(queue.queue, vec![(6, 36), (7, 49), (8, 64)]);
+}
+
+#[test]
+fn eventqueue_queue_event_indexed_returns_no_index_when_new_event_is_ignored() {
+    let initial_buffer = vec![(4, 16), (6, 36), (7, 49)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_indexed((6, 25), AlwaysIgnoreNew);
+
+    assert_eq!(result, (None, Some((6, 25))));
+    assert_eq!(queue.queue, initial_buffer);
+}
+
+#[test]
+fn eventqueue_queue_event_indexed_returns_index_of_replaced_event() {
+    let initial_buffer = vec![(4, 16), (6, 36), (7, 49)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_indexed((6, 25), AlwaysRemoveOld);
+
+    assert_eq!(result, (Some(1), Some((6, 36))));
+    assert_eq!(queue.queue, vec![(4, 16), (6, 25), (7, 49)]);
+}
+
+#[test]
+fn eventqueue_queue_event_within_events_one_frame_apart_collide_with_window_of_two() {
+    let initial_buffer = vec![(4, 16), (6, 36), (8, 64)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_within((5, 25), 2, AlwaysIgnoreNew);
+
+    assert_eq!(result, Some((5, 25)));
+    assert_eq!(queue.queue, initial_buffer);
+}
+
+#[test]
+fn eventqueue_queue_event_within_events_one_frame_apart_do_not_collide_with_window_of_zero() {
+    let initial_buffer = vec![(4, 16), (6, 36), (8, 64)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_within((5, 25), 0, AlwaysIgnoreNew);
+
+    assert_eq!(result, None);
+    assert_eq!(queue.queue, vec![(4, 16), (5, 25), (6, 36), (8, 64)]);
+}
+
+#[test]
+fn eventqueue_queue_event_within_does_not_overflow_near_the_end_of_the_time_range() {
+    let mut queue: EventQueue<u32, u32> = EventQueue::new(4);
+    queue.queue_event((u32::MAX - 1, 1), AlwaysInsertNewAfterOld);
+    let result = queue.queue_event_within((u32::MAX, 2), 2, AlwaysIgnoreNew);
+    assert_eq!(result, Some((u32::MAX, 2)));
+    assert_eq!(queue.queue, vec![(u32::MAX - 1, 1)]);
+}
+
+#[test]
+fn eventqueue_queue_event_within_removes_colliding_old_event() {
+    let initial_buffer = vec![(4, 16), (8, 64)];
+    let mut queue = EventQueue::from_vec(initial_buffer.clone());
+    queue.queue.reserve(1);
+
+    let result = queue.queue_event_within((5, 25), 2, AlwaysRemoveOld);
+
+    assert_eq!(result, Some((4, 16)));
+    assert_eq!(queue.queue, vec![(5, 25), (8, 64)]);
+}
+
 #[test]
 fn eventqueue_forget_before() {
     let mut queue = EventQueue::from_vec({ vec![(4, 16), (6, 36), (7, 49), (8, 64)] });
@@ -376,6 +560,22 @@ fn eventqueue_forget_everything() {
     assert_eq!(queue.queue, Vec::new());
 }
 
+#[test]
+fn eventqueue_drain_all_yields_events_in_time_order() {
+    let mut queue = EventQueue::from_vec({ vec![(4, 16), (6, 36), (7, 49), (8, 64)] });
+    let drained: Vec<_> = queue.drain_all().collect();
+    assert_eq!(drained, vec![(4, 16), (6, 36), (7, 49), (8, 64)]);
+    assert!(queue.queue.is_empty());
+}
+
+#[test]
+fn eventqueue_drain_all_rev_yields_events_in_reverse_time_order() {
+    let mut queue = EventQueue::from_vec({ vec![(4, 16), (6, 36), (7, 49), (8, 64)] });
+    let drained: Vec<_> = queue.drain_all_rev().collect();
+    assert_eq!(drained, vec![(8, 64), (7, 49), (6, 36), (4, 16)]);
+    assert!(queue.queue.is_empty());
+}
+
 /// Draining iterator created by the [`EventQueue::drain`] method.
 pub struct DrainingIter<'a, T, E> {
     inner: Drain<'a, (T, E)>,
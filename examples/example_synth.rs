@@ -12,11 +12,10 @@ use polyphony::{
 };
 use rand::{thread_rng, Rng};
 use rsynth::event::{
-    ContextualEventHandler, EventHandler, Indexed, RawMidiEvent, SysExEvent, Timed,
+    ContextualEventHandler, EventHandler, Indexed, MidiMessage, RawMidiEvent, SysExEvent, Timed,
 };
 use rsynth::{AudioHandler, ContextualAudioRenderer};
 
-use midi_consts::channel_event::*;
 use rsynth::backend::HostInterface;
 use rsynth::buffer::AudioBufferInOut;
 use rsynth::meta::{InOut, Meta, MetaData};
@@ -29,6 +28,34 @@ static SAMPLE_SIZE: usize = 65536;
 static NUMBER_OF_VOICES: usize = 6;
 static AMPLIFY_MULTIPLIER: f32 = 1.0 / NUMBER_OF_VOICES as f32;
 
+// A seedable source of white noise, based on the "xorshift" random number generator.
+// Two `WhiteNoise`s that are created with the same seed generate the same noise,
+// so that the output of the synthesizer can be reproduced (e.g. in tests).
+pub struct WhiteNoise {
+    state: u32,
+}
+
+impl WhiteNoise {
+    pub fn with_seed(seed: u32) -> Self {
+        // The state of a xorshift generator must never be zero.
+        WhiteNoise {
+            state: if seed == 0 { 0x9E37_79B9 } else { seed },
+        }
+    }
+}
+
+impl Iterator for WhiteNoise {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        // We map the random number to the range -1 to 1.
+        Some(self.state as f32 / u32::MAX as f32 * 2.0 - 1.0)
+    }
+}
+
 // This struct defines the data that we will need to play one "noise"
 pub struct Noise {
     // Random data of the noise.
@@ -42,17 +69,8 @@ pub struct Noise {
 }
 
 impl Noise {
-    fn new(sample_size: usize) -> Self {
-        let mut rng = thread_rng();
-        let samples: Vec<f32> = rng
-            .gen_iter::<f32>()
-            .take(sample_size)
-            .map(|r| {
-                // The random generator generates noise between 0 and 1,
-                // we map it to the range -1 to 1.
-                2.0 * r - 1.0
-            })
-            .collect::<Vec<f32>>();
+    fn new(sample_size: usize, noise: WhiteNoise) -> Self {
+        let samples: Vec<f32> = noise.take(sample_size).collect();
         Noise {
             white_noise: samples,
             position: 0,
@@ -97,20 +115,16 @@ impl Voice<SimpleVoiceState<ToneIdentifier>> for Noise {
 
 impl EventHandler<Timed<RawMidiEvent>> for Noise {
     fn handle_event(&mut self, timed: Timed<RawMidiEvent>) {
-        let state_and_channel = timed.event.data()[0];
-
-        // We are digging into the details of midi-messages here.
-        // Alternatively, you could use the `wmidi` crate.
-        let mut is_note_off_event = state_and_channel & EVENT_TYPE_MASK == NOTE_OFF;
-        if state_and_channel & EVENT_TYPE_MASK == NOTE_ON {
-            let velocity = timed.event.data()[2];
-            if velocity != 0 {
+        let is_note_off_event = match timed.event.parse() {
+            Some(MidiMessage::NoteOn { note, velocity, .. }) if velocity != 0 => {
                 self.amplitude = velocity as f32 / 127.0 * AMPLIFY_MULTIPLIER;
-                self.state = SimpleVoiceState::Active(ToneIdentifier(timed.event.data()[1]));
-            } else {
-                is_note_off_event = true;
+                self.state = SimpleVoiceState::Active(ToneIdentifier(note));
+                false
             }
-        }
+            // A note on event with velocity 0 is treated as a note off event.
+            Some(MidiMessage::NoteOn { .. }) | Some(MidiMessage::NoteOff { .. }) => true,
+            _ => false,
+        };
         if is_note_off_event {
             self.amplitude = 0.0;
             self.state = SimpleVoiceState::Idle;
@@ -139,9 +153,17 @@ impl NoisePlayer {
     }
 
     pub fn new() -> Self {
+        Self::with_seed(thread_rng().gen())
+    }
+
+    // Create a `NoisePlayer` that generates noise based on the given seed.
+    // Two `NoisePlayer`s with the same seed render the same audio.
+    pub fn with_seed(seed: u32) -> Self {
         let mut voices = Vec::new();
-        for _ in 0..NUMBER_OF_VOICES {
-            voices.push(Noise::new(SAMPLE_SIZE));
+        for voice_index in 0..NUMBER_OF_VOICES {
+            // Every voice gets its own seed, so that the voices do not all sound the same.
+            let voice_seed = seed.wrapping_add(voice_index as u32);
+            voices.push(Noise::new(SAMPLE_SIZE, WhiteNoise::with_seed(voice_seed)));
         }
         Self {
             meta_data: Self::meta_data(),
@@ -174,7 +196,7 @@ where
     fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>, context: &mut Context) {
         if !context.output_initialized() {
             // Initialize the output buffer.
-            buffer.outputs().set(S::zero());
+            buffer.outputs().silence();
         }
         for noise in self.voices.iter_mut() {
             noise.render_audio_buffer(buffer);
@@ -217,3 +239,35 @@ impl<'a, Context> ContextualEventHandler<Indexed<Timed<SysExEvent<'a>>>, Context
         // We don't do anything with SysEx events
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NoisePlayer;
+    use midi_consts::channel_event::NOTE_ON;
+    use rsynth::backend::HostInterface;
+    use rsynth::buffer::{AudioBufferInOut, AudioChunk};
+    use rsynth::event::{EventHandler, RawMidiEvent, Timed};
+    use rsynth::ContextualAudioRenderer;
+
+    struct TestHost;
+    impl HostInterface for TestHost {}
+
+    fn render(player: &mut NoisePlayer) -> AudioChunk<f32> {
+        player.handle_event(Timed::new(0, RawMidiEvent::new(&[NOTE_ON, 69, 100])));
+        let mut output = AudioChunk::<f32>::zero(2, 64);
+        {
+            let mut outputs = output.as_mut_slices();
+            let mut buffer = AudioBufferInOut::new(&[], &mut outputs, 64);
+            player.render_buffer(&mut buffer, &mut TestHost);
+        }
+        output
+    }
+
+    #[test]
+    fn noise_players_with_the_same_seed_render_the_same_audio() {
+        let output1 = render(&mut NoisePlayer::with_seed(1234));
+        let output2 = render(&mut NoisePlayer::with_seed(1234));
+        assert!(output1.channels()[0].iter().any(|s| *s != 0.0));
+        assert_eq!(output1, output2);
+    }
+}
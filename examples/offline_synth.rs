@@ -24,7 +24,7 @@ mod example_synth;
 use example_synth::*;
 
 #[cfg(feature = "backend-combined")]
-use rsynth::backend::combined::dummy::{AudioDummy, MidiDummy};
+use rsynth::backend::combined::dummy::AudioDummy;
 #[cfg(feature = "backend-combined-wav-0-6")]
 use rsynth::backend::combined::memory::wav_0_6::{read, write, BitDepth, Header};
 #[cfg(feature = "backend-combined")]
@@ -32,7 +32,7 @@ use rsynth::backend::combined::memory::AudioBufferWriter;
 #[cfg(feature = "backend-combined-midly-0-5")]
 use rsynth::backend::combined::midly::{midly_0_5::Smf, MidlyMidiReader};
 #[cfg(feature = "backend-combined")]
-use rsynth::backend::combined::run;
+use rsynth::backend::combined::{run, RunConfig};
 use rsynth::buffer::AudioChunk;
 use std::fs::OpenOptions;
 use std::{env, fs};
@@ -63,15 +63,12 @@ fn main() {
             number_of_seconds * samplerate as usize,
         );
         let midi_event_reader = MidlyMidiReader::new(&smf).unwrap();
-        let midi_out = MidiDummy::new();
         println!("Rendering {} tracks of audio.", number_of_seconds);
         run(
             &mut plugin,
-            buffer_size_in_frames,
-            audio_in,
-            audio_buffer_writer,
-            midi_event_reader,
-            midi_out,
+            RunConfig::new(audio_in, audio_buffer_writer)
+                .with_buffer_size(buffer_size_in_frames)
+                .with_midi_in(midi_event_reader),
         )
         .unwrap();
 
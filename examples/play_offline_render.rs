@@ -0,0 +1,67 @@
+// An example that renders a note of the example synthesizer offline and plays the result
+// on the default audio output device, without writing it to a file first.
+// The code of the synthesizer is in the `example_synth.rs` file.
+//
+// Compiling
+// =========
+// You can compile this example with
+// ```bash
+// cargo build --release --example play_offline_render --features backend-cpal
+// ```
+// This generates a standalone application that you can find
+//
+// * in `target/release/examples/play_offline_render` when you're using Linux
+// * under the `target/release/examples/` folder when you're using Windows or MacOs
+//
+#[macro_use]
+extern crate log;
+extern crate num_traits;
+extern crate rand;
+extern crate rsynth;
+
+mod example_synth;
+use example_synth::*;
+
+use rsynth::backend::combined::cpal::CpalPlayer;
+use rsynth::backend::combined::dummy::AudioDummy;
+use rsynth::backend::combined::memory::{AudioBufferWriter, AudioChunkReader};
+use rsynth::backend::combined::{run, RunConfig};
+use rsynth::buffer::AudioChunk;
+use rsynth::event::{DeltaEvent, RawMidiEvent};
+
+fn main() {
+    let samplerate = 44100;
+    let number_of_seconds = 2;
+    let mut output_buffer = AudioChunk::<f32>::new(2);
+    let mut plugin = NoisePlayer::new();
+    // A note that starts immediately and stops after one and a half seconds.
+    let events = vec![
+        DeltaEvent {
+            microseconds_since_previous_event: 0,
+            event: RawMidiEvent::new(&[0x90, 60, 100]),
+        },
+        DeltaEvent {
+            microseconds_since_previous_event: 1_500_000,
+            event: RawMidiEvent::new(&[0x80, 60, 0]),
+        },
+    ];
+
+    println!("Rendering {} seconds of audio.", number_of_seconds);
+    run(
+        &mut plugin,
+        RunConfig::new(
+            AudioDummy::with_sample_rate_and_length(
+                samplerate,
+                number_of_seconds * samplerate as usize,
+            ),
+            AudioBufferWriter::new(&mut output_buffer),
+        )
+        .with_midi_in(events.into_iter()),
+    )
+    .unwrap();
+
+    println!("Playing the rendered audio.");
+    let player = CpalPlayer::play(AudioChunkReader::new(output_buffer, samplerate as u64))
+        .expect("Could not play the rendered audio.");
+    player.wait();
+}
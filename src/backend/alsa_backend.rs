@@ -0,0 +1,342 @@
+//! Real-time audio output and midi input with [ALSA] on Linux (behind the `backend-alsa` feature).
+//!
+//! This backend does not need a running JACK server.
+//! It opens an ALSA PCM device for audio output and, optionally, an ALSA rawmidi device
+//! for midi input. There is no audio input and no midi output.
+//!
+//! # Usage
+//! See the documentation of the [`run`] function.
+//!
+//! [ALSA]: https://www.alsa-project.org/
+//! [`run`]: ./fn.run.html
+use crate::backend::{HostInterface, Stop};
+use crate::buffer::BufferStorage;
+use crate::event::{ContextualEventHandler, RawMidiEvent, Timed};
+use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+use std::io::{ErrorKind, Read};
+use std::iter;
+
+/// Re-exports of the [`alsa`](https://crates.io/crates/alsa) crate.
+/// Use this so that your code doesn't break when `rsynth` upgrades its dependency on `alsa`.
+pub mod alsa {
+    pub use alsa::*;
+}
+
+use self::alsa::pcm::{Access, Format, HwParams, PCM};
+use self::alsa::rawmidi::Rawmidi;
+use self::alsa::{Direction, ValueOr};
+
+// The size of the buffer for reading bytes from the rawmidi device.
+const MIDI_READ_BUFFER_SIZE: usize = 256;
+
+/// The configuration for the ALSA backend, see [`run`].
+///
+/// [`run`]: ./fn.run.html
+#[derive(Debug, Clone)]
+pub struct AlsaConfig {
+    pcm_device: String,
+    midi_device: Option<String>,
+    sample_rate: u32,
+}
+
+impl AlsaConfig {
+    /// Create a new `AlsaConfig` that uses the "default" PCM device at 44100 Hz and no midi input.
+    pub fn new() -> Self {
+        Self {
+            pcm_device: "default".to_string(),
+            midi_device: None,
+            sample_rate: 44100,
+        }
+    }
+
+    /// Use the given PCM device (e.g. `"hw:0,0"`) for audio output.
+    pub fn with_pcm_device(self, pcm_device: &str) -> Self {
+        Self {
+            pcm_device: pcm_device.to_string(),
+            ..self
+        }
+    }
+
+    /// Use the given rawmidi device (e.g. `"hw:1,0,0"`) for midi input.
+    pub fn with_midi_device(self, midi_device: &str) -> Self {
+        Self {
+            midi_device: Some(midi_device.to_string()),
+            ..self
+        }
+    }
+
+    /// Request the given sample rate. The device may use a sample rate close to it;
+    /// the actual sample rate is passed to the plugin with `set_sample_rate`.
+    pub fn with_sample_rate(self, sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            ..self
+        }
+    }
+}
+
+impl Default for AlsaConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Used to communicate with the ALSA backend from within the plugin.
+///
+/// `AlsaHost` implements [`Stop`]: when the plugin calls `stop()`, the [`run`] function returns
+/// after the current buffer.
+///
+/// [`Stop`]: ../trait.Stop.html
+/// [`run`]: ./fn.run.html
+pub struct AlsaHost {
+    stop_requested: bool,
+}
+
+impl HostInterface for AlsaHost {
+    fn stop(&mut self) {
+        self.stop_requested = true;
+    }
+}
+
+impl Stop for AlsaHost {}
+
+/// Splits a stream of midi bytes (as read from a rawmidi device) into [`RawMidiEvent`]s.
+///
+/// Running status is supported. System exclusive messages are skipped.
+///
+/// [`RawMidiEvent`]: ../../event/struct.RawMidiEvent.html
+pub struct MidiByteParser {
+    running_status: Option<u8>,
+    data: [u8; 2],
+    number_of_data_bytes: usize,
+    in_system_exclusive: bool,
+}
+
+impl MidiByteParser {
+    /// Create a new `MidiByteParser`.
+    pub fn new() -> Self {
+        Self {
+            running_status: None,
+            data: [0; 2],
+            number_of_data_bytes: 0,
+            in_system_exclusive: false,
+        }
+    }
+
+    // The number of data bytes that follow the given status byte.
+    fn expected_number_of_data_bytes(status: u8) -> usize {
+        match status {
+            0xC0..=0xDF => 1,
+            0x80..=0xEF => 2,
+            0xF1 | 0xF3 => 1,
+            0xF2 => 2,
+            _ => 0,
+        }
+    }
+
+    /// Parse the next byte and return the event if the byte completes one.
+    pub fn parse(&mut self, byte: u8) -> Option<RawMidiEvent> {
+        match byte {
+            // System real-time messages can be interleaved with other messages.
+            0xF8..=0xFF => Some(RawMidiEvent::new(&[byte])),
+            0xF0 => {
+                self.in_system_exclusive = true;
+                self.running_status = None;
+                None
+            }
+            0xF7 => {
+                self.in_system_exclusive = false;
+                None
+            }
+            0x80..=0xF6 => {
+                self.in_system_exclusive = false;
+                self.number_of_data_bytes = 0;
+                if byte >= 0xF0 {
+                    // System common messages cancel the running status.
+                    self.running_status = None;
+                    if Self::expected_number_of_data_bytes(byte) == 0 {
+                        return Some(RawMidiEvent::new(&[byte]));
+                    }
+                }
+                self.running_status = Some(byte);
+                None
+            }
+            _ => {
+                if self.in_system_exclusive {
+                    return None;
+                }
+                let status = self.running_status?;
+                self.data[self.number_of_data_bytes] = byte;
+                self.number_of_data_bytes += 1;
+                let expected = Self::expected_number_of_data_bytes(status);
+                if self.number_of_data_bytes < expected {
+                    return None;
+                }
+                self.number_of_data_bytes = 0;
+                if status >= 0xF0 {
+                    self.running_status = None;
+                }
+                Some(match expected {
+                    1 => RawMidiEvent::new(&[status, self.data[0]]),
+                    _ => RawMidiEvent::new(&[status, self.data[0], self.data[1]]),
+                })
+            }
+        }
+    }
+}
+
+impl Default for MidiByteParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn open_pcm(
+    config: &AlsaConfig,
+    number_of_channels: usize,
+) -> Result<(PCM, u32, usize), alsa::Error> {
+    let pcm = PCM::new(&config.pcm_device, Direction::Playback, false)?;
+    let (sample_rate, period_size) = {
+        let hw_params = HwParams::any(&pcm)?;
+        hw_params.set_channels(number_of_channels as u32)?;
+        hw_params.set_rate(config.sample_rate, ValueOr::Nearest)?;
+        hw_params.set_format(Format::float())?;
+        hw_params.set_access(Access::RWInterleaved)?;
+        pcm.hw_params(&hw_params)?;
+        (hw_params.get_rate()?, hw_params.get_period_size()? as usize)
+    };
+    Ok((pcm, sample_rate, period_size))
+}
+
+/// Run the plugin until it calls `stop()` on the [`AlsaHost`].
+///
+/// The number of output channels is the maximum number of audio outputs of the plugin
+/// (see [`AudioHandlerMeta`]). The sample rate is passed to the plugin with
+/// [`AudioHandler::set_sample_rate`] before the output starts.
+///
+/// Midi events are read from the rawmidi device (if any) before each buffer is rendered.
+/// ALSA rawmidi does not provide timestamps, so all events get time `0` in the buffer.
+///
+/// Panics
+/// ======
+/// Panics if the plugin has no audio outputs.
+///
+/// [`AlsaHost`]: ./struct.AlsaHost.html
+/// [`AudioHandlerMeta`]: ../../trait.AudioHandlerMeta.html
+/// [`AudioHandler::set_sample_rate`]: ../../trait.AudioHandler.html#tymethod.set_sample_rate
+pub fn run<P>(mut plugin: P, config: AlsaConfig) -> Result<P, alsa::Error>
+where
+    P: AudioHandler
+        + AudioHandlerMeta
+        + ContextualAudioRenderer<f32, AlsaHost>
+        + ContextualEventHandler<Timed<RawMidiEvent>, AlsaHost>,
+{
+    let number_of_channels = plugin.max_number_of_audio_outputs();
+    assert!(number_of_channels > 0);
+    let (pcm, sample_rate, period_size) = open_pcm(&config, number_of_channels)?;
+    plugin.set_sample_rate(sample_rate as f64);
+    let io = pcm.io_f32()?;
+
+    let rawmidi = match config.midi_device {
+        Some(ref midi_device) => Some(Rawmidi::new(midi_device, Direction::Capture, true)?),
+        None => None,
+    };
+    let mut midi_parser = MidiByteParser::new();
+    let mut midi_bytes = [0; MIDI_READ_BUFFER_SIZE];
+
+    let mut buffers = vec![vec![0.0; period_size]; number_of_channels];
+    let mut storage = BufferStorage::new(0, number_of_channels);
+    let mut interleaved = vec![0.0; period_size * number_of_channels];
+    let mut host = AlsaHost {
+        stop_requested: false,
+    };
+
+    while !host.stop_requested {
+        if let Some(ref rawmidi) = rawmidi {
+            loop {
+                match rawmidi.io().read(&mut midi_bytes) {
+                    Ok(0) => break,
+                    Ok(number_of_bytes) => {
+                        for byte in midi_bytes[..number_of_bytes].iter() {
+                            if let Some(event) = midi_parser.parse(*byte) {
+                                plugin.handle_event(Timed::new(0, event), &mut host);
+                            }
+                        }
+                    }
+                    Err(ref e) if e.kind() == ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        error!("Could not read midi: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        storage.with_buffers(
+            iter::empty(),
+            buffers.iter_mut().map(|buffer| buffer.as_mut_slice()),
+            period_size,
+            |buffer| plugin.render_buffer(buffer, &mut host),
+        );
+        for (frame_index, frame) in interleaved.chunks_mut(number_of_channels).enumerate() {
+            for (sample, buffer) in frame.iter_mut().zip(buffers.iter()) {
+                *sample = buffer[frame_index];
+            }
+        }
+
+        if let Err(e) = io.writei(&interleaved) {
+            // E.g. a buffer underrun. Try to recover, so that the next buffer can be written.
+            warn!("Could not write audio: {}", e);
+            pcm.try_recover(e, true)?;
+        }
+    }
+    pcm.drain()?;
+    Ok(plugin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MidiByteParser;
+    use crate::event::RawMidiEvent;
+
+    fn parse(bytes: &[u8]) -> Vec<RawMidiEvent> {
+        let mut parser = MidiByteParser::new();
+        bytes
+            .iter()
+            .filter_map(|byte| parser.parse(*byte))
+            .collect()
+    }
+
+    #[test]
+    fn bytes_are_split_into_events() {
+        assert_eq!(
+            parse(&[0x90, 60, 100, 0xC0, 5, 0x80, 60, 0]),
+            vec![
+                RawMidiEvent::new(&[0x90, 60, 100]),
+                RawMidiEvent::new(&[0xC0, 5]),
+                RawMidiEvent::new(&[0x80, 60, 0])
+            ]
+        );
+    }
+
+    #[test]
+    fn running_status_is_supported() {
+        assert_eq!(
+            parse(&[0x90, 60, 100, 62, 90, 0xF8, 64, 80]),
+            vec![
+                RawMidiEvent::new(&[0x90, 60, 100]),
+                RawMidiEvent::new(&[0x90, 62, 90]),
+                RawMidiEvent::new(&[0xF8]),
+                RawMidiEvent::new(&[0x90, 64, 80])
+            ]
+        );
+    }
+
+    #[test]
+    fn system_exclusive_messages_are_skipped() {
+        assert_eq!(
+            parse(&[0xF0, 1, 2, 3, 0xF7, 0xB0, 7, 100]),
+            vec![RawMidiEvent::new(&[0xB0, 7, 100])]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Play audio to the default output device, based on the `cpal` crate.
+//! This is mostly useful for listening to the output of offline rendering without
+//! writing it to a file first.
+use super::AudioReader;
+use crate::buffer::{buffers_as_mut_slice, AudioBufferOut};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Re-exports from the `cpal` crate (version range 0.13.x).
+pub mod cpal_0_13 {
+    pub use cpal_0_13::*;
+}
+
+use self::cpal_0_13::traits::{DeviceTrait, HostTrait, StreamTrait};
+use self::cpal_0_13::{
+    BufferSize, BuildStreamError, DefaultStreamConfigError, OutputCallbackInfo, PlayStreamError,
+    SampleRate, Stream, StreamConfig,
+};
+
+const POLLING_INTERVAL_IN_MILLISECONDS: u64 = 10;
+
+/// The error type that represents the errors you can get when starting to play audio.
+#[derive(Debug)]
+pub enum CpalError {
+    /// There is no default output device.
+    NoOutputDevice,
+    /// The default configuration of the output device could not be retrieved.
+    DefaultStreamConfigError(DefaultStreamConfigError),
+    /// The output stream could not be created, e.g. because the device does not support
+    /// the number of channels or the sample rate of the audio.
+    BuildStreamError(BuildStreamError),
+    /// The output stream could not be started.
+    PlayStreamError(PlayStreamError),
+}
+
+impl Display for CpalError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            CpalError::NoOutputDevice => write!(f, "No default output device"),
+            CpalError::DefaultStreamConfigError(ref e) => {
+                write!(f, "Could not get the default output configuration: {}", e)
+            }
+            CpalError::BuildStreamError(ref e) => write!(f, "Could not build stream: {}", e),
+            CpalError::PlayStreamError(ref e) => write!(f, "Could not play stream: {}", e),
+        }
+    }
+}
+
+impl Error for CpalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CpalError::NoOutputDevice => None,
+            CpalError::DefaultStreamConfigError(ref e) => Some(e),
+            CpalError::BuildStreamError(ref e) => Some(e),
+            CpalError::PlayStreamError(ref e) => Some(e),
+        }
+    }
+}
+
+/// Plays the audio from an [`AudioReader`] to the default output device.
+///
+/// Playing starts when the `CpalPlayer` is created and stops when it is dropped
+/// or when the [`AudioReader`] has no more frames.
+///
+/// Example
+/// -------
+/// ```no_run
+/// use rsynth::audio_chunk;
+/// use rsynth::backend::combined::cpal::CpalPlayer;
+/// use rsynth::backend::combined::memory::AudioChunkReader;
+/// let chunk = audio_chunk![[0.0f32, 0.5, 0.0, -0.5]];
+/// let player = CpalPlayer::play(AudioChunkReader::new(chunk, 44100))
+///     .expect("Could not play audio.");
+/// player.wait();
+/// ```
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Reading the audio in the audio thread may allocate memory.
+///
+/// [`AudioReader`]: ../trait.AudioReader.html
+pub struct CpalPlayer {
+    // Playing stops when the stream is dropped.
+    _stream: Stream,
+    finished: Arc<AtomicBool>,
+}
+
+impl CpalPlayer {
+    /// Start playing the audio from the given reader on the default output device.
+    ///
+    /// The output stream uses the number of channels and the sample rate of the reader,
+    /// no conversion is done.
+    /// Errors that occur while reading are logged and stop the playing.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if the reader has no channels.
+    pub fn play<R>(reader: R) -> Result<Self, CpalError>
+    where
+        R: AudioReader<f32> + Send + 'static,
+        R::Err: Debug,
+    {
+        let number_of_channels = reader.number_of_channels();
+        assert!(number_of_channels > 0);
+        let config = StreamConfig {
+            channels: number_of_channels as u16,
+            sample_rate: SampleRate(reader.frames_per_second() as u32),
+            buffer_size: BufferSize::Default,
+        };
+        let device = cpal_0_13::default_host()
+            .default_output_device()
+            .ok_or(CpalError::NoOutputDevice)?;
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let finished_in_callback = finished.clone();
+        let mut reader = reader;
+        let mut buffers: Vec<Vec<f32>> = vec![Vec::new(); number_of_channels];
+        let stream = device
+            .build_output_stream(
+                &config,
+                move |data: &mut [f32], _: &OutputCallbackInfo| {
+                    let number_of_frames = data.len() / number_of_channels;
+                    let frames_read = if finished_in_callback.load(Ordering::Acquire) {
+                        0
+                    } else {
+                        for buffer in buffers.iter_mut() {
+                            buffer.resize(number_of_frames, 0.0);
+                        }
+                        let mut slices = buffers_as_mut_slice(&mut buffers, number_of_frames);
+                        let mut buffer = AudioBufferOut::new(&mut slices, number_of_frames);
+                        match reader.fill_buffer(&mut buffer) {
+                            Ok(frames_read) => frames_read,
+                            Err(e) => {
+                                error!("Error while reading audio: {:?}", e);
+                                0
+                            }
+                        }
+                    };
+                    if frames_read < number_of_frames {
+                        finished_in_callback.store(true, Ordering::Release);
+                    }
+                    for (frame_index, frame) in data.chunks_mut(number_of_channels).enumerate() {
+                        for (sample, buffer) in frame.iter_mut().zip(buffers.iter()) {
+                            *sample = if frame_index < frames_read {
+                                buffer[frame_index]
+                            } else {
+                                0.0
+                            };
+                        }
+                    }
+                },
+                |e| error!("Error in the output stream: {}", e),
+            )
+            .map_err(CpalError::BuildStreamError)?;
+        stream.play().map_err(CpalError::PlayStreamError)?;
+        Ok(Self {
+            _stream: stream,
+            finished,
+        })
+    }
+
+    /// Return `true` if all audio has been played.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Block the current thread until all audio has been played.
+    pub fn wait(&self) {
+        while !self.is_finished() {
+            thread::sleep(Duration::from_millis(POLLING_INTERVAL_IN_MILLISECONDS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpalError, CpalPlayer};
+    use crate::backend::combined::memory::AudioChunkReader;
+    use crate::buffer::AudioChunk;
+
+    #[test]
+    #[ignore] // Needs an audio output device.
+    fn starting_to_play_succeeds_when_there_is_an_output_device() {
+        let reader = AudioChunkReader::new(AudioChunk::<f32>::zero(2, 441), 44100);
+        match CpalPlayer::play(reader) {
+            Ok(player) => {
+                let _ = player.is_finished();
+            }
+            Err(CpalError::NoOutputDevice) => {}
+            Err(e) => panic!("Could not play audio: {}", e),
+        }
+    }
+}
@@ -1,62 +1,71 @@
 //! Backend for reading and writing `.wav` files, based on the `hound` crate.
-use super::{AudioReader, AudioWriter};
+use super::{AudioReader, AudioWriter, SeekError};
 use crate::buffer::{AudioBufferIn, AudioBufferOut};
 use dasp_sample::conv::{FromSample, ToSample};
-use hound::{WavReader, WavSamples, WavWriter};
+use dasp_sample::I24;
+use hound::{WavReader, WavSpec, WavWriter};
+use std::borrow::BorrowMut;
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
-use std::io::{Read, Seek, Write};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::marker::PhantomData;
 
 pub struct HoundAudioReader<'wr, S>
 where
-    S: FromSample<f32> + FromSample<i32> + FromSample<i16>,
+    S: FromSample<f32> + FromSample<i32> + FromSample<I24> + FromSample<i16> + FromSample<i8>,
 {
     hound_sample_reader: Box<dyn HoundSampleReader<S> + 'wr>,
     number_of_channels: usize,
     frames_per_second: u64,
+    spec: WavSpec,
 }
 
 #[derive(Debug)]
 pub enum HoundAudioError {
     UnsupportedAudioFormat,
+    /// The `.wav` data could not be read.
+    HoundError(hound::Error),
 }
 
 impl Display for HoundAudioError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "Unsupported audio format")
+        match self {
+            HoundAudioError::UnsupportedAudioFormat => write!(f, "Unsupported audio format"),
+            HoundAudioError::HoundError(e) => write!(f, "Error reading the .wav data: {}", e),
+        }
     }
 }
 
 impl Error for HoundAudioError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        None
+        match self {
+            HoundAudioError::UnsupportedAudioFormat => None,
+            HoundAudioError::HoundError(e) => Some(e),
+        }
     }
 }
 
 impl<'wr, S> HoundAudioReader<'wr, S>
 where
-    S: FromSample<f32> + FromSample<i32> + FromSample<i16>,
+    S: FromSample<f32> + FromSample<i32> + FromSample<I24> + FromSample<i16> + FromSample<i8>,
 {
-    fn reader<R: Read>(
-        r: &'wr mut WavReader<R>,
+    fn reader<Src: HoundSource + 'wr>(
+        spec: WavSpec,
+        source: Src,
     ) -> Result<Box<dyn HoundSampleReader<S> + 'wr>, HoundAudioError> {
-        let spec = r.spec();
         Ok(match spec.sample_format {
             hound::SampleFormat::Float => match spec.bits_per_sample {
-                32 => Box::new(F32SampleReader {
-                    samples: r.samples(),
-                }),
+                32 => Box::new(F32SampleReader { source }),
                 _ => {
                     return Err(HoundAudioError::UnsupportedAudioFormat);
                 }
             },
             hound::SampleFormat::Int => match spec.bits_per_sample {
-                24 | 32 => Box::new(I32SampleReader {
-                    samples: r.samples(),
-                }),
-                8 | 16 => Box::new(I16SampleReader {
-                    samples: r.samples(),
-                }),
+                32 => Box::new(I32SampleReader { source }),
+                24 => Box::new(I24SampleReader { source }),
+                16 => Box::new(I16SampleReader { source }),
+                8 => Box::new(I8SampleReader { source }),
                 _ => {
                     // Note: until 3.4.0, Hound only supports 8, 16, 24, 32 bits/sample.
                     // Something else (e.g. 12 bits) would result in an error at runtime,
@@ -67,22 +76,86 @@ where
         })
     }
 
-    pub fn new<R: Read>(reader: &'wr mut WavReader<R>) -> Result<Self, HoundAudioError> {
-        let spec = reader.spec();
-
-        let number_of_channels = spec.channels as usize;
-        let hound_sample_reader = Self::reader(reader)?;
+    fn from_spec_and_source<Src: HoundSource + 'wr>(
+        spec: WavSpec,
+        source: Src,
+    ) -> Result<Self, HoundAudioError> {
         Ok(Self {
-            number_of_channels,
+            number_of_channels: spec.channels as usize,
             frames_per_second: spec.sample_rate as u64,
-            hound_sample_reader,
+            hound_sample_reader: Self::reader(spec, source)?,
+            spec,
         })
     }
+
+    /// The format of the `.wav` data that is read.
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// Create a `WavWriter` that writes `.wav` data with the same format as the data that
+    /// is read (number of channels, sample rate, bits per sample and sample format).
+    ///
+    /// Use this e.g. when rendering an effect offline, so that the output file has the same
+    /// format as the input file.
+    /// Wrap the `WavWriter` in a [`HoundAudioWriter`] to use it as an [`AudioWriter`].
+    ///
+    /// [`HoundAudioWriter`]: ./struct.HoundAudioWriter.html
+    /// [`AudioWriter`]: ../trait.AudioWriter.html
+    pub fn matching_wav_writer<W: Write + Seek>(
+        &self,
+        writer: W,
+    ) -> Result<WavWriter<W>, hound::Error> {
+        WavWriter::new(writer, self.spec)
+    }
+
+    /// Create a new `HoundAudioReader` that reads from the given `WavReader`.
+    ///
+    /// _Note_: a `HoundAudioReader` created with this method does not support seeking,
+    /// use [`new_seekable`] if the underlying reader implements `Seek`.
+    ///
+    /// [`new_seekable`]: #method.new_seekable
+    pub fn new<R: Read + 'wr>(reader: &'wr mut WavReader<R>) -> Result<Self, HoundAudioError> {
+        Self::from_spec_and_source(reader.spec(), Sequential::new(reader))
+    }
+
+    /// Create a new `HoundAudioReader` that reads from the given `WavReader` and that
+    /// supports seeking.
+    pub fn new_seekable<R: Read + Seek + 'wr>(
+        reader: &'wr mut WavReader<R>,
+    ) -> Result<Self, HoundAudioError> {
+        Self::from_spec_and_source(reader.spec(), Seekable::new(reader))
+    }
+
+    /// Create a new `HoundAudioReader` that takes ownership of the given `WavReader`
+    /// and that supports seeking.
+    pub fn from_wav_reader<R: Read + Seek + 'wr>(
+        reader: WavReader<R>,
+    ) -> Result<Self, HoundAudioError> {
+        Self::from_spec_and_source(reader.spec(), Seekable::new(reader))
+    }
+
+    /// Create a new `HoundAudioReader` that reads the `.wav` data from memory,
+    /// e.g. data that is embedded with `include_bytes!`.
+    ///
+    /// Errors
+    /// ======
+    /// Returns `HoundAudioError::HoundError` if the data is not valid `.wav` data and
+    /// `HoundAudioError::UnsupportedAudioFormat` if the audio format is not supported.
+    pub fn from_bytes(bytes: &'wr [u8]) -> Result<Self, HoundAudioError> {
+        let reader = WavReader::new(Cursor::new(bytes)).map_err(HoundAudioError::HoundError)?;
+        Self::from_wav_reader(reader)
+    }
 }
 
 impl<'wr, S> AudioReader<S> for HoundAudioReader<'wr, S>
 where
-    S: Copy + FromSample<f32> + FromSample<i32> + FromSample<i16>,
+    S: Copy
+        + FromSample<f32>
+        + FromSample<i32>
+        + FromSample<I24>
+        + FromSample<i16>
+        + FromSample<i8>,
 {
     type Err = hound::Error;
 
@@ -110,66 +183,216 @@ where
         }
         Ok(frame_index)
     }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        // `hound` computes the index of the sample as a `u32`, so this must not overflow.
+        let number_of_channels = self.spec.channels as u32;
+        match u32::try_from(frame)
+            .ok()
+            .filter(|frame| frame.checked_mul(number_of_channels).is_some())
+        {
+            Some(frame) => self.hound_sample_reader.seek(frame),
+            None => Err(SeekError::ReaderError(hound::Error::IoError(
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "frame index does not fit in a .wav file",
+                ),
+            ))),
+        }
+    }
+}
+
+// A borrowed or an owned `WavReader`, from which hound can read samples.
+trait HoundSource {
+    type Reader: Read;
+    fn wav_reader(&mut self) -> &mut WavReader<Self::Reader>;
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>>;
+}
+
+// A `WavReader` that does not support seeking.
+struct Sequential<W, R> {
+    reader: W,
+    _phantom: PhantomData<R>,
+}
+
+impl<W, R> Sequential<W, R> {
+    fn new(reader: W) -> Self {
+        Self {
+            reader,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W, R> HoundSource for Sequential<W, R>
+where
+    W: BorrowMut<WavReader<R>>,
+    R: Read,
+{
+    type Reader = R;
+
+    fn wav_reader(&mut self) -> &mut WavReader<R> {
+        self.reader.borrow_mut()
+    }
+
+    fn seek(&mut self, _frame: u32) -> Result<(), SeekError<hound::Error>> {
+        Err(SeekError::Unsupported)
+    }
+}
+
+// A `WavReader` that supports seeking.
+struct Seekable<W, R> {
+    reader: W,
+    _phantom: PhantomData<R>,
+}
+
+impl<W, R> Seekable<W, R> {
+    fn new(reader: W) -> Self {
+        Self {
+            reader,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<W, R> HoundSource for Seekable<W, R>
+where
+    W: BorrowMut<WavReader<R>>,
+    R: Read + Seek,
+{
+    type Reader = R;
+
+    fn wav_reader(&mut self) -> &mut WavReader<R> {
+        self.reader.borrow_mut()
+    }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.reader
+            .borrow_mut()
+            .seek(frame)
+            .map_err(|e| SeekError::ReaderError(hound::Error::IoError(e)))
+    }
 }
 
 trait HoundSampleReader<S> {
     fn read_sample(&mut self) -> Result<Option<S>, hound::Error>;
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>>;
 }
 
-struct F32SampleReader<'wr, R: Read> {
-    samples: WavSamples<'wr, R, f32>,
+struct F32SampleReader<Src> {
+    source: Src,
 }
 
-impl<'wr, R: Read, S> HoundSampleReader<S> for F32SampleReader<'wr, R>
+impl<Src, S> HoundSampleReader<S> for F32SampleReader<Src>
 where
+    Src: HoundSource,
     S: FromSample<f32>,
 {
     fn read_sample(&mut self) -> Result<Option<S>, hound::Error> {
-        if let Some(n) = self.samples.next() {
+        if let Some(n) = self.source.wav_reader().samples::<f32>().next() {
             Ok(Some(S::from_sample_(n?)))
         } else {
             Ok(None)
         }
     }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.source.seek(frame)
+    }
 }
 
-struct I32SampleReader<'wr, R: Read> {
-    samples: WavSamples<'wr, R, i32>,
+struct I32SampleReader<Src> {
+    source: Src,
 }
 
-impl<'wr, R: Read, S> HoundSampleReader<S> for I32SampleReader<'wr, R>
+impl<Src, S> HoundSampleReader<S> for I32SampleReader<Src>
 where
+    Src: HoundSource,
     S: FromSample<i32>,
 {
     fn read_sample(&mut self) -> Result<Option<S>, hound::Error> {
-        if let Some(n) = self.samples.next() {
+        if let Some(n) = self.source.wav_reader().samples::<i32>().next() {
             Ok(Some(S::from_sample_(n?)))
         } else {
             Ok(None)
         }
     }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.source.seek(frame)
+    }
 }
 
-struct I16SampleReader<'wr, R: Read> {
-    samples: WavSamples<'wr, R, i16>,
+// Hound reads 24 bits samples as `i32`s in the range of an `I24`.
+struct I24SampleReader<Src> {
+    source: Src,
 }
 
-impl<'wr, R: Read, S> HoundSampleReader<S> for I16SampleReader<'wr, R>
+impl<Src, S> HoundSampleReader<S> for I24SampleReader<Src>
 where
+    Src: HoundSource,
+    S: FromSample<I24>,
+{
+    fn read_sample(&mut self) -> Result<Option<S>, hound::Error> {
+        if let Some(n) = self.source.wav_reader().samples::<i32>().next() {
+            Ok(Some(S::from_sample_(I24::new_unchecked(n?))))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.source.seek(frame)
+    }
+}
+
+struct I16SampleReader<Src> {
+    source: Src,
+}
+
+impl<Src, S> HoundSampleReader<S> for I16SampleReader<Src>
+where
+    Src: HoundSource,
     S: FromSample<i16>,
 {
     fn read_sample(&mut self) -> Result<Option<S>, hound::Error> {
-        if let Some(n) = self.samples.next() {
+        if let Some(n) = self.source.wav_reader().samples::<i16>().next() {
+            Ok(Some(S::from_sample_(n?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.source.seek(frame)
+    }
+}
+
+struct I8SampleReader<Src> {
+    source: Src,
+}
+
+impl<Src, S> HoundSampleReader<S> for I8SampleReader<Src>
+where
+    Src: HoundSource,
+    S: FromSample<i8>,
+{
+    fn read_sample(&mut self) -> Result<Option<S>, hound::Error> {
+        if let Some(n) = self.source.wav_reader().samples::<i8>().next() {
             Ok(Some(S::from_sample_(n?)))
         } else {
             Ok(None)
         }
     }
+
+    fn seek(&mut self, frame: u32) -> Result<(), SeekError<hound::Error>> {
+        self.source.seek(frame)
+    }
 }
 
 pub struct HoundAudioWriter<'ww, S>
 where
-    S: ToSample<f32> + ToSample<i32> + ToSample<i16>,
+    S: ToSample<f32> + ToSample<i32> + ToSample<I24> + ToSample<i16> + ToSample<i8>,
 {
     hound_sample_writer: Box<dyn HoundSampleWriter<S> + 'ww>,
     number_of_channels: usize,
@@ -177,7 +400,7 @@ where
 
 impl<'ww, S> HoundAudioWriter<'ww, S>
 where
-    S: ToSample<f32> + ToSample<i32> + ToSample<i16>,
+    S: ToSample<f32> + ToSample<i32> + ToSample<I24> + ToSample<i16> + ToSample<i8>,
 {
     fn hound_sample_writer<W: Write + Seek>(
         writer: &'ww mut WavWriter<W>,
@@ -191,8 +414,10 @@ where
                 }
             },
             hound::SampleFormat::Int => match spec.bits_per_sample {
-                22 | 32 => Box::new(I32SampleWriter { writer }),
-                8 | 16 => Box::new(I16SampleWriter { writer }),
+                32 => Box::new(I32SampleWriter { writer }),
+                24 => Box::new(I24SampleWriter { writer }),
+                16 => Box::new(I16SampleWriter { writer }),
+                8 => Box::new(I8SampleWriter { writer }),
                 _ => {
                     // Note: until 3.4.0, Hound only supports 8, 16, 24, 32 bits/sample.
                     // Something else (e.g. 12 bits) would result in an error while writing
@@ -215,7 +440,7 @@ where
 
 impl<'ww, S> AudioWriter<S> for HoundAudioWriter<'ww, S>
 where
-    S: ToSample<f32> + ToSample<i32> + ToSample<i16> + Copy,
+    S: ToSample<f32> + ToSample<i32> + ToSample<I24> + ToSample<i16> + ToSample<i8> + Copy,
 {
     type Err = hound::Error;
 
@@ -290,6 +515,29 @@ where
     }
 }
 
+// Hound writes 24 bits samples from `i32`s in the range of an `I24`.
+struct I24SampleWriter<'ww, W>
+where
+    W: Write + Seek,
+{
+    writer: &'ww mut WavWriter<W>,
+}
+
+impl<'ww, S, W> HoundSampleWriter<S> for I24SampleWriter<'ww, W>
+where
+    S: ToSample<I24>,
+    W: Write + Seek,
+{
+    fn write_sample(&mut self, sample: S) -> Result<(), hound::Error> {
+        let sample: I24 = sample.to_sample_();
+        self.writer.write_sample::<i32>(sample.inner())
+    }
+
+    fn flush(&mut self) -> Result<(), hound::Error> {
+        self.writer.flush()
+    }
+}
+
 struct I16SampleWriter<'ww, W>
 where
     W: Write + Seek,
@@ -310,3 +558,205 @@ where
         self.writer.flush()
     }
 }
+
+struct I8SampleWriter<'ww, W>
+where
+    W: Write + Seek,
+{
+    writer: &'ww mut WavWriter<W>,
+}
+
+impl<'ww, S, W> HoundSampleWriter<S> for I8SampleWriter<'ww, W>
+where
+    S: ToSample<i8>,
+    W: Write + Seek,
+{
+    fn write_sample(&mut self, sample: S) -> Result<(), hound::Error> {
+        self.writer.write_sample::<i8>(sample.to_sample_())
+    }
+
+    fn flush(&mut self) -> Result<(), hound::Error> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HoundAudioError, HoundAudioReader, HoundAudioWriter};
+    use crate::backend::combined::{AudioReader, AudioWriter, SeekError};
+    use crate::buffer::{AudioBufferIn, AudioBufferOut, AudioChunk};
+    use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+    use std::io::Cursor;
+
+    fn round_trip(bits_per_sample: u16, input: &AudioChunk<f32>) -> AudioChunk<f32> {
+        let number_of_frames = input.channels()[0].len();
+        let spec = WavSpec {
+            channels: input.number_of_channels() as u16,
+            sample_rate: 44100,
+            bits_per_sample,
+            sample_format: SampleFormat::Int,
+        };
+        let mut data = Vec::new();
+        {
+            let mut wav_writer =
+                WavWriter::new(Cursor::new(&mut data), spec).expect("Unexpected error.");
+            {
+                let mut writer = HoundAudioWriter::new(&mut wav_writer).expect("Unexpected error.");
+                let input_slices = input.as_slices();
+                writer
+                    .write_buffer(&AudioBufferIn::new(&input_slices, number_of_frames))
+                    .expect("Unexpected error.");
+            }
+            wav_writer.finalize().expect("Unexpected error.");
+        }
+
+        let mut wav_reader = WavReader::new(Cursor::new(&data)).expect("Unexpected error.");
+        assert_eq!(wav_reader.spec().bits_per_sample, bits_per_sample);
+        let mut reader = HoundAudioReader::new(&mut wav_reader).expect("Unexpected error.");
+        let mut output = AudioChunk::zero(input.number_of_channels(), number_of_frames);
+        {
+            let mut output_slices = output.as_mut_slices();
+            let mut buffer = AudioBufferOut::new(&mut output_slices, number_of_frames);
+            assert_eq!(
+                reader.fill_buffer(&mut buffer).expect("Unexpected error."),
+                number_of_frames
+            );
+        }
+        output
+    }
+
+    #[test]
+    fn round_trip_24_bits() {
+        let input = audio_chunk![[0.0, 0.5, -0.25, 0.125], [-1.0, 0.25, 0.0, -0.5]];
+        assert_eq!(round_trip(24, &input), input);
+    }
+
+    #[test]
+    fn round_trip_8_bits() {
+        let input = audio_chunk![[0.0, 0.5, -0.25, -1.0]];
+        assert_eq!(round_trip(8, &input), input);
+    }
+
+    // A mono, 16 bits, 44100 Hz `.wav` file with four samples.
+    const TINY_WAV: &[u8] = &[
+        b'R', b'I', b'F', b'F', 44, 0, 0, 0, b'W', b'A', b'V', b'E', // RIFF header
+        b'f', b'm', b't', b' ', 16, 0, 0, 0, // fmt chunk
+        1, 0, 1, 0, 0x44, 0xAC, 0, 0, 0x88, 0x58, 0x01, 0, 2, 0, 16, 0, // format
+        b'd', b'a', b't', b'a', 8, 0, 0, 0, // data chunk
+        0, 0, 0, 0x40, 0, 0xC0, 0, 0x40, // samples
+    ];
+
+    #[test]
+    fn reads_wav_data_from_memory() {
+        let mut reader = HoundAudioReader::<f32>::from_bytes(TINY_WAV).expect("Unexpected error.");
+        assert_eq!(reader.number_of_channels(), 1);
+        assert_eq!(reader.frames_per_second(), 44100);
+        let mut output = AudioChunk::zero(1, 8);
+        let mut output_slices = output.as_mut_slices();
+        let mut buffer = AudioBufferOut::new(&mut output_slices, 8);
+        assert_eq!(
+            reader.fill_buffer(&mut buffer).expect("Unexpected error."),
+            4
+        );
+        assert_eq!(&buffer.index_channel(0)[..4], &[0.0, 0.5, -0.5, 0.5]);
+    }
+
+    #[test]
+    fn matching_wav_writer_preserves_the_format_of_the_input() {
+        let mut reader = HoundAudioReader::<f32>::from_bytes(TINY_WAV).expect("Unexpected error.");
+        let mut data = Vec::new();
+        {
+            let mut wav_writer = reader
+                .matching_wav_writer(Cursor::new(&mut data))
+                .expect("Unexpected error.");
+            {
+                let mut writer = HoundAudioWriter::new(&mut wav_writer).expect("Unexpected error.");
+                let mut chunk = AudioChunk::zero(1, 4);
+                {
+                    let mut slices = chunk.as_mut_slices();
+                    let mut buffer = AudioBufferOut::new(&mut slices, 4);
+                    reader.fill_buffer(&mut buffer).expect("Unexpected error.");
+                }
+                let slices = chunk.as_slices();
+                writer
+                    .write_buffer(&AudioBufferIn::new(&slices, 4))
+                    .expect("Unexpected error.");
+            }
+            wav_writer.finalize().expect("Unexpected error.");
+        }
+        let input_spec = WavReader::new(Cursor::new(TINY_WAV))
+            .expect("Unexpected error.")
+            .spec();
+        let output_spec = WavReader::new(Cursor::new(&data))
+            .expect("Unexpected error.")
+            .spec();
+        assert_eq!(output_spec, input_spec);
+        assert_eq!(reader.spec(), input_spec);
+    }
+
+    #[test]
+    fn from_bytes_rejects_invalid_data() {
+        match HoundAudioReader::<f32>::from_bytes(&TINY_WAV[..20]) {
+            Err(HoundAudioError::HoundError(_)) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Expected an error."),
+        }
+    }
+
+    #[test]
+    fn reading_continues_from_the_frame_that_was_sought() {
+        let mut reader = HoundAudioReader::<f32>::from_bytes(TINY_WAV).expect("Unexpected error.");
+        reader.seek(2).expect("Unexpected error.");
+        let mut output = AudioChunk::zero(1, 4);
+        let mut output_slices = output.as_mut_slices();
+        let mut buffer = AudioBufferOut::new(&mut output_slices, 4);
+        assert_eq!(
+            reader.fill_buffer(&mut buffer).expect("Unexpected error."),
+            2
+        );
+        assert_eq!(&buffer.index_channel(0)[..2], &[-0.5, 0.5]);
+    }
+
+    #[test]
+    fn reader_created_with_new_does_not_support_seeking() {
+        let mut wav_reader = WavReader::new(Cursor::new(TINY_WAV)).expect("Unexpected error.");
+        let mut reader = HoundAudioReader::<f32>::new(&mut wav_reader).expect("Unexpected error.");
+        match reader.seek(2) {
+            Err(SeekError::Unsupported) => {}
+            _ => panic!("Expected seeking to be unsupported."),
+        }
+    }
+
+    #[test]
+    fn seeking_beyond_the_largest_stereo_frame_index_fails() {
+        let spec = WavSpec {
+            channels: 2,
+            sample_rate: 44100,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let mut data = Vec::new();
+        {
+            let mut wav_writer =
+                WavWriter::new(Cursor::new(&mut data), spec).expect("Unexpected error.");
+            for _ in 0..4 {
+                wav_writer.write_sample(0i16).expect("Unexpected error.");
+            }
+            wav_writer.finalize().expect("Unexpected error.");
+        }
+        let mut reader = HoundAudioReader::<f32>::from_bytes(&data).expect("Unexpected error.");
+        match reader.seek(u32::MAX as u64 / 2 + 1) {
+            Err(SeekError::ReaderError(hound::Error::IoError(_))) => {}
+            _ => panic!("Expected an io error."),
+        }
+    }
+
+    #[test]
+    fn seeking_beyond_the_largest_wav_frame_index_fails() {
+        let mut reader = HoundAudioReader::<f32>::from_bytes(TINY_WAV).expect("Unexpected error.");
+        match reader.seek(u32::MAX as u64 + 1) {
+            Err(SeekError::ReaderError(hound::Error::IoError(_))) => {}
+            _ => panic!("Expected an io error."),
+        }
+    }
+}
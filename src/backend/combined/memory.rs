@@ -1,11 +1,12 @@
 //! In-memory backend, useful for testing.
-use super::{AudioReader, AudioWriter};
-use crate::buffer::{AudioBufferIn, AudioBufferOut, AudioChunk};
+use super::{AudioReader, AudioWriter, SeekError};
+use crate::buffer::{buffers_as_mut_slice, AudioBufferIn, AudioBufferOut, AudioChunk};
 #[cfg(feature = "dasp_sample")]
 // Re-exports from the `dasp-sample` crate
 pub mod dasp_sample {
     pub use dasp_sample::*;
 }
+use num_traits::{AsPrimitive, Zero};
 use std::borrow::Borrow;
 use std::marker::PhantomData;
 
@@ -68,6 +69,21 @@ where
     }
 }
 
+impl<S, T> Clone for AudioChunkReader<S, T>
+where
+    T: Borrow<AudioChunk<S>> + Clone,
+    S: Copy,
+{
+    fn clone(&self) -> Self {
+        Self {
+            frames_per_second: self.frames_per_second,
+            frame: self.frame,
+            chunk: self.chunk.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<S, T> AudioReader<S> for AudioChunkReader<S, T>
 where
     T: Borrow<AudioChunk<S>>,
@@ -99,6 +115,17 @@ where
         self.frame += frames_to_copy;
         Ok(frames_to_copy)
     }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        let number_of_frames = self
+            .chunk
+            .borrow()
+            .channels()
+            .first()
+            .map_or(0, |channel| channel.len());
+        self.frame = std::cmp::min(frame, number_of_frames as u64) as usize;
+        Ok(())
+    }
 }
 
 /// An [`AudioReader`] that reads from a given [`AudioChunk`].
@@ -145,6 +172,615 @@ mod AudioBufferReaderTests {
             assert_eq!(slices[2], vec![15, 14].as_slice());
         }
     }
+
+    mod seek {
+        use super::super::super::AudioReader;
+        use super::super::AudioBufferReader;
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn reading_continues_from_the_given_frame() {
+            let audio_buffer = audio_chunk![[1, 2, 3, 4, 5], [6, 7, 8, 9, 10]];
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16);
+            let mut output_buffer = AudioChunk::zero(2, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            reader.seek(3).expect("Unexpected error.");
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(2), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(&slices[0][..2], &[4, 5]);
+            assert_eq!(&slices[1][..2], &[9, 10]);
+            reader.seek(1).expect("Unexpected error.");
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(4), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], &[2, 3, 4, 5]);
+        }
+
+        #[test]
+        fn seeking_beyond_the_end_jumps_to_the_end() {
+            let audio_buffer = audio_chunk![[1, 2, 3]];
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16);
+            let mut output_buffer = AudioChunk::zero(1, 2);
+            let mut slices = output_buffer.as_mut_slices();
+            reader.seek(100).expect("Unexpected error.");
+            let mut buffers = AudioBufferOut::new(&mut slices, 2);
+            assert_eq!(Ok(0), reader.fill_buffer(&mut buffers));
+        }
+    }
+}
+
+/// An [`AudioReader`] that wraps another [`AudioReader`] and starts reading again from the
+/// start when the end is reached, e.g. to feed the same [`AudioChunk`] to an oscillator
+/// over and over again.
+///
+/// In order to go back to the start, the `LoopingReader` keeps a clone of the inner reader
+/// as it was passed to the constructor, so the inner reader needs to implement `Clone`.
+/// The [`AudioChunkReader`] implements `Clone` when its chunk implements `Clone`
+/// (e.g. for an [`AudioBufferReader`]).
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate rsynth;
+/// use rsynth::backend::combined::AudioReader;
+/// use rsynth::backend::combined::memory::{AudioBufferReader, LoopingReader};
+/// use rsynth::buffer::{AudioBufferOut, AudioChunk};
+/// # fn main() {
+/// let chunk = audio_chunk![[1, 2, 3]];
+/// let mut reader = LoopingReader::new(AudioBufferReader::new(&chunk, 44100));
+/// let mut output = AudioChunk::zero(1, 7);
+/// let mut slices = output.as_mut_slices();
+/// let mut buffer = AudioBufferOut::new(&mut slices, 7);
+/// assert_eq!(reader.fill_buffer(&mut buffer), Ok(7));
+/// assert_eq!(buffer.index_channel(0), &[1, 2, 3, 1, 2, 3, 1]);
+/// # }
+/// ```
+///
+/// Seeking
+/// =======
+/// Seeking starts the first repetition again and then seeks the inner reader to the given
+/// frame, so `frame` is counted from the start of the inner reader, not from the start
+/// of the looped audio.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Going back to the start clones the inner reader and allocates a small amount of memory.
+///
+/// [`AudioReader`]: ../trait.AudioReader.html
+/// [`AudioChunk`]: ../../../buffer/struct.AudioChunk.html
+/// [`AudioChunkReader`]: ./struct.AudioChunkReader.html
+/// [`AudioBufferReader`]: ./type.AudioBufferReader.html
+pub struct LoopingReader<R> {
+    start: R,
+    current: R,
+    repeat_count: Option<usize>,
+    remaining_repeats: Option<usize>,
+}
+
+impl<R> LoopingReader<R>
+where
+    R: Clone,
+{
+    /// Create a new `LoopingReader` that reads the audio from `reader` over and over again,
+    /// without end.
+    pub fn new(reader: R) -> Self {
+        Self {
+            current: reader.clone(),
+            start: reader,
+            repeat_count: None,
+            remaining_repeats: None,
+        }
+    }
+
+    /// Create a new `LoopingReader` that reads the audio from `reader` `repeat_count` times
+    /// in total.
+    ///
+    /// # Panics
+    /// Panics if `repeat_count` is `0`.
+    pub fn with_repeat_count(reader: R, repeat_count: usize) -> Self {
+        assert!(repeat_count > 0);
+        Self {
+            current: reader.clone(),
+            start: reader,
+            repeat_count: Some(repeat_count),
+            remaining_repeats: Some(repeat_count - 1),
+        }
+    }
+}
+
+impl<S, R> AudioReader<S> for LoopingReader<R>
+where
+    R: AudioReader<S> + Clone,
+    S: Copy + 'static,
+{
+    type Err = R::Err;
+
+    fn number_of_channels(&self) -> usize {
+        self.current.number_of_channels()
+    }
+
+    fn frames_per_second(&self) -> u64 {
+        self.current.frames_per_second()
+    }
+
+    fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err> {
+        let buffer_size = output.number_of_frames();
+        let mut frames_read = self.current.fill_buffer(output)?;
+        while frames_read < buffer_size {
+            match self.remaining_repeats {
+                Some(0) => break,
+                Some(ref mut remaining_repeats) => *remaining_repeats -= 1,
+                None => {}
+            }
+            self.current = self.start.clone();
+            let mut channels = Vec::with_capacity(output.number_of_channels());
+            let mut remainder = output.index_frames(frames_read.., &mut channels);
+            let frames_read_after_restart = self.current.fill_buffer(&mut remainder)?;
+            if frames_read_after_restart == 0 {
+                // The inner reader does not contain any audio, avoid looping forever.
+                break;
+            }
+            frames_read += frames_read_after_restart;
+        }
+        Ok(frames_read)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        let mut current = self.start.clone();
+        current.seek(frame)?;
+        self.current = current;
+        self.remaining_repeats = self.repeat_count.map(|repeat_count| repeat_count - 1);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod looping_reader_tests {
+    mod fill_buffer {
+        use super::super::super::AudioReader;
+        use super::super::{AudioBufferReader, LoopingReader};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn starts_again_when_the_end_is_reached() {
+            let audio_buffer = audio_chunk![[1, 2, 3], [4, 5, 6]];
+            let mut reader = LoopingReader::new(AudioBufferReader::new(&audio_buffer, 16));
+            assert_eq!(reader.number_of_channels(), 2);
+            assert_eq!(reader.frames_per_second(), 16);
+            let mut output_buffer = AudioChunk::zero(2, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(4), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![1, 2, 3, 1].as_slice());
+            assert_eq!(slices[1], vec![4, 5, 6, 4].as_slice());
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(4), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![2, 3, 1, 2].as_slice());
+            assert_eq!(slices[1], vec![5, 6, 4, 5].as_slice());
+        }
+
+        #[test]
+        fn stops_after_the_given_number_of_repeats() {
+            let audio_buffer = audio_chunk![[1, 2, 3]];
+            let mut reader =
+                LoopingReader::with_repeat_count(AudioBufferReader::new(&audio_buffer, 16), 2);
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(4), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![1, 2, 3, 1].as_slice());
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(2), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![2, 3, 3, 1].as_slice());
+        }
+
+        #[test]
+        fn does_not_loop_forever_on_empty_audio() {
+            let audio_buffer: AudioChunk<i32> = AudioChunk::new(1);
+            let mut reader = LoopingReader::new(AudioBufferReader::new(&audio_buffer, 16));
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            let mut buffers = AudioBufferOut::new(&mut slices, 4);
+            assert_eq!(Ok(0), reader.fill_buffer(&mut buffers));
+        }
+    }
+
+    mod seek {
+        use super::super::super::AudioReader;
+        use super::super::{AudioBufferReader, LoopingReader};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn starts_the_first_repetition_again_from_the_given_frame() {
+            let audio_buffer = audio_chunk![[1, 2, 3]];
+            let mut reader =
+                LoopingReader::with_repeat_count(AudioBufferReader::new(&audio_buffer, 16), 2);
+            let mut output_buffer = AudioChunk::zero(1, 5);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 5);
+                assert_eq!(Ok(5), reader.fill_buffer(&mut buffers));
+            }
+            reader.seek(1).expect("Unexpected error.");
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 5);
+                assert_eq!(Ok(5), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![2, 3, 1, 2, 3].as_slice());
+        }
+    }
+}
+
+/// An [`AudioReader`] that wraps another [`AudioReader`] and up-mixes or down-mixes the audio
+/// to the given number of channels, e.g. to feed a mono wav file to a stereo plugin.
+/// The generic parameter type `S` represents the sample type.
+///
+/// Mixing rules
+/// ============
+/// Suppose the inner reader has `m` channels and the `ChannelAdapter` has `n` channels.
+/// * When `n == m`, the audio is passed unchanged.
+/// * When `n > m` (up-mixing), output channel `i` is a copy of input channel `i % m`.
+///   In particular, when `m == 1` (mono to `n` channels), every output channel
+///   is a copy of the mono input.
+/// * When `n < m` (down-mixing), output channel `i` is the average of the input channels
+///   `i`, `i + n`, `i + 2n`, ...
+///   In particular, when `n == 1` (`m` channels to mono), the output is the average of all
+///   input channels.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// The audio of the inner reader is read into an internal buffer, which allocates memory
+/// when the buffer size grows.
+///
+/// [`AudioReader`]: ../trait.AudioReader.html
+pub struct ChannelAdapter<S, R> {
+    inner: R,
+    number_of_channels: usize,
+    buffers: Vec<Vec<S>>,
+}
+
+impl<S, R> ChannelAdapter<S, R>
+where
+    S: Copy,
+    R: AudioReader<S>,
+{
+    /// Create a new `ChannelAdapter` that reads from `inner` and has `number_of_channels`
+    /// channels.
+    ///
+    /// # Panics
+    /// Panics if `number_of_channels` is `0` or if `inner` has no channels.
+    pub fn new(inner: R, number_of_channels: usize) -> Self {
+        assert!(number_of_channels > 0);
+        let number_of_inner_channels = inner.number_of_channels();
+        assert!(number_of_inner_channels > 0);
+        Self {
+            inner,
+            number_of_channels,
+            buffers: (0..number_of_inner_channels).map(|_| Vec::new()).collect(),
+        }
+    }
+}
+
+impl<S, R> AudioReader<S> for ChannelAdapter<S, R>
+where
+    S: Copy + Zero + AsPrimitive<f64> + 'static,
+    f64: AsPrimitive<S>,
+    R: AudioReader<S>,
+{
+    type Err = R::Err;
+
+    fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+
+    fn frames_per_second(&self) -> u64 {
+        self.inner.frames_per_second()
+    }
+
+    fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err> {
+        assert_eq!(output.number_of_channels(), self.number_of_channels);
+        let buffer_size = output.number_of_frames();
+        for buffer in self.buffers.iter_mut() {
+            buffer.resize(buffer_size, S::zero());
+        }
+        let frames_read = {
+            let mut slices = buffers_as_mut_slice(&mut self.buffers, buffer_size);
+            let mut input = AudioBufferOut::new(&mut slices, buffer_size);
+            self.inner.fill_buffer(&mut input)?
+        };
+
+        let number_of_inner_channels = self.buffers.len();
+        for (output_index, output_channel) in output.channel_iter_mut().enumerate() {
+            if self.number_of_channels >= number_of_inner_channels {
+                let input_channel = &self.buffers[output_index % number_of_inner_channels];
+                output_channel[..frames_read].copy_from_slice(&input_channel[..frames_read]);
+            } else {
+                for (frame, sample) in output_channel[..frames_read].iter_mut().enumerate() {
+                    let mut sum: f64 = 0.0;
+                    let mut number_of_mixed_channels = 0;
+                    for input_channel in self
+                        .buffers
+                        .iter()
+                        .skip(output_index)
+                        .step_by(self.number_of_channels)
+                    {
+                        sum += input_channel[frame].as_();
+                        number_of_mixed_channels += 1;
+                    }
+                    *sample = (sum / number_of_mixed_channels as f64).as_();
+                }
+            }
+        }
+        Ok(frames_read)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        self.inner.seek(frame)
+    }
+}
+
+#[cfg(test)]
+mod channel_adapter_tests {
+    mod fill_buffer {
+        use super::super::super::AudioReader;
+        use super::super::{AudioBufferReader, ChannelAdapter};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn duplicates_mono_to_stereo() {
+            let audio_buffer = audio_chunk![[1.0, 2.0, 3.0]];
+            let mut reader = ChannelAdapter::new(AudioBufferReader::new(&audio_buffer, 16), 2);
+            assert_eq!(reader.number_of_channels(), 2);
+            assert_eq!(reader.frames_per_second(), 16);
+            let mut output_buffer = AudioChunk::zero(2, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(3), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![1.0, 2.0, 3.0, 0.0].as_slice());
+            assert_eq!(slices[1], vec![1.0, 2.0, 3.0, 0.0].as_slice());
+        }
+
+        #[test]
+        fn averages_stereo_to_mono() {
+            let audio_buffer = audio_chunk![[1.0, 2.0, 3.0], [3.0, 4.0, -3.0]];
+            let mut reader = ChannelAdapter::new(AudioBufferReader::new(&audio_buffer, 16), 1);
+            assert_eq!(reader.number_of_channels(), 1);
+            let mut output_buffer = AudioChunk::zero(1, 3);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 3);
+                assert_eq!(Ok(3), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![2.0, 3.0, 0.0].as_slice());
+        }
+
+        #[test]
+        fn mixes_other_channel_counts_by_index() {
+            let audio_buffer = audio_chunk![[1, 1], [2, 2], [3, 3], [4, 4]];
+            let mut reader = ChannelAdapter::new(AudioBufferReader::new(&audio_buffer, 16), 2);
+            let mut output_buffer = AudioChunk::zero(2, 2);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 2);
+                assert_eq!(Ok(2), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(slices[0], vec![2, 2].as_slice());
+            assert_eq!(slices[1], vec![3, 3].as_slice());
+        }
+    }
+}
+
+/// An [`AudioReader`] that wraps another [`AudioReader`] and stops after a given number
+/// of frames, e.g. to render only the first few seconds of a long file.
+///
+/// Use [`TakeFramesExt::take_frames`] or [`TakeFrames::new`] to create a `TakeFrames`.
+///
+/// # Example
+/// ```
+/// # #[macro_use]
+/// # extern crate rsynth;
+/// use rsynth::backend::combined::AudioReader;
+/// use rsynth::backend::combined::memory::{AudioBufferReader, TakeFramesExt};
+/// use rsynth::buffer::{AudioBufferOut, AudioChunk};
+/// # fn main() {
+/// let chunk = audio_chunk![[1, 2, 3, 4, 5]];
+/// let mut reader = AudioBufferReader::new(&chunk, 44100).take_frames(3);
+/// let mut output = AudioChunk::zero(1, 4);
+/// let mut slices = output.as_mut_slices();
+/// let mut buffer = AudioBufferOut::new(&mut slices, 4);
+/// assert_eq!(reader.fill_buffer(&mut buffer), Ok(3));
+/// assert_eq!(&buffer.index_channel(0)[..3], &[1, 2, 3]);
+/// # }
+/// ```
+///
+/// Seeking beyond the given number of frames jumps to the last frame that can be read.
+/// Frames are counted from the position of the inner reader when the `TakeFrames` was created,
+/// so the inner reader should be at its start at that time for seeking to work as expected.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Reading the last, incomplete, buffer allocates a small amount of memory.
+///
+/// [`AudioReader`]: ../trait.AudioReader.html
+/// [`TakeFramesExt::take_frames`]: ./trait.TakeFramesExt.html#method.take_frames
+/// [`TakeFrames::new`]: #method.new
+pub struct TakeFrames<R> {
+    inner: R,
+    number_of_frames: u64,
+    remaining_frames: u64,
+}
+
+impl<R> TakeFrames<R> {
+    /// Create a new `TakeFrames` that reads at most `number_of_frames` frames from `inner`.
+    pub fn new(inner: R, number_of_frames: u64) -> Self {
+        Self {
+            inner,
+            number_of_frames,
+            remaining_frames: number_of_frames,
+        }
+    }
+
+    /// The number of frames that can still be read.
+    pub fn remaining_frames(&self) -> u64 {
+        self.remaining_frames
+    }
+
+    /// Get back the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<S, R> AudioReader<S> for TakeFrames<R>
+where
+    R: AudioReader<S>,
+    S: Copy + 'static,
+{
+    type Err = R::Err;
+
+    fn number_of_channels(&self) -> usize {
+        self.inner.number_of_channels()
+    }
+
+    fn frames_per_second(&self) -> u64 {
+        self.inner.frames_per_second()
+    }
+
+    fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err> {
+        let buffer_size = output.number_of_frames();
+        let frames_to_read = std::cmp::min(buffer_size as u64, self.remaining_frames) as usize;
+        let frames_read = if frames_to_read == buffer_size {
+            self.inner.fill_buffer(output)?
+        } else if frames_to_read == 0 {
+            0
+        } else {
+            let mut channels = Vec::with_capacity(output.number_of_channels());
+            let mut limited = output.index_frames(..frames_to_read, &mut channels);
+            self.inner.fill_buffer(&mut limited)?
+        };
+        self.remaining_frames -= frames_read as u64;
+        Ok(frames_read)
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        let frame = std::cmp::min(frame, self.number_of_frames);
+        self.inner.seek(frame)?;
+        self.remaining_frames = self.number_of_frames - frame;
+        Ok(())
+    }
+}
+
+/// Extension trait to create a [`TakeFrames`] from any [`AudioReader`].
+///
+/// [`TakeFrames`]: ./struct.TakeFrames.html
+/// [`AudioReader`]: ../trait.AudioReader.html
+pub trait TakeFramesExt<S>: AudioReader<S> + Sized
+where
+    S: Copy,
+{
+    /// Read at most `number_of_frames` frames from this reader.
+    fn take_frames(self, number_of_frames: u64) -> TakeFrames<Self> {
+        TakeFrames::new(self, number_of_frames)
+    }
+}
+
+impl<S, R> TakeFramesExt<S> for R
+where
+    R: AudioReader<S>,
+    S: Copy,
+{
+}
+
+#[cfg(test)]
+mod take_frames_tests {
+    mod fill_buffer {
+        use super::super::super::AudioReader;
+        use super::super::{AudioBufferReader, TakeFramesExt};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn never_reads_more_than_the_budget() {
+            let audio_buffer = AudioChunk::from_channels(vec![(0..100).collect()]);
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16).take_frames(10);
+            assert_eq!(reader.number_of_channels(), 1);
+            assert_eq!(reader.frames_per_second(), 16);
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            let mut total_frames = 0;
+            let mut read = Vec::new();
+            loop {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                let frames_read = reader.fill_buffer(&mut buffers).expect("Unexpected error.");
+                total_frames += frames_read;
+                read.extend_from_slice(&buffers.index_channel(0)[..frames_read]);
+                if frames_read < 4 {
+                    break;
+                }
+            }
+            assert_eq!(total_frames, 10);
+            assert_eq!(read, (0..10).collect::<Vec<_>>());
+            assert_eq!(reader.remaining_frames(), 0);
+            let mut buffers = AudioBufferOut::new(&mut slices, 4);
+            assert_eq!(Ok(0), reader.fill_buffer(&mut buffers));
+        }
+
+        #[test]
+        fn stops_when_the_inner_reader_ends_first() {
+            let audio_buffer = audio_chunk![[1, 2, 3]];
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16).take_frames(10);
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            let mut buffers = AudioBufferOut::new(&mut slices, 4);
+            assert_eq!(Ok(3), reader.fill_buffer(&mut buffers));
+            assert_eq!(reader.remaining_frames(), 7);
+        }
+    }
+
+    mod seek {
+        use super::super::super::AudioReader;
+        use super::super::{AudioBufferReader, TakeFramesExt};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn reading_continues_from_the_given_frame_within_the_budget() {
+            let audio_buffer = audio_chunk![[1, 2, 3, 4, 5, 6]];
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16).take_frames(4);
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            reader.seek(1).expect("Unexpected error.");
+            assert_eq!(reader.remaining_frames(), 3);
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(Ok(3), reader.fill_buffer(&mut buffers));
+            }
+            assert_eq!(&slices[0][..3], &[2, 3, 4]);
+        }
+
+        #[test]
+        fn seeking_beyond_the_budget_jumps_to_the_end() {
+            let audio_buffer = audio_chunk![[1, 2, 3, 4, 5, 6]];
+            let mut reader = AudioBufferReader::new(&audio_buffer, 16).take_frames(4);
+            let mut output_buffer = AudioChunk::zero(1, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            reader.seek(5).expect("Unexpected error.");
+            assert_eq!(reader.remaining_frames(), 0);
+            let mut buffers = AudioBufferOut::new(&mut slices, 4);
+            assert_eq!(Ok(0), reader.fill_buffer(&mut buffers));
+        }
+    }
 }
 
 #[cfg(feature = "backend-combined-wav-0-6")]
@@ -156,6 +792,11 @@ pub mod wav_0_6 {
     use super::{AudioChunk, AudioChunkReader};
     use std::marker::PhantomData;
 
+    /// # Panics
+    /// Panics if the number of samples is not an integer multiple of the number of channels
+    /// in the header, see [`AudioChunk::from_interlaced_iterator`].
+    ///
+    /// [`AudioChunk::from_interlaced_iterator`]: ../../../buffer/struct.AudioChunk.html#method.from_interlaced_iterator
     impl<S> From<(Header, BitDepth)> for AudioChunkReader<S, AudioChunk<S>>
     where
         S: Copy + FromSample<u8> + FromSample<i16> + FromSample<I24> + FromSample<f32>,
@@ -199,6 +840,9 @@ pub mod wav_0_6 {
 /// =======================================
 /// Because this appends to an [`AudioChunk`], it may allocate memory
 /// when the capacity of the [`AudioChunk`] is exceeded.
+/// Use [`with_expected_frames`] to reserve the capacity up front.
+///
+/// [`with_expected_frames`]: #method.with_expected_frames
 ///
 /// [`AudioWriter`]: ../trait.AudioWriter.html
 /// [`AudioChunk`]: ../../../buffer/struct.AudioChunk.html
@@ -210,6 +854,20 @@ impl<'b, S> AudioBufferWriter<'b, S> {
     pub fn new(buffer: &'b mut AudioChunk<S>) -> Self {
         Self { buffer }
     }
+
+    /// Create a new `AudioBufferWriter` and reserve capacity in `buffer` for
+    /// `expected_number_of_frames` more frames, so that writing up to this number of frames
+    /// does not allocate memory.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn with_expected_frames(
+        buffer: &'b mut AudioChunk<S>,
+        expected_number_of_frames: usize,
+    ) -> Self {
+        buffer.reserve(expected_number_of_frames);
+        Self { buffer }
+    }
 }
 
 impl<'b, S> AudioWriter<S> for AudioBufferWriter<'b, S>
@@ -230,3 +888,27 @@ where
         self.buffer.number_of_channels()
     }
 }
+
+#[cfg(test)]
+mod audio_buffer_writer_tests {
+    use super::super::AudioWriter;
+    use super::AudioBufferWriter;
+    use crate::buffer::{AudioBufferIn, AudioChunk};
+
+    #[test]
+    fn with_expected_frames_does_not_reallocate() {
+        let mut output_buffer = AudioChunk::new(2);
+        let input = audio_chunk![[1, 2, 3, 4], [5, 6, 7, 8]];
+        let input_slices = input.as_slices();
+        AudioBufferWriter::with_expected_frames(&mut output_buffer, 16);
+        let capacity = output_buffer.capacity();
+        assert!(capacity >= 16);
+        for _ in 0..4 {
+            AudioBufferWriter::new(&mut output_buffer)
+                .write_buffer(&AudioBufferIn::new(&input_slices, 4))
+                .unwrap();
+            assert_eq!(output_buffer.capacity(), capacity);
+        }
+        assert_eq!(output_buffer.channels()[0].len(), 16);
+    }
+}
@@ -1,5 +1,5 @@
-//! Read midi files.
-use super::MICROSECONDS_PER_SECOND;
+//! Read and write midi files.
+use super::{MidiWriter, MICROSECONDS_PER_SECOND};
 use crate::event::{DeltaEvent, RawMidiEvent, TimeStretcher};
 
 /// Re-exports from the `midly` crate.
@@ -7,28 +7,38 @@ pub mod midly_0_5 {
     pub use midly_0_5::*;
 }
 
-use self::midly_0_5::Timing;
+use self::midly_0_5::live::LiveEvent;
+use self::midly_0_5::{
+    num::{u15, u24, u28},
+    Format, Header, MetaMessage, Timing, Track, TrackEvent, TrackEventKind,
+};
 #[cfg(test)]
 use self::midly_0_5::{
-    num::{u15, u24, u28, u4, u7},
-    Format, Header, MidiMessage, Track, TrackEvent,
+    num::{u4, u7},
+    MidiMessage,
 };
-use self::midly_0_5::{MetaMessage, TrackEventKind};
 use crate::backend::combined::midly::midly_0_5::Smf;
 use itertools::Itertools;
 use std::convert::TryFrom;
+use std::io::Write;
 use std::num::NonZeroU64;
 
 const SECONDS_PER_MINUTE: u64 = 60;
 const MICROSECONDS_PER_MINUTE: u64 = SECONDS_PER_MINUTE * MICROSECONDS_PER_SECOND;
 const DEFAULT_BEATS_PER_MINUTE: u64 = 120;
+const DEFAULT_MICROSECONDS_PER_BEAT: u64 = MICROSECONDS_PER_MINUTE / DEFAULT_BEATS_PER_MINUTE;
 
 /// Read from midi events as parsed by the `midly` crate.
+///
+/// The time of the events (in ticks) is converted to microseconds with the tempo at that time:
+/// the tempo changes with every "Set Tempo" meta event (`FF 51`) in the tracks that are read.
+/// Until the first tempo event, the tempo is 120 beats per minute.
 pub struct MidlyMidiReader<'a, 'b> {
     event_iter: Box<dyn Iterator<Item = (u64, TrackEventKind<'a>)> + 'b>,
     timestretcher: TimeStretcher,
     previous_time_in_microseconds: u64,
     ticks_per_beat: NonZeroU64,
+    microseconds_per_beat: u64,
 }
 
 impl<'a, 'b> MidlyMidiReader<'a, 'b>
@@ -66,17 +76,21 @@ where
         //                   ticks * microseconds_per_beat
         // microseconds = -----------------------------------
         //                   ticks_per_beat
-        let timestretcher = TimeStretcher::new(
-            MICROSECONDS_PER_MINUTE / DEFAULT_BEATS_PER_MINUTE,
-            ticks_per_beat,
-        );
+        let timestretcher = TimeStretcher::new(DEFAULT_MICROSECONDS_PER_BEAT, ticks_per_beat);
         Ok(Self {
             ticks_per_beat,
             event_iter,
             previous_time_in_microseconds: 0,
             timestretcher,
+            microseconds_per_beat: DEFAULT_MICROSECONDS_PER_BEAT,
         })
     }
+
+    /// The current tempo, in microseconds per beat, as set by the last tempo event that has
+    /// been read.
+    pub fn microseconds_per_beat(&self) -> u64 {
+        self.microseconds_per_beat
+    }
 }
 
 impl<'a, 'b> Iterator for MidlyMidiReader<'a, 'b> {
@@ -86,7 +100,8 @@ impl<'a, 'b> Iterator for MidlyMidiReader<'a, 'b> {
         loop {
             let (t, event) = self.event_iter.next()?;
             let new_factor = if let TrackEventKind::Meta(MetaMessage::Tempo(tempo)) = event {
-                Some((tempo.as_int() as u64, self.ticks_per_beat))
+                self.microseconds_per_beat = tempo.as_int() as u64;
+                Some((self.microseconds_per_beat, self.ticks_per_beat))
             } else {
                 None
             };
@@ -105,6 +120,151 @@ impl<'a, 'b> Iterator for MidlyMidiReader<'a, 'b> {
     }
 }
 
+/// Write midi events to a single track that can be saved as a midi file with the `midly` crate.
+///
+/// The track has a fixed tempo of 120 beats per minute.
+/// Events that cannot be represented in a midi file (e.g. system real time messages)
+/// are ignored.
+///
+/// _Note_: `MidiWriter` is also implemented for `&mut MidlyMidiWriter`, so you can pass
+/// a mutable reference to the [`run`] function and retrieve the midi file afterwards.
+///
+/// [`run`]: ../fn.run.html
+pub struct MidlyMidiWriter {
+    ticks_per_beat: u16,
+    track: Track<'static>,
+    time_in_microseconds: u64,
+    time_in_ticks: u64,
+}
+
+impl MidlyMidiWriter {
+    /// Create a new `MidlyMidiWriter` with the given resolution.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `ticks_per_beat` is `0` or does not fit in 15 bits.
+    pub fn new(ticks_per_beat: u16) -> Self {
+        assert!(ticks_per_beat > 0);
+        assert!(ticks_per_beat < 1 << 15);
+        Self {
+            ticks_per_beat,
+            track: vec![TrackEvent {
+                delta: u28::from(0),
+                kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::from(
+                    DEFAULT_MICROSECONDS_PER_BEAT as u32,
+                ))),
+            }],
+            time_in_microseconds: 0,
+            time_in_ticks: 0,
+        }
+    }
+
+    /// The events that have been written so far, preceded by a tempo event.
+    pub fn track(&self) -> &Track<'static> {
+        &self.track
+    }
+
+    /// Convert into an [`Smf`] with one track, which can then be saved to a file.
+    ///
+    /// [`Smf`]: ./midly_0_5/struct.Smf.html
+    pub fn into_smf(mut self) -> Smf<'static> {
+        self.track.push(TrackEvent {
+            delta: u28::from(0),
+            kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+        });
+        Smf {
+            header: Header {
+                format: Format::SingleTrack,
+                timing: Timing::Metrical(u15::from(self.ticks_per_beat)),
+            },
+            tracks: vec![self.track],
+        }
+    }
+
+    /// Write the events as a midi file with one track to the given writer.
+    ///
+    /// _Note_: the midi file is only written when this method is called, so make sure
+    /// to call it when rendering is done.
+    pub fn finish<W: Write>(self, writer: W) -> std::io::Result<()> {
+        self.into_smf().write_std(writer)
+    }
+}
+
+impl MidiWriter for MidlyMidiWriter {
+    fn write_event(&mut self, event: DeltaEvent<RawMidiEvent>) {
+        // Compute the delta from the absolute times in order to avoid drifting.
+        self.time_in_microseconds += event.microseconds_since_previous_event;
+        let time_in_ticks =
+            self.time_in_microseconds * self.ticks_per_beat as u64 / DEFAULT_MICROSECONDS_PER_BEAT;
+        let kind = match LiveEvent::parse(event.event.bytes()) {
+            Ok(LiveEvent::Midi { channel, message }) => TrackEventKind::Midi { channel, message },
+            _ => {
+                warn!(
+                    "Ignoring midi event {:?}: it cannot be written to a midi file.",
+                    event.event
+                );
+                return;
+            }
+        };
+        self.track.push(TrackEvent {
+            delta: u28::from((time_in_ticks - self.time_in_ticks) as u32),
+            kind,
+        });
+        self.time_in_ticks = time_in_ticks;
+    }
+}
+
+#[test]
+fn midly_midi_writer_writes_events_at_the_right_time() {
+    let mut writer = MidlyMidiWriter::new(480);
+    // One beat is 500 000 microseconds, so 480 ticks.
+    writer.write_event(DeltaEvent {
+        microseconds_since_previous_event: 500_000,
+        event: RawMidiEvent::new(&[0x90, 60, 90]),
+    });
+    writer.write_event(DeltaEvent {
+        microseconds_since_previous_event: 250_000,
+        event: RawMidiEvent::new(&[0x80, 60, 0]),
+    });
+    // System real time messages cannot be written to a midi file.
+    writer.write_event(DeltaEvent {
+        microseconds_since_previous_event: 0,
+        event: RawMidiEvent::new(&[0xF8]),
+    });
+    let smf = writer.into_smf();
+    let track = &smf.tracks[0];
+    assert_eq!(track.len(), 4);
+    assert_eq!(track[1].delta, u28::from(480));
+    assert_eq!(
+        track[1].kind,
+        TrackEventKind::Midi {
+            channel: u4::from(0),
+            message: MidiMessage::NoteOn {
+                key: u7::from(60),
+                vel: u7::from(90),
+            },
+        }
+    );
+    assert_eq!(track[2].delta, u28::from(240));
+    assert_eq!(track[3].kind, TrackEventKind::Meta(MetaMessage::EndOfTrack));
+}
+
+#[test]
+fn midly_midi_writer_finish_writes_a_midi_file() {
+    let mut writer = MidlyMidiWriter::new(480);
+    writer.write_event(DeltaEvent {
+        microseconds_since_previous_event: 500_000,
+        event: RawMidiEvent::new(&[0x90, 60, 90]),
+    });
+    let mut bytes = Vec::new();
+    writer.finish(&mut bytes).expect("Unexpected error");
+    let smf = Smf::parse(&bytes).expect("Unexpected error");
+    assert_eq!(smf.header.timing, Timing::Metrical(u15::from(480)));
+    assert_eq!(smf.tracks.len(), 1);
+    assert_eq!(smf.tracks[0].len(), 3);
+    assert_eq!(smf.tracks[0][1].delta, u28::from(480));
+}
+
 #[test]
 pub fn iterator_correctly_returns_one_event() {
     // 120 beats per minute
@@ -149,6 +309,58 @@ pub fn iterator_correctly_returns_one_event() {
     assert_eq!(mr.next(), None);
 }
 
+#[test]
+fn iterator_honours_tempo_changes() {
+    let ticks_per_beat = 32;
+    let note_on = TrackEventKind::Midi {
+        channel: u4::from(0),
+        message: MidiMessage::NoteOn {
+            key: u7::from(60),
+            vel: u7::from(90),
+        },
+    };
+    let events = vec![
+        TrackEvent {
+            delta: u28::from(0),
+            kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::from(500_000))),
+        },
+        // Two beats at 500 000 microseconds per beat.
+        TrackEvent {
+            delta: u28::from(64),
+            kind: note_on,
+        },
+        // Twice as fast.
+        TrackEvent {
+            delta: u28::from(0),
+            kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::from(250_000))),
+        },
+        // Two beats at 250 000 microseconds per beat.
+        TrackEvent {
+            delta: u28::from(64),
+            kind: note_on,
+        },
+    ];
+    let header = Header {
+        timing: Timing::Metrical(u15::from(ticks_per_beat)),
+        format: Format::SingleTrack,
+    };
+    let smf = Smf {
+        header,
+        tracks: vec![events],
+    };
+    let mut mr = MidlyMidiReader::new(&smf).expect("No errors should occur now.");
+    assert_eq!(mr.microseconds_per_beat(), DEFAULT_MICROSECONDS_PER_BEAT);
+    let observed = mr.next().expect("MidlyMidiReader should return one event.");
+    assert_eq!(observed.microseconds_since_previous_event, 1_000_000);
+    assert_eq!(mr.microseconds_per_beat(), 500_000);
+    let observed = mr
+        .next()
+        .expect("MidlyMidiReader should return a second event.");
+    assert_eq!(observed.microseconds_since_previous_event, 500_000);
+    assert_eq!(mr.microseconds_per_beat(), 250_000);
+    assert_eq!(mr.next(), None);
+}
+
 #[cfg(test)]
 pub fn iterator_correctly_returns_two_events() {
     // 120 beats per minute
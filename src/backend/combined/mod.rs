@@ -6,14 +6,28 @@
 //!
 //! The [`run`] function can be used to run a plugin and read audio and midi from the
 //! inputs and write audio and midi to the outputs.
+//! The inputs and outputs are specified with a [`RunConfig`].
 //!
 //! Currently, the following inputs and outputs are available:
 //!
 //! * Dummy: [`AudioDummy`]: dummy audio input (generates silence) and output and [`MidiDummy`]: dummy midi input (generates no events) and output
 //! * Hound: [`HoundAudioReader`] and [`HoundAudioWriter`]: read and write `.wav` files (behind the "backend-combined-hound" feature)
-//! * Midly: [`MidlyMidiReader`]: read `.mid` files (behind the "backend-combined-midly-0-5" feature)
-//! * Memory: [`AudioBufferReader`] and [`AudioBufferWriter`]: read and write audio from memory
-//! * Testing: [`TestAudioReader`] and [`TestAudioWriter`]: audio input and output, to be used in tests
+//! * Midly: [`MidlyMidiReader`] and [`MidlyMidiWriter`]: read and write `.mid` files (behind the "backend-combined-midly-0-5" feature)
+//! * Memory: [`AudioBufferReader`] and [`AudioBufferWriter`]: read and write audio from memory,
+//!   [`LoopingReader`]: read the audio from another reader over and over again,
+//!   [`ChannelAdapter`]: up-mix or down-mix the audio from another reader,
+//!   [`TakeFrames`]: read only the first frames from another reader
+//! * Raw: [`RawPcmReader`] and [`RawPcmWriter`]: read and write raw (headerless) interleaved PCM audio
+//! * Cpal: [`CpalPlayer`]: play audio to the default output device (behind the "backend-cpal" feature)
+//! * Spectrum: [`SpectrumWriter`]: compute the spectrum of the audio that is written, to be used in tests
+//!   (behind the "backend-combined-rustfft-6" feature)
+//! * Testing: [`TestAudioReader`] and [`TestAudioWriter`]: audio input and output,
+//!   [`TestMidiReader`] and [`TestMidiWriter`]: midi input and output, to be used in tests
+//!
+//! Multiple audio inputs (e.g. a main signal and a sidechain signal) can be combined with the
+//! [`ChannelGroupsReader`].
+//! Events from sources with a different time origin can be aligned with the [`ShiftedMidiReader`].
+//! The musical position for a changing tempo can be computed with the [`TempoTracker`].
 //!
 //! Note that, when compiled with the `backend-combined-wav` feature,
 //! [`AudioChunkReader`] implements `From<(Header, BitDepth)>`
@@ -24,20 +38,35 @@
 //! [`HoundAudioReader`]: ./hound/struct.HoundAudioReader.html
 //! [`HoundAudioWriter`]: ./hound/struct.HoundAudioWriter.html
 //! [`MidlyMidiReader`]: ./midly/struct.MidlyMidiReader.html
+//! [`MidlyMidiWriter`]: ./midly/struct.MidlyMidiWriter.html
 //! [`TestAudioReader`]: ./struct.TestAudioReader.html
 //! [`TestAudioWriter`]: ./struct.TestAudioWriter.html
+//! [`TestMidiReader`]: ./struct.TestMidiReader.html
+//! [`TestMidiWriter`]: ./struct.TestMidiWriter.html
 //! [`AudioBufferReader`]: ./memory/struct.AudioBufferReader.html
 //! [`AudioBufferWriter`]: ./memory/struct.AudioBufferWriter.html
+//! [`LoopingReader`]: ./memory/struct.LoopingReader.html
+//! [`ChannelAdapter`]: ./memory/struct.ChannelAdapter.html
+//! [`TakeFrames`]: ./memory/struct.TakeFrames.html
+//! [`RawPcmReader`]: ./raw/struct.RawPcmReader.html
+//! [`RawPcmWriter`]: ./raw/struct.RawPcmWriter.html
+//! [`CpalPlayer`]: ./cpal/struct.CpalPlayer.html
+//! [`SpectrumWriter`]: ./spectrum/struct.SpectrumWriter.html
 //! [`run`]: ./fn.run.html
+//! [`RunConfig`]: ./struct.RunConfig.html
+//! [`ChannelGroupsReader`]: ./struct.ChannelGroupsReader.html
+//! [`ShiftedMidiReader`]: ./struct.ShiftedMidiReader.html
+//! [`TempoTracker`]: ./tempo/struct.TempoTracker.html
 //! [the cargo reference]: https://doc.rust-lang.org/cargo/reference/manifest.html#the-features-section
 //! [`AudioChunkReader`]: ./memory/struct.AudioChunkReader.html
 
+use self::dummy::MidiDummy;
 use crate::backend::{HostInterface, Stop};
 use crate::buffer::{
     buffers_as_mut_slice, buffers_as_slice, AudioBufferIn, AudioBufferInOut, AudioBufferOut,
     AudioChunk,
 };
-use crate::event::{DeltaEvent, EventHandler, RawMidiEvent, Timed};
+use crate::event::{DeltaEvent, EventHandler, MidiMessage, RawMidiEvent, Timed};
 use crate::ContextualAudioRenderer;
 use event_queue::{AlwaysInsertNewAfterOld, EventQueue};
 use itertools::Itertools;
@@ -46,12 +75,18 @@ use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
 use vecstorage::VecStorage;
 
+#[cfg(feature = "backend-cpal")]
+pub mod cpal;
 pub mod dummy;
 #[cfg(feature = "backend-combined-hound")]
 pub mod hound;
 pub mod memory;
 #[cfg(feature = "backend-combined-midly-0-5")]
 pub mod midly;
+pub mod raw;
+#[cfg(feature = "backend-combined-rustfft-6")]
+pub mod spectrum;
+pub mod tempo;
 
 /// Define how audio is read.
 ///
@@ -73,6 +108,16 @@ where
     /// to the buffer.
     /// If the return value is `<` the number of frames in the input, no more frames can be expected.
     fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err>;
+
+    /// Jump to the given frame, so that the next call to `fill_buffer` starts reading
+    /// from this frame.
+    /// Seeking beyond the end jumps to the end.
+    ///
+    /// The default implementation returns `Err(SeekError::Unsupported)`.
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        let _ = frame;
+        Err(SeekError::Unsupported)
+    }
 }
 
 /// Define how audio is written.
@@ -120,18 +165,273 @@ where
     }
 }
 
-pub const MICROSECONDS_PER_SECOND: u64 = 1_000_000;
+impl<'w, W, S> AudioWriter<S> for &'w mut W
+where
+    W: AudioWriter<S>,
+    S: Copy,
+{
+    type Err = <W as AudioWriter<S>>::Err;
+
+    fn write_buffer(&mut self, buffer: &AudioBufferIn<S>) -> Result<(), Self::Err> {
+        (**self).write_buffer(buffer)
+    }
+
+    fn specifies_number_of_channels(&self) -> bool {
+        (**self).specifies_number_of_channels()
+    }
+
+    fn number_of_channels(&self) -> usize {
+        (**self).number_of_channels()
+    }
+}
+
+pub use crate::event::MICROSECONDS_PER_SECOND;
+
+/// Define how events are read.
+///
+/// The type parameter `E` corresponds to the type of the event.
+///
+/// _Note_: this trait is implemented for every `Iterator<Item = DeltaEvent<E>>`.
+pub trait EventReader<E> {
+    /// Read the next event.
+    /// Return `None` when no more events can be expected.
+    fn read_event(&mut self) -> Option<DeltaEvent<E>>;
+}
+
+impl<I, E> EventReader<E> for I
+where
+    I: Iterator<Item = DeltaEvent<E>>,
+{
+    fn read_event(&mut self) -> Option<DeltaEvent<E>> {
+        self.next()
+    }
+}
+
+/// Define how midi is read.
+///
+/// This trait is implemented for every [`EventReader`]`<`[`RawMidiEvent`]`>`,
+/// so you do not need to implement it yourself.
+///
+/// [`EventReader`]: ./trait.EventReader.html
+/// [`RawMidiEvent`]: ../../event/struct.RawMidiEvent.html
+pub trait MidiReader: EventReader<RawMidiEvent> {}
+
+impl<T> MidiReader for T where T: EventReader<RawMidiEvent> {}
+
+/// An [`EventReader`] that shifts all events from another [`EventReader`] by a constant
+/// offset in time, e.g. to align events from sources with a different time origin,
+/// or to add some pre-roll.
+///
+/// Because the timing of a [`DeltaEvent`] is relative to the previous event, only the
+/// first event is changed: the offset is added to its `microseconds_since_previous_event`.
+/// The timing of the subsequent events is unchanged.
+///
+/// [`EventReader`]: ./trait.EventReader.html
+/// [`DeltaEvent`]: ../../event/struct.DeltaEvent.html
+pub struct ShiftedMidiReader<R> {
+    inner: R,
+    offset_in_microseconds: u64,
+    first_event_read: bool,
+}
+
+impl<R> ShiftedMidiReader<R> {
+    /// Create a new `ShiftedMidiReader` that shifts the events from `inner` by
+    /// `offset_in_microseconds`.
+    pub fn new(inner: R, offset_in_microseconds: u64) -> Self {
+        Self {
+            inner,
+            offset_in_microseconds,
+            first_event_read: false,
+        }
+    }
+
+    /// Create a new `ShiftedMidiReader` that shifts the events from `inner` by
+    /// `offset_in_frames`, at a sample rate of `frames_per_second`.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `frames_per_second` is `0`.
+    pub fn with_offset_in_frames(inner: R, offset_in_frames: u64, frames_per_second: u64) -> Self {
+        assert!(frames_per_second > 0);
+        Self::new(
+            inner,
+            offset_in_frames * MICROSECONDS_PER_SECOND / frames_per_second,
+        )
+    }
+}
+
+impl<R, E> EventReader<E> for ShiftedMidiReader<R>
+where
+    R: EventReader<E>,
+{
+    fn read_event(&mut self) -> Option<DeltaEvent<E>> {
+        let mut event = self.inner.read_event()?;
+        if !self.first_event_read {
+            event.microseconds_since_previous_event += self.offset_in_microseconds;
+            self.first_event_read = true;
+        }
+        Some(event)
+    }
+}
+
+const NUMBER_OF_MIDI_CHANNELS: usize = 16;
+const NUMBER_OF_MIDI_NOTES: usize = 128;
+
+/// A [`MidiReader`] for rendering from a given start time (e.g. with
+/// [`RunConfig::with_start_frame`]), that keeps the notes sounding that have started
+/// before the start time, but have not ended yet.
+///
+/// The events before the start time are not returned. Instead, a note on event is returned
+/// (at the start time) for every note that is sounding at the start time, with the velocity
+/// of the original note on event. The events at or after the start time are returned unchanged.
+/// The timing of the events is still counted from the start of the event stream, as with
+/// the inner `MidiReader`.
+///
+/// _Note_: only notes are "chased", other events before the start time
+/// (e.g. control changes) are ignored.
+///
+/// [`MidiReader`]: ./trait.MidiReader.html
+/// [`RunConfig::with_start_frame`]: ./struct.RunConfig.html#method.with_start_frame
+pub struct ChasingMidiReader<R> {
+    inner: R,
+    start_in_microseconds: u64,
+    // The velocity of every note that is sounding at the start, `0` if the note is not sounding.
+    velocities: [[u8; NUMBER_OF_MIDI_NOTES]; NUMBER_OF_MIDI_CHANNELS],
+    caught_up: bool,
+    // The index (`channel * NUMBER_OF_MIDI_NOTES + note`) of the next note to check for
+    // a note on event.
+    next_note_index: usize,
+    // The first event at or after the start, with its time counted from the start of the stream.
+    first_event_after_start: Option<(u64, RawMidiEvent)>,
+    // The time of the last event that has been returned, counted from the start of the stream.
+    previous_time_in_microseconds: u64,
+}
+
+impl<R> ChasingMidiReader<R>
+where
+    R: MidiReader,
+{
+    /// Create a new `ChasingMidiReader` that starts at `start_in_microseconds`.
+    pub fn new(inner: R, start_in_microseconds: u64) -> Self {
+        Self {
+            inner,
+            start_in_microseconds,
+            velocities: [[0; NUMBER_OF_MIDI_NOTES]; NUMBER_OF_MIDI_CHANNELS],
+            caught_up: false,
+            next_note_index: 0,
+            first_event_after_start: None,
+            previous_time_in_microseconds: 0,
+        }
+    }
+
+    /// Create a new `ChasingMidiReader` that starts at `start_in_frames`, at a sample rate of
+    /// `frames_per_second`.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `frames_per_second` is `0`.
+    pub fn with_start_in_frames(inner: R, start_in_frames: u64, frames_per_second: u64) -> Self {
+        assert!(frames_per_second > 0);
+        // Round up, so that the start time in microseconds is not converted to an earlier frame.
+        let start_in_microseconds =
+            (start_in_frames * MICROSECONDS_PER_SECOND + frames_per_second - 1) / frames_per_second;
+        Self::new(inner, start_in_microseconds)
+    }
+
+    fn catch_up(&mut self) {
+        self.caught_up = true;
+        let mut time_in_microseconds = 0;
+        while let Some(event) = self.inner.read_event() {
+            time_in_microseconds += event.microseconds_since_previous_event;
+            if time_in_microseconds >= self.start_in_microseconds {
+                self.first_event_after_start = Some((time_in_microseconds, event.event));
+                return;
+            }
+            match event.event.parse() {
+                Some(MidiMessage::NoteOn {
+                    channel,
+                    note,
+                    velocity,
+                }) => {
+                    self.velocities[channel as usize][note as usize] = velocity;
+                }
+                Some(MidiMessage::NoteOff { channel, note, .. }) => {
+                    self.velocities[channel as usize][note as usize] = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn delta_event(
+        &mut self,
+        time_in_microseconds: u64,
+        event: RawMidiEvent,
+    ) -> DeltaEvent<RawMidiEvent> {
+        let microseconds_since_previous_event =
+            time_in_microseconds - self.previous_time_in_microseconds;
+        self.previous_time_in_microseconds = time_in_microseconds;
+        DeltaEvent {
+            microseconds_since_previous_event,
+            event,
+        }
+    }
+}
+
+impl<R> EventReader<RawMidiEvent> for ChasingMidiReader<R>
+where
+    R: MidiReader,
+{
+    fn read_event(&mut self) -> Option<DeltaEvent<RawMidiEvent>> {
+        if !self.caught_up {
+            self.catch_up();
+        }
+        while self.next_note_index < NUMBER_OF_MIDI_CHANNELS * NUMBER_OF_MIDI_NOTES {
+            let channel = self.next_note_index / NUMBER_OF_MIDI_NOTES;
+            let note = self.next_note_index % NUMBER_OF_MIDI_NOTES;
+            self.next_note_index += 1;
+            let velocity = self.velocities[channel][note];
+            if velocity > 0 {
+                let event = RawMidiEvent::from(MidiMessage::NoteOn {
+                    channel: channel as u8,
+                    note: note as u8,
+                    velocity,
+                });
+                return Some(self.delta_event(self.start_in_microseconds, event));
+            }
+        }
+        if let Some((time_in_microseconds, event)) = self.first_event_after_start.take() {
+            return Some(self.delta_event(time_in_microseconds, event));
+        }
+        self.inner.read_event()
+    }
+}
 
 /// Define how midi is written.
-/// _Note_: there is no "`MidiReader`"; we use `Iterator<Item = DeltaEvent<RawMidiEvent>>` for that.
 pub trait MidiWriter {
     fn write_event(&mut self, event: DeltaEvent<RawMidiEvent>);
 }
 
-// TODO: find a better name for this.
-/// A wrapper around a midi writer that implements `EventHandler<Timed<RawMidiEvent>>` by queueing
-/// the events, which can then be written to the encapsulated `MidiWriter` by calling `step_frames`.
-pub struct MidiWriterWrapper<W>
+impl<'w, W> MidiWriter for &'w mut W
+where
+    W: MidiWriter + ?Sized,
+{
+    fn write_event(&mut self, event: DeltaEvent<RawMidiEvent>) {
+        (**self).write_event(event)
+    }
+}
+
+/// The context that is passed to the plugin by the [`run`] function.
+///
+/// It implements `EventHandler<Timed<RawMidiEvent>>` by queueing the midi events,
+/// which can then be written to the encapsulated `MidiWriter` by calling `step_frames`.
+///
+/// It also implements [`Stop`]: when the plugin calls `stop()`, the [`run`] function stops
+/// after the audio of the current buffer has been written.
+///
+/// [`run`]: ./fn.run.html
+/// [`Stop`]: ../trait.Stop.html
+pub struct OfflineHost<W>
 where
     W: MidiWriter,
 {
@@ -143,7 +443,13 @@ where
     must_stop: bool,
 }
 
-impl<W> HostInterface for MidiWriterWrapper<W>
+/// The former name of [`OfflineHost`].
+///
+/// [`OfflineHost`]: ./struct.OfflineHost.html
+#[deprecated(since = "0.1.2", note = "Use `OfflineHost` instead.")]
+pub type MidiWriterWrapper<W> = OfflineHost<W>;
+
+impl<W> HostInterface for OfflineHost<W>
 where
     W: MidiWriter,
 {
@@ -156,14 +462,14 @@ where
     }
 }
 
-impl<W> Stop for MidiWriterWrapper<W> where W: MidiWriter {}
+impl<W> Stop for OfflineHost<W> where W: MidiWriter {}
 
-impl<W> MidiWriterWrapper<W>
+impl<W> OfflineHost<W>
 where
     W: MidiWriter,
 {
     pub fn new(inner: W, micro_seconds_per_frame: f64) -> Self {
-        MidiWriterWrapper {
+        OfflineHost {
             inner,
             previous_time_in_microseconds: 0,
             current_time_in_frames: 0,
@@ -192,7 +498,7 @@ where
     }
 }
 
-impl<W> EventHandler<Timed<RawMidiEvent>> for MidiWriterWrapper<W>
+impl<W> EventHandler<Timed<RawMidiEvent>> for OfflineHost<W>
 where
     W: MidiWriter,
 {
@@ -239,31 +545,229 @@ where
     }
 }
 
-/// Run an audio renderer with the given audio input, audio output, midi input and midi output.
+/// The error type that represents the errors you can get from the `seek` method
+/// of the [`AudioReader`] trait.
+///
+/// [`AudioReader`]: ./trait.AudioReader.html
+#[derive(Debug)]
+pub enum SeekError<ReaderErr> {
+    /// The reader does not support seeking.
+    Unsupported,
+    /// An error occurred when seeking.
+    ReaderError(ReaderErr),
+}
+
+impl<ReaderErr> Display for SeekError<ReaderErr>
+where
+    ReaderErr: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SeekError::Unsupported => write!(f, "Seeking is not supported"),
+            SeekError::ReaderError(ref e) => write!(f, "Error when seeking: {}", e),
+        }
+    }
+}
+
+impl<ReaderErr> Error for SeekError<ReaderErr>
+where
+    ReaderErr: Error,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SeekError::Unsupported => None,
+            SeekError::ReaderError(ref e) => e.source(),
+        }
+    }
+}
+
+/// The buffer size (in frames) that is used by a [`RunConfig`], unless specified otherwise.
 ///
-/// Parameters
-/// ==========
-/// * `buffer_size_in_frames`: the buffer size in frames.
+/// [`RunConfig`]: ./struct.RunConfig.html
+pub const DEFAULT_BUFFER_SIZE_IN_FRAMES: usize = 256;
+
+/// The configuration for the [`run`] function: the audio input, audio output, midi input,
+/// midi output and the buffer size.
 ///
-/// Panics
-/// ======
-/// Panics if `buffer_size_in_frames` is `0` or `> u32::MAX`.
-pub fn run<S, AudioIn, AudioOut, MidiIn, MidiOut, R>(
-    plugin: &mut R,
+/// A new `RunConfig` (created with [`new`]) has no midi input and no midi output
+/// (both are a [`MidiDummy`]) and uses a buffer size of [`DEFAULT_BUFFER_SIZE_IN_FRAMES`].
+/// Use the `with_...` methods to change this.
+///
+/// # Example
+/// ```
+/// use rsynth::backend::combined::dummy::{AudioDummy, MidiDummy};
+/// use rsynth::backend::combined::RunConfig;
+///
+/// let config = RunConfig::new(
+///     AudioDummy::<f32>::with_sample_rate_and_length(44100, 44100),
+///     AudioDummy::<f32>::with_sample_rate_and_length(44100, 0),
+/// )
+/// .with_buffer_size(64)
+/// .with_midi_out(MidiDummy::new());
+/// assert_eq!(config.buffer_size_in_frames(), 64);
+/// ```
+///
+/// [`run`]: ./fn.run.html
+/// [`new`]: #method.new
+/// [`MidiDummy`]: ./dummy/struct.MidiDummy.html
+/// [`DEFAULT_BUFFER_SIZE_IN_FRAMES`]: ./constant.DEFAULT_BUFFER_SIZE_IN_FRAMES.html
+pub struct RunConfig<AudioIn, AudioOut, MidiIn, MidiOut> {
     buffer_size_in_frames: usize,
-    mut audio_in: AudioIn,
-    mut audio_out: AudioOut,
+    start_frame: u64,
+    audio_in: AudioIn,
+    audio_out: AudioOut,
     midi_in: MidiIn,
     midi_out: MidiOut,
+}
+
+impl<AudioIn, AudioOut> RunConfig<AudioIn, AudioOut, MidiDummy, MidiDummy> {
+    /// Create a new `RunConfig` with the given audio input and audio output.
+    pub fn new(audio_in: AudioIn, audio_out: AudioOut) -> Self {
+        Self {
+            buffer_size_in_frames: DEFAULT_BUFFER_SIZE_IN_FRAMES,
+            start_frame: 0,
+            audio_in,
+            audio_out,
+            midi_in: MidiDummy::new(),
+            midi_out: MidiDummy::new(),
+        }
+    }
+}
+
+impl<AudioIn, AudioOut, MidiIn, MidiOut> RunConfig<AudioIn, AudioOut, MidiIn, MidiOut> {
+    /// Use the given buffer size (in frames).
+    ///
+    /// _Note_: the [`run`] function panics if the buffer size is `0` or `> u32::MAX`.
+    ///
+    /// [`run`]: ./fn.run.html
+    pub fn with_buffer_size(self, buffer_size_in_frames: usize) -> Self {
+        Self {
+            buffer_size_in_frames,
+            ..self
+        }
+    }
+
+    /// Start rendering at the given frame of the audio input and the midi input,
+    /// e.g. to render only a section of a file.
+    ///
+    /// The audio input is sought to `start_frame`. If the audio input does not support
+    /// seeking, the frames before `start_frame` are read and discarded.
+    /// The midi events before `start_frame` are skipped; the timing of the other midi events
+    /// (and of the midi output) is counted from `start_frame`.
+    /// In order to keep the notes sounding that have started before `start_frame`,
+    /// wrap the midi input in a [`ChasingMidiReader`].
+    ///
+    /// [`ChasingMidiReader`]: ./struct.ChasingMidiReader.html
+    pub fn with_start_frame(self, start_frame: u64) -> Self {
+        Self {
+            start_frame,
+            ..self
+        }
+    }
+
+    /// Read the events from the given [`EventReader`]. Despite its name,
+    /// this does not need to read midi events: any event type `E` can be used, as long as
+    /// the plugin implements `EventHandler<Timed<E>>`.
+    ///
+    /// [`EventReader`]: ./trait.EventReader.html
+    pub fn with_midi_in<M>(self, midi_in: M) -> RunConfig<AudioIn, AudioOut, M, MidiOut> {
+        RunConfig {
+            buffer_size_in_frames: self.buffer_size_in_frames,
+            start_frame: self.start_frame,
+            audio_in: self.audio_in,
+            audio_out: self.audio_out,
+            midi_in,
+            midi_out: self.midi_out,
+        }
+    }
+
+    /// Write the midi events to the given [`MidiWriter`].
+    ///
+    /// [`MidiWriter`]: ./trait.MidiWriter.html
+    pub fn with_midi_out<M>(self, midi_out: M) -> RunConfig<AudioIn, AudioOut, MidiIn, M> {
+        RunConfig {
+            buffer_size_in_frames: self.buffer_size_in_frames,
+            start_frame: self.start_frame,
+            audio_in: self.audio_in,
+            audio_out: self.audio_out,
+            midi_in: self.midi_in,
+            midi_out,
+        }
+    }
+
+    /// The buffer size, in frames.
+    pub fn buffer_size_in_frames(&self) -> usize {
+        self.buffer_size_in_frames
+    }
+
+    /// The frame at which rendering starts.
+    pub fn start_frame(&self) -> u64 {
+        self.start_frame
+    }
+}
+
+// Skip the first `number_of_frames` frames of the audio reader.
+fn skip_frames<S, AudioIn>(
+    audio_in: &mut AudioIn,
+    number_of_frames: u64,
+    buffers: &mut [Vec<S>],
+    buffer_size_in_frames: usize,
+) -> Result<(), AudioIn::Err>
+where
+    AudioIn: AudioReader<S>,
+    S: Copy + 'static,
+{
+    match audio_in.seek(number_of_frames) {
+        Ok(()) => return Ok(()),
+        Err(SeekError::ReaderError(e)) => return Err(e),
+        Err(SeekError::Unsupported) => {}
+    }
+    let mut remaining_frames = number_of_frames;
+    while remaining_frames > 0 {
+        let frames_to_read = std::cmp::min(remaining_frames, buffer_size_in_frames as u64) as usize;
+        let mut slices = buffers_as_mut_slice(buffers, frames_to_read);
+        let mut buffer = AudioBufferOut::new(&mut slices, frames_to_read);
+        let frames_read = audio_in.fill_buffer(&mut buffer)?;
+        if frames_read < frames_to_read {
+            break;
+        }
+        remaining_frames -= frames_read as u64;
+    }
+    Ok(())
+}
+
+/// Run an audio renderer with the audio input, audio output, midi input and midi output
+/// from the given [`RunConfig`].
+///
+/// Rendering ends when the audio input has no more frames, or when the plugin calls
+/// `stop()` on the [`OfflineHost`] that is passed as context.
+///
+/// Panics
+/// ======
+/// Panics if the buffer size is `0` or `> u32::MAX`.
+///
+/// [`RunConfig`]: ./struct.RunConfig.html
+/// [`OfflineHost`]: ./struct.OfflineHost.html
+pub fn run<S, AudioIn, AudioOut, MidiIn, MidiOut, R, E>(
+    plugin: &mut R,
+    config: RunConfig<AudioIn, AudioOut, MidiIn, MidiOut>,
 ) -> Result<(), CombinedError<<AudioIn as AudioReader<S>>::Err, <AudioOut as AudioWriter<S>>::Err>>
 where
     AudioIn: AudioReader<S>,
     AudioOut: AudioWriter<S>,
-    MidiIn: Iterator<Item = DeltaEvent<RawMidiEvent>>,
+    MidiIn: EventReader<E>,
     MidiOut: MidiWriter,
     S: Copy + Zero + 'static,
-    R: ContextualAudioRenderer<S, MidiWriterWrapper<MidiOut>> + EventHandler<Timed<RawMidiEvent>>,
+    R: ContextualAudioRenderer<S, OfflineHost<MidiOut>> + EventHandler<Timed<E>>,
 {
+    let RunConfig {
+        buffer_size_in_frames,
+        start_frame,
+        mut audio_in,
+        mut audio_out,
+        mut midi_in,
+        midi_out,
+    } = config;
     assert!(buffer_size_in_frames > 0);
     assert!(buffer_size_in_frames < u32::MAX as usize);
 
@@ -282,22 +786,33 @@ where
     let mut output_buffers =
         AudioChunk::zero(number_of_output_channels, buffer_size_in_frames).inner();
 
+    if start_frame > 0 {
+        skip_frames(
+            &mut audio_in,
+            start_frame,
+            &mut input_buffers,
+            buffer_size_in_frames,
+        )
+        .map_err(CombinedError::AudioInError)?;
+    }
+
     let mut last_time_in_frames = 0;
 
-    let mut writer = MidiWriterWrapper::new(
+    let mut writer = OfflineHost::new(
         midi_out,
         MICROSECONDS_PER_SECOND as f64 / frames_per_second as f64,
     );
 
     let mut last_event_time_in_microseconds = 0;
-    let mut input_midi_iterator_in_absolute_frames = midi_in
+    let mut input_midi_iterator_in_absolute_frames = std::iter::from_fn(|| midi_in.read_event())
         .map(|e| {
-            last_event_time_in_microseconds += e.microseconds_since_previous_event;
             (
-                last_event_time_in_microseconds * frames_per_second / MICROSECONDS_PER_SECOND,
+                e.absolute_time_in_frames(frames_per_second, &mut last_event_time_in_microseconds),
                 e.event,
             )
         })
+        .filter(|(time_in_frames, _)| *time_in_frames >= start_frame)
+        .map(|(time_in_frames, event)| (time_in_frames - start_frame, event))
         .peekable();
 
     let mut conversion_storage: VecStorage<&'static [S]> =
@@ -353,6 +868,142 @@ where
     Ok(())
 }
 
+/// The error type of a [`ChannelGroupsReader`].
+///
+/// [`ChannelGroupsReader`]: ./struct.ChannelGroupsReader.html
+#[derive(Debug)]
+pub enum ChannelGroupsError<FirstErr, SecondErr> {
+    /// An error occurred when reading the first group of channels.
+    FirstError(FirstErr),
+    /// An error occurred when reading the second group of channels.
+    SecondError(SecondErr),
+}
+
+impl<FirstErr, SecondErr> Display for ChannelGroupsError<FirstErr, SecondErr>
+where
+    FirstErr: Display,
+    SecondErr: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            ChannelGroupsError::FirstError(ref e) => {
+                write!(f, "Error reading the first group of channels: {}", e)
+            }
+            ChannelGroupsError::SecondError(ref e) => {
+                write!(f, "Error reading the second group of channels: {}", e)
+            }
+        }
+    }
+}
+
+impl<FirstErr, SecondErr> Error for ChannelGroupsError<FirstErr, SecondErr>
+where
+    FirstErr: Error,
+    SecondErr: Error,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ChannelGroupsError::FirstError(ref e) => e.source(),
+            ChannelGroupsError::SecondError(ref e) => e.source(),
+        }
+    }
+}
+
+/// An [`AudioReader`] that combines two audio readers: the channels of the first reader
+/// come first, followed by the channels of the second reader.
+///
+/// This can be used to feed e.g. a main signal and a sidechain signal to the [`run`] function.
+/// More than two audio readers can be combined by nesting `ChannelGroupsReader`s.
+///
+/// When one of the readers returns less frames than the other,
+/// the smallest number of frames is returned, so that rendering stops when one of the inputs ends.
+///
+/// Seeking seeks both readers to the same frame. When only the first reader supports seeking,
+/// the first reader has already moved when [`SeekError::Unsupported`] is returned.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Reading allocates memory.
+///
+/// [`AudioReader`]: ./trait.AudioReader.html
+/// [`run`]: ./fn.run.html
+/// [`SeekError::Unsupported`]: ./enum.SeekError.html#variant.Unsupported
+pub struct ChannelGroupsReader<First, Second> {
+    first: First,
+    second: Second,
+}
+
+impl<First, Second> ChannelGroupsReader<First, Second> {
+    /// Create a new `ChannelGroupsReader`.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `first` and `second` do not have the same sample rate.
+    pub fn new<S>(first: First, second: Second) -> Self
+    where
+        S: Copy,
+        First: AudioReader<S>,
+        Second: AudioReader<S>,
+    {
+        assert_eq!(
+            first.frames_per_second(),
+            second.frames_per_second(),
+            "All audio readers should have the same sample rate."
+        );
+        Self { first, second }
+    }
+}
+
+impl<S, First, Second> AudioReader<S> for ChannelGroupsReader<First, Second>
+where
+    S: Copy,
+    First: AudioReader<S>,
+    Second: AudioReader<S>,
+{
+    type Err = ChannelGroupsError<First::Err, Second::Err>;
+
+    fn number_of_channels(&self) -> usize {
+        self.first.number_of_channels() + self.second.number_of_channels()
+    }
+
+    fn frames_per_second(&self) -> u64 {
+        self.first.frames_per_second()
+    }
+
+    fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err> {
+        let number_of_frames = output.number_of_frames();
+        let mut channels: Vec<&mut [S]> = output.channel_iter_mut().collect();
+        let (first_channels, second_channels) =
+            channels.split_at_mut(self.first.number_of_channels());
+        let first_frames = self
+            .first
+            .fill_buffer(&mut AudioBufferOut::new(first_channels, number_of_frames))
+            .map_err(ChannelGroupsError::FirstError)?;
+        let second_frames = self
+            .second
+            .fill_buffer(&mut AudioBufferOut::new(second_channels, number_of_frames))
+            .map_err(ChannelGroupsError::SecondError)?;
+        Ok(std::cmp::min(first_frames, second_frames))
+    }
+
+    fn seek(&mut self, frame: u64) -> Result<(), SeekError<Self::Err>> {
+        match self.first.seek(frame) {
+            Ok(()) => {}
+            Err(SeekError::Unsupported) => return Err(SeekError::Unsupported),
+            Err(SeekError::ReaderError(e)) => {
+                return Err(SeekError::ReaderError(ChannelGroupsError::FirstError(e)))
+            }
+        }
+        match self.second.seek(frame) {
+            Ok(()) => Ok(()),
+            Err(SeekError::Unsupported) => Err(SeekError::Unsupported),
+            Err(SeekError::ReaderError(e)) => {
+                Err(SeekError::ReaderError(ChannelGroupsError::SecondError(e)))
+            }
+        }
+    }
+}
+
 /// An audio reader, useful for testing.
 pub struct TestAudioReader<'b, S>
 where
@@ -456,7 +1107,35 @@ where
     }
 }
 
-/// A midi reader, useful for testing.
+/// A midi reader that returns the events from memory, in the given order, useful for testing.
+///
+/// This allows to test the timing of notes with offline rendering without writing
+/// a `.mid` file first.
+///
+/// # Example
+/// ```
+/// use rsynth::backend::combined::{EventReader, TestMidiReader};
+/// use rsynth::event::{DeltaEvent, RawMidiEvent};
+///
+/// let note_on = DeltaEvent {
+///     microseconds_since_previous_event: 0,
+///     event: RawMidiEvent::new(&[0x90, 60, 100]),
+/// };
+/// // The note off event is one second after the note on event.
+/// let note_off = DeltaEvent {
+///     microseconds_since_previous_event: 1_000_000,
+///     event: RawMidiEvent::new(&[0x80, 60, 0]),
+/// };
+/// let mut reader = TestMidiReader::new(vec![note_on, note_off]);
+/// assert_eq!(reader.read_event(), Some(note_on));
+/// assert_eq!(reader.read_event(), Some(note_off));
+/// assert_eq!(reader.read_event(), None);
+/// ```
+///
+/// The reader can be used as the midi input of a [`RunConfig`] with [`with_midi_in`].
+///
+/// [`RunConfig`]: ./struct.RunConfig.html
+/// [`with_midi_in`]: ./struct.RunConfig.html#method.with_midi_in
 pub struct TestMidiReader {
     provided_events: Vec<DeltaEvent<RawMidiEvent>>,
     event_index: usize,
@@ -525,15 +1204,18 @@ impl MidiWriter for TestMidiWriter {
 mod tests {
     mod run {
         use super::super::{
-            dummy::MidiDummy,
             memory::{AudioBufferReader, AudioBufferWriter},
             DeltaEvent, TestAudioReader, TestAudioWriter,
         };
-        use crate::backend::combined::{TestMidiReader, TestMidiWriter};
-        use crate::buffer::AudioChunk;
-        use crate::event::{RawMidiEvent, Timed};
+        use crate::backend::combined::{
+            ChannelGroupsReader, ChasingMidiReader, EventReader, RunConfig, TestMidiReader,
+            TestMidiWriter, DEFAULT_BUFFER_SIZE_IN_FRAMES,
+        };
+        use crate::backend::Stop;
+        use crate::buffer::{AudioBufferInOut, AudioChunk};
+        use crate::event::{EventHandler, RawMidiEvent, Timed};
         use crate::test_utilities::TestPlugin;
-        use crate::{AudioHandler, AudioHandlerMeta};
+        use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
 
         struct DummyMeta;
 
@@ -589,25 +1271,26 @@ mod tests {
             let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
             super::super::run(
                 &mut test_plugin,
-                BUFFER_SIZE,
-                TestAudioReader::new(
-                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
-                    NUMBER_OF_CHANNELS,
-                    vec![
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                    ],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(BUFFER_SIZE),
-                ),
-                TestMidiReader::new(vec![input_event]),
-                MidiDummy::new(),
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                        NUMBER_OF_CHANNELS,
+                        vec![
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                        ],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(BUFFER_SIZE),
+                    ),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_in(TestMidiReader::new(vec![input_event])),
             )
             .expect("Unexpected error");
             test_plugin.check_last();
@@ -656,25 +1339,93 @@ mod tests {
             let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
             super::super::run(
                 &mut test_plugin,
-                BUFFER_SIZE,
-                TestAudioReader::new(
-                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
-                    NUMBER_OF_CHANNELS,
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                        NUMBER_OF_CHANNELS,
+                        vec![
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                        ],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(BUFFER_SIZE),
+                    ),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_in(TestMidiReader::new(vec![input_event1, input_event2])),
+            )
+            .expect("Unexpected error");
+            test_plugin.check_last();
+        }
+
+        #[test]
+        fn reads_several_events_in_the_same_future_buffer() {
+            const BUFFER_SIZE: usize = 3;
+            const NUMBER_OF_CHANNELS: usize = 1;
+            const SAMPLE_RATE: u64 = 8000;
+            let input_data = AudioChunk::<i16>::zero(1, 16);
+            let output_data = AudioChunk::<i16>::zero(1, 16);
+
+            // So 1 frame  is 1/8000 seconds = 125 microseconds.
+            let event1 = RawMidiEvent::new(&[1, 2, 3]);
+            let event2 = RawMidiEvent::new(&[4, 5, 6]);
+            let event3 = RawMidiEvent::new(&[7, 8, 9]);
+            let event4 = RawMidiEvent::new(&[10, 11, 12]);
+            // Events are expected at frames 9, 10, 11 and 13:
+            // 0 1 2 3 4 5 6 7 8 9 A B C D
+            // . . .|. . .|. . .|1 2 3|. 4 .|.
+            let input_events = vec![
+                DeltaEvent {
+                    microseconds_since_previous_event: 1125,
+                    event: event1,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 125,
+                    event: event2,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 125,
+                    event: event3,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 250,
+                    event: event4,
+                },
+            ];
+
+            let mut test_plugin = TestPlugin::new(
+                input_data.clone().split(BUFFER_SIZE),
+                output_data.clone().split(BUFFER_SIZE),
+                vec![
+                    vec![],
+                    vec![],
+                    vec![],
                     vec![
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
+                        Timed::new(0, event1),
+                        Timed::new(1, event2),
+                        Timed::new(2, event3),
                     ],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(BUFFER_SIZE),
-                ),
-                TestMidiReader::new(vec![input_event1, input_event2]),
-                MidiDummy::new(),
+                    vec![Timed::new(1, event4)],
+                    vec![],
+                ],
+                vec![Vec::new(); 6],
+                DummyMeta,
+            );
+            let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
+            super::super::run(
+                &mut test_plugin,
+                RunConfig::new(
+                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                    AudioBufferWriter::new(&mut output_buffer),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_in(TestMidiReader::new(input_events)),
             )
             .expect("Unexpected error");
             test_plugin.check_last();
@@ -698,23 +1449,90 @@ mod tests {
             let mut output_buffer = AudioChunk::new(2);
             super::super::run(
                 &mut test_plugin,
-                2,
-                TestAudioReader::new(
-                    AudioBufferReader::new(&input_data, EXPECTED_SAMPLE_RATE as u64),
-                    2,
-                    vec![buffer_size; 4],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(buffer_size),
-                ),
-                MidiDummy::new(),
-                MidiDummy::new(),
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, EXPECTED_SAMPLE_RATE as u64),
+                        2,
+                        vec![buffer_size; 4],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(buffer_size),
+                    ),
+                )
+                .with_buffer_size(2),
             )
             .expect("Unexpected error.");
             assert_eq!(output_buffer, output_data);
         }
 
+        #[test]
+        fn starts_rendering_at_the_start_frame() {
+            const BUFFER_SIZE: usize = 4;
+            const SAMPLE_RATE: u64 = 8000;
+            const START_FRAME: u64 = 6;
+            let input_data = audio_chunk![[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]];
+            let expected_input_data = audio_chunk![[6, 7, 8, 9, 10, 11, 12, 13, 14, 15]];
+            let output_data = audio_chunk![[-6, -7, -8, -9, -10, -11, -12, -13, -14, -15]];
+
+            // 1 frame is 1/8000 seconds = 125 microseconds.
+            let note_on_60 = RawMidiEvent::new(&[0x90, 60, 100]);
+            let note_on_62 = RawMidiEvent::new(&[0x90, 62, 90]);
+            let note_off_62 = RawMidiEvent::new(&[0x80, 62, 0]);
+            let control_change = RawMidiEvent::new(&[0xB0, 7, 80]);
+            // Frame:  0 1 2 3 4 5 6 7 8
+            // Events:         A B   C D  (B starts and ends at frame 5)
+            let input_events = vec![
+                DeltaEvent {
+                    microseconds_since_previous_event: 500,
+                    event: note_on_60,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 125,
+                    event: note_on_62,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 75,
+                    event: note_off_62,
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 300,
+                    event: control_change,
+                },
+            ];
+
+            // The note that is still sounding at the start frame is restarted at the start.
+            let mut test_plugin = TestPlugin::new(
+                expected_input_data.split(BUFFER_SIZE),
+                output_data.clone().split(BUFFER_SIZE),
+                vec![
+                    vec![Timed::new(0, note_on_60), Timed::new(2, control_change)],
+                    vec![],
+                    vec![],
+                ],
+                vec![Vec::new(); 3],
+                DummyMeta,
+            );
+            let mut output_buffer = AudioChunk::new(1);
+            super::super::run(
+                &mut test_plugin,
+                RunConfig::new(
+                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                    AudioBufferWriter::new(&mut output_buffer),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_start_frame(START_FRAME)
+                .with_midi_in(ChasingMidiReader::with_start_in_frames(
+                    TestMidiReader::new(input_events),
+                    START_FRAME,
+                    SAMPLE_RATE,
+                )),
+            )
+            .expect("Unexpected error");
+            test_plugin.check_last();
+            assert_eq!(output_buffer, output_data);
+        }
+
         #[test]
         fn writes_events_at_the_right_time() {
             const BUFFER_SIZE: usize = 3;
@@ -751,25 +1569,26 @@ mod tests {
             let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
             super::super::run(
                 &mut test_plugin,
-                BUFFER_SIZE,
-                TestAudioReader::new(
-                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
-                    NUMBER_OF_CHANNELS,
-                    vec![
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                    ],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(BUFFER_SIZE),
-                ),
-                MidiDummy::new(),
-                TestMidiWriter::new(vec![input_event]),
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                        NUMBER_OF_CHANNELS,
+                        vec![
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                        ],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(BUFFER_SIZE),
+                    ),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_out(TestMidiWriter::new(vec![input_event])),
             )
             .expect("No errors are expected");
         }
@@ -813,25 +1632,26 @@ mod tests {
             let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
             super::super::run(
                 &mut test_plugin,
-                BUFFER_SIZE,
-                TestAudioReader::new(
-                    AudioBufferReader::new(&input_data, SAMPLE_RATE),
-                    NUMBER_OF_CHANNELS,
-                    vec![
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                    ],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(BUFFER_SIZE),
-                ),
-                MidiDummy::new(),
-                TestMidiWriter::new(vec![output_event1, output_event2]),
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                        NUMBER_OF_CHANNELS,
+                        vec![
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                        ],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(BUFFER_SIZE),
+                    ),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_out(TestMidiWriter::new(vec![output_event1, output_event2])),
             )
             .expect("Unexpected error.");
         }
@@ -872,27 +1692,425 @@ mod tests {
             let mut output_buffer = AudioChunk::new(NUMBER_OF_CHANNELS);
             super::super::run(
                 &mut test_plugin,
-                BUFFER_SIZE,
-                TestAudioReader::new(
+                RunConfig::new(
+                    TestAudioReader::new(
+                        AudioBufferReader::new(&input_data, SAMPLE_RATE),
+                        NUMBER_OF_CHANNELS,
+                        vec![
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                            BUFFER_SIZE,
+                        ],
+                    ),
+                    TestAudioWriter::new(
+                        &mut AudioBufferWriter::new(&mut output_buffer),
+                        output_data.clone().split(BUFFER_SIZE),
+                    ),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_out(TestMidiWriter::new(vec![output_event1, output_event2])),
+            )
+            .expect("Unexpected error.");
+        }
+
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        enum CustomEvent {
+            Start,
+            Stop,
+        }
+
+        struct CustomEventReader {
+            events: Vec<DeltaEvent<CustomEvent>>,
+        }
+
+        impl EventReader<CustomEvent> for CustomEventReader {
+            fn read_event(&mut self) -> Option<DeltaEvent<CustomEvent>> {
+                if self.events.is_empty() {
+                    None
+                } else {
+                    Some(self.events.remove(0))
+                }
+            }
+        }
+
+        struct CustomEventRecorder {
+            received_events: Vec<(usize, Timed<CustomEvent>)>,
+            buffer_index: usize,
+        }
+
+        impl EventHandler<Timed<CustomEvent>> for CustomEventRecorder {
+            fn handle_event(&mut self, event: Timed<CustomEvent>) {
+                self.received_events.push((self.buffer_index, event));
+            }
+        }
+
+        impl<S, C> ContextualAudioRenderer<S, C> for CustomEventRecorder
+        where
+            S: Copy,
+        {
+            fn render_buffer(&mut self, _buffer: &mut AudioBufferInOut<S>, _context: &mut C) {
+                self.buffer_index += 1;
+            }
+        }
+
+        #[test]
+        fn reads_custom_events_at_the_right_time() {
+            const BUFFER_SIZE: usize = 3;
+            const SAMPLE_RATE: u64 = 8000;
+            let input_data = AudioChunk::<i16>::zero(1, 16);
+            let mut output_buffer = AudioChunk::new(1);
+
+            // 8 frames is 1000 microseconds.
+            let reader = CustomEventReader {
+                events: vec![
+                    DeltaEvent {
+                        microseconds_since_previous_event: 125,
+                        event: CustomEvent::Start,
+                    },
+                    DeltaEvent {
+                        microseconds_since_previous_event: 875,
+                        event: CustomEvent::Stop,
+                    },
+                ],
+            };
+            let mut plugin = CustomEventRecorder {
+                received_events: Vec::new(),
+                buffer_index: 0,
+            };
+            super::super::run(
+                &mut plugin,
+                RunConfig::new(
                     AudioBufferReader::new(&input_data, SAMPLE_RATE),
-                    NUMBER_OF_CHANNELS,
-                    vec![
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                        BUFFER_SIZE,
-                    ],
-                ),
-                TestAudioWriter::new(
-                    &mut AudioBufferWriter::new(&mut output_buffer),
-                    output_data.clone().split(BUFFER_SIZE),
-                ),
-                MidiDummy::new(),
-                TestMidiWriter::new(vec![output_event1, output_event2]),
+                    AudioBufferWriter::new(&mut output_buffer),
+                )
+                .with_buffer_size(BUFFER_SIZE)
+                .with_midi_in(reader),
+            )
+            .expect("Unexpected error.");
+            assert_eq!(
+                plugin.received_events,
+                vec![
+                    (0, Timed::new(1, CustomEvent::Start)),
+                    (2, Timed::new(2, CustomEvent::Stop))
+                ]
+            );
+        }
+
+        // Passes the first input channel (the main signal) to the output,
+        // attenuated when the second input channel (the sidechain) is loud.
+        struct SidechainDucker;
+
+        impl<C> ContextualAudioRenderer<f32, C> for SidechainDucker {
+            fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut C) {
+                for frame in 0..buffer.number_of_frames() {
+                    let main = buffer.index_input_channel(0)[frame];
+                    let sidechain = buffer.index_input_channel(1)[frame];
+                    let gain = if sidechain.abs() > 0.5 { 0.25 } else { 1.0 };
+                    buffer.index_output_channel(0)[frame] = main * gain;
+                }
+            }
+        }
+
+        impl EventHandler<Timed<RawMidiEvent>> for SidechainDucker {
+            fn handle_event(&mut self, _event: Timed<RawMidiEvent>) {}
+        }
+
+        #[test]
+        fn sidechain_input_is_read_from_a_separate_audio_reader() {
+            const SAMPLE_RATE: u64 = 8000;
+            let main = audio_chunk![[1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]];
+            let sidechain = audio_chunk![[0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0]];
+            let mut output_buffer = AudioChunk::new(1);
+            super::super::run(
+                &mut SidechainDucker,
+                RunConfig::new(
+                    ChannelGroupsReader::new(
+                        AudioBufferReader::new(&main, SAMPLE_RATE),
+                        AudioBufferReader::new(&sidechain, SAMPLE_RATE),
+                    ),
+                    AudioBufferWriter::new(&mut output_buffer),
+                )
+                .with_buffer_size(3),
+            )
+            .expect("Unexpected error.");
+            assert_eq!(
+                output_buffer,
+                audio_chunk![[1.0, 1.0, 1.0, 0.25, 0.25, 0.25, 1.0, 1.0]]
+            );
+        }
+
+        #[test]
+        fn run_config_uses_the_default_buffer_size_and_no_midi() {
+            const SAMPLE_RATE: u64 = 8000;
+            const NUMBER_OF_FRAMES: usize = DEFAULT_BUFFER_SIZE_IN_FRAMES + 44;
+            let input = AudioChunk::from_channels(vec![
+                vec![0.5; NUMBER_OF_FRAMES],
+                vec![0.0; NUMBER_OF_FRAMES],
+            ]);
+            let mut output_buffer = AudioChunk::new(1);
+            let config = RunConfig::new(
+                AudioBufferReader::new(&input, SAMPLE_RATE),
+                AudioBufferWriter::new(&mut output_buffer),
+            );
+            assert_eq!(
+                config.buffer_size_in_frames(),
+                DEFAULT_BUFFER_SIZE_IN_FRAMES
+            );
+            super::super::run(&mut SidechainDucker, config).expect("Unexpected error.");
+            assert_eq!(
+                output_buffer,
+                AudioChunk::from_channels(vec![vec![0.5; NUMBER_OF_FRAMES]])
+            );
+        }
+
+        // Plays a constant signal and stops after the given number of buffers.
+        struct StoppingPlugin {
+            remaining_buffers: usize,
+        }
+
+        impl EventHandler<Timed<RawMidiEvent>> for StoppingPlugin {
+            fn handle_event(&mut self, _event: Timed<RawMidiEvent>) {}
+        }
+
+        impl<H> ContextualAudioRenderer<f32, H> for StoppingPlugin
+        where
+            H: Stop,
+        {
+            fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut H) {
+                buffer.outputs().set(0.5);
+                self.remaining_buffers -= 1;
+                if self.remaining_buffers == 0 {
+                    context.stop();
+                }
+            }
+        }
+
+        #[test]
+        fn stops_when_the_plugin_calls_stop() {
+            const SAMPLE_RATE: u64 = 8000;
+            let input = AudioChunk::from_channels(vec![vec![0.0; 20]]);
+            let mut output_buffer = AudioChunk::new(1);
+            super::super::run(
+                &mut StoppingPlugin {
+                    remaining_buffers: 2,
+                },
+                RunConfig::new(
+                    AudioBufferReader::new(&input, SAMPLE_RATE),
+                    AudioBufferWriter::new(&mut output_buffer),
+                )
+                .with_buffer_size(4),
+            )
+            .expect("Unexpected error.");
+            // The audio of the buffer in which `stop` was called is still written.
+            assert_eq!(output_buffer, AudioChunk::from_channels(vec![vec![0.5; 8]]));
+        }
+
+        // Plays a constant signal and echoes the midi events that it receives.
+        #[cfg(feature = "backend-combined-midly-0-5")]
+        struct MidiEcho {
+            received_events: Vec<Timed<RawMidiEvent>>,
+        }
+
+        #[cfg(feature = "backend-combined-midly-0-5")]
+        impl EventHandler<Timed<RawMidiEvent>> for MidiEcho {
+            fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+                self.received_events.push(event);
+            }
+        }
+
+        #[cfg(feature = "backend-combined-midly-0-5")]
+        impl<C> ContextualAudioRenderer<f32, C> for MidiEcho
+        where
+            C: EventHandler<Timed<RawMidiEvent>>,
+        {
+            fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut C) {
+                buffer.outputs().set(0.5);
+                for event in self.received_events.drain(..) {
+                    context.handle_event(event);
+                }
+            }
+        }
+
+        #[cfg(feature = "backend-combined-midly-0-5")]
+        #[test]
+        fn writes_audio_and_midi_at_the_same_time() {
+            use crate::backend::combined::dummy::AudioDummy;
+            use crate::backend::combined::midly::MidlyMidiWriter;
+            const SAMPLE_RATE: u32 = 8000;
+            let mut plugin = MidiEcho {
+                received_events: Vec::new(),
+            };
+            let mut audio_output = AudioChunk::new(1);
+            let mut midi_output = MidlyMidiWriter::new(480);
+            super::super::run(
+                &mut plugin,
+                RunConfig::new(
+                    AudioDummy::with_sample_rate_and_length(SAMPLE_RATE, SAMPLE_RATE as usize),
+                    AudioBufferWriter::new(&mut audio_output),
+                )
+                .with_buffer_size(256)
+                .with_midi_in(TestMidiReader::new(vec![DeltaEvent {
+                    microseconds_since_previous_event: 500_000,
+                    event: RawMidiEvent::new(&[0x90, 60, 90]),
+                }]))
+                .with_midi_out(&mut midi_output),
             )
             .expect("Unexpected error.");
+            assert_eq!(
+                audio_output,
+                AudioChunk::from_channels(vec![vec![0.5; SAMPLE_RATE as usize]])
+            );
+            // The tempo event, followed by the echoed note on event after one beat.
+            let track = midi_output.track();
+            assert_eq!(track.len(), 2);
+            assert_eq!(track[1].delta.as_int(), 480);
+        }
+    }
+
+    mod channel_groups_reader {
+        use super::super::memory::AudioBufferReader;
+        use super::super::{AudioReader, ChannelGroupsReader};
+        use crate::buffer::{AudioBufferOut, AudioChunk};
+
+        #[test]
+        fn seeking_seeks_both_readers() {
+            let first = audio_chunk![[1, 2, 3, 4]];
+            let second = audio_chunk![[5, 6, 7, 8]];
+            let mut reader = ChannelGroupsReader::new(
+                AudioBufferReader::new(&first, 16),
+                AudioBufferReader::new(&second, 16),
+            );
+            reader.seek(2).expect("Unexpected error.");
+            let mut output_buffer = AudioChunk::zero(2, 4);
+            let mut slices = output_buffer.as_mut_slices();
+            {
+                let mut buffers = AudioBufferOut::new(&mut slices, 4);
+                assert_eq!(
+                    reader.fill_buffer(&mut buffers).expect("Unexpected error."),
+                    2
+                );
+            }
+            assert_eq!(&slices[0][..2], &[3, 4]);
+            assert_eq!(&slices[1][..2], &[7, 8]);
+        }
+    }
+
+    mod shifted_midi_reader {
+        use super::super::{DeltaEvent, EventReader, ShiftedMidiReader, TestMidiReader};
+        use crate::event::RawMidiEvent;
+
+        fn events() -> Vec<DeltaEvent<RawMidiEvent>> {
+            vec![
+                DeltaEvent {
+                    microseconds_since_previous_event: 10,
+                    event: RawMidiEvent::new(&[0x90, 60, 100]),
+                },
+                DeltaEvent {
+                    microseconds_since_previous_event: 20,
+                    event: RawMidiEvent::new(&[0x80, 60, 0]),
+                },
+            ]
+        }
+
+        #[test]
+        fn only_the_first_event_is_shifted() {
+            let mut reader = ShiftedMidiReader::new(TestMidiReader::new(events()), 1000);
+            let expected = events();
+            assert_eq!(
+                reader.read_event(),
+                Some(DeltaEvent {
+                    microseconds_since_previous_event: 1010,
+                    event: expected[0].event,
+                })
+            );
+            assert_eq!(reader.read_event(), Some(expected[1]));
+            assert_eq!(reader.read_event(), None::<DeltaEvent<RawMidiEvent>>);
+        }
+
+        #[test]
+        fn offset_can_be_specified_in_frames() {
+            let mut reader = ShiftedMidiReader::with_offset_in_frames(
+                TestMidiReader::new(events()),
+                4410,
+                44100,
+            );
+            let first: DeltaEvent<RawMidiEvent> = reader.read_event().expect("Expected an event.");
+            assert_eq!(first.microseconds_since_previous_event, 100_010);
+            let second: DeltaEvent<RawMidiEvent> = reader.read_event().expect("Expected an event.");
+            assert_eq!(second.microseconds_since_previous_event, 20);
+        }
+    }
+
+    mod chasing_midi_reader {
+        use super::super::{ChasingMidiReader, DeltaEvent, EventReader, TestMidiReader};
+        use crate::event::RawMidiEvent;
+
+        #[test]
+        fn sounding_notes_are_restarted_at_the_start() {
+            let note_on_60 = RawMidiEvent::new(&[0x90, 60, 100]);
+            let note_on_62 = RawMidiEvent::new(&[0x90, 62, 90]);
+            let note_off_62 = RawMidiEvent::new(&[0x80, 62, 0]);
+            let control_change = RawMidiEvent::new(&[0xB0, 7, 80]);
+            let mut reader = ChasingMidiReader::new(
+                TestMidiReader::new(vec![
+                    DeltaEvent {
+                        microseconds_since_previous_event: 10,
+                        event: note_on_60,
+                    },
+                    DeltaEvent {
+                        microseconds_since_previous_event: 10,
+                        event: note_on_62,
+                    },
+                    DeltaEvent {
+                        microseconds_since_previous_event: 10,
+                        event: note_off_62,
+                    },
+                    DeltaEvent {
+                        microseconds_since_previous_event: 20,
+                        event: control_change,
+                    },
+                ]),
+                40,
+            );
+            assert_eq!(
+                reader.read_event(),
+                Some(DeltaEvent {
+                    microseconds_since_previous_event: 40,
+                    event: note_on_60,
+                })
+            );
+            assert_eq!(
+                reader.read_event(),
+                Some(DeltaEvent {
+                    microseconds_since_previous_event: 10,
+                    event: control_change,
+                })
+            );
+            assert_eq!(reader.read_event(), None);
+        }
+    }
+
+    mod test_midi_reader {
+        use super::super::{DeltaEvent, EventReader, TestMidiReader};
+        use crate::event::RawMidiEvent;
+
+        #[test]
+        fn returns_the_events_in_the_given_order() {
+            let events: Vec<_> = (0..3)
+                .map(|i| DeltaEvent {
+                    microseconds_since_previous_event: 100 * i,
+                    event: RawMidiEvent::new(&[0x90, 60 + i as u8, 100]),
+                })
+                .collect();
+            let mut reader = TestMidiReader::new(events.clone());
+            for event in events {
+                assert_eq!(reader.read_event(), Some(event));
+            }
+            assert_eq!(reader.read_event(), None);
+            assert_eq!(reader.read_event(), None);
         }
     }
 }
@@ -0,0 +1,285 @@
+//! Backend for reading and writing raw (headerless) interleaved PCM audio, e.g. the
+//! `f32le` or `s16le` formats of `ffmpeg`.
+//!
+//! Samples are stored in little-endian byte order, the samples of one frame are stored
+//! next to each other (one sample per channel).
+//! Because raw PCM carries no header, the number of channels and the sample rate
+//! must be given explicitly.
+use super::{AudioReader, AudioWriter};
+use crate::buffer::{AudioBufferIn, AudioBufferOut};
+use std::convert::TryInto;
+use std::io::{ErrorKind, Read, Write};
+use std::marker::PhantomData;
+
+/// A sample type that can be stored in raw PCM audio.
+pub trait RawPcmSample: Copy {
+    /// The number of bytes that are used to store one sample.
+    const SIZE_IN_BYTES: usize;
+
+    /// Decode the sample from its little-endian representation.
+    /// `bytes` contains exactly `SIZE_IN_BYTES` bytes.
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+
+    /// Encode the sample in its little-endian representation.
+    /// `bytes` contains exactly `SIZE_IN_BYTES` bytes.
+    fn write_le_bytes(self, bytes: &mut [u8]);
+}
+
+macro_rules! raw_pcm_sample_impl {
+    ($type:ty, $size:expr) => {
+        impl RawPcmSample for $type {
+            const SIZE_IN_BYTES: usize = $size;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                <$type>::from_le_bytes(bytes.try_into().expect("Unexpected number of bytes"))
+            }
+
+            fn write_le_bytes(self, bytes: &mut [u8]) {
+                bytes.copy_from_slice(&self.to_le_bytes());
+            }
+        }
+    };
+}
+
+raw_pcm_sample_impl!(i16, 2);
+raw_pcm_sample_impl!(i32, 4);
+raw_pcm_sample_impl!(f32, 4);
+raw_pcm_sample_impl!(f64, 8);
+
+/// Read raw interleaved PCM audio from a [`Read`].
+///
+/// The type parameter `S` is the sample type, both in the raw data and in the buffers
+/// that are filled.
+///
+/// _Note_: `RawPcmReader` reads many bytes at once, so there is no need to wrap a file in a
+/// `BufReader`.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// `fill_buffer` may allocate memory the first time it is called with a larger buffer.
+///
+/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+pub struct RawPcmReader<R: Read, S> {
+    reader: R,
+    number_of_channels: usize,
+    frames_per_second: u64,
+    bytes: Vec<u8>,
+    _phantom: PhantomData<S>,
+}
+
+impl<R: Read, S> RawPcmReader<R, S> {
+    /// Create a new `RawPcmReader` that reads audio with the given number of channels
+    /// and sample rate.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `number_of_channels` is `0`.
+    pub fn new(reader: R, number_of_channels: usize, frames_per_second: u64) -> Self {
+        assert!(number_of_channels > 0);
+        Self {
+            reader,
+            number_of_channels,
+            frames_per_second,
+            bytes: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Get back the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read, S> AudioReader<S> for RawPcmReader<R, S>
+where
+    S: RawPcmSample,
+{
+    type Err = std::io::Error;
+
+    fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+
+    fn frames_per_second(&self) -> u64 {
+        self.frames_per_second
+    }
+
+    /// Fill the buffer.
+    ///
+    /// Returns an error of kind `UnexpectedEof` if the data ends in the middle of a frame.
+    fn fill_buffer(&mut self, output: &mut AudioBufferOut<S>) -> Result<usize, Self::Err> {
+        assert_eq!(output.number_of_channels(), self.number_of_channels);
+        let frame_size_in_bytes = self.number_of_channels * S::SIZE_IN_BYTES;
+        self.bytes
+            .resize(output.number_of_frames() * frame_size_in_bytes, 0);
+        let mut bytes_read = 0;
+        while bytes_read < self.bytes.len() {
+            match self.reader.read(&mut self.bytes[bytes_read..]) {
+                Ok(0) => break,
+                Ok(n) => bytes_read += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if bytes_read % frame_size_in_bytes != 0 {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                "raw PCM data ends in the middle of a frame",
+            ));
+        }
+        let frames_read = bytes_read / frame_size_in_bytes;
+        for (channel_index, channel) in output.channel_iter_mut().enumerate() {
+            for (frame_index, sample) in channel[..frames_read].iter_mut().enumerate() {
+                let start = frame_index * frame_size_in_bytes + channel_index * S::SIZE_IN_BYTES;
+                *sample = S::from_le_bytes(&self.bytes[start..start + S::SIZE_IN_BYTES]);
+            }
+        }
+        Ok(frames_read)
+    }
+}
+
+/// Write raw interleaved PCM audio to a [`Write`].
+///
+/// The type parameter `S` is the sample type, both in the buffers that are written
+/// and in the raw data.
+///
+/// _Note_: `RawPcmWriter` writes many bytes at once, so there is no need to wrap a file in a
+/// `BufWriter`.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// `write_buffer` may allocate memory the first time it is called with a larger buffer.
+///
+/// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+pub struct RawPcmWriter<W: Write, S> {
+    writer: W,
+    number_of_channels: usize,
+    frames_per_second: u64,
+    bytes: Vec<u8>,
+    _phantom: PhantomData<S>,
+}
+
+impl<W: Write, S> RawPcmWriter<W, S> {
+    /// Create a new `RawPcmWriter` that writes audio with the given number of channels
+    /// and sample rate.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `number_of_channels` is `0`.
+    pub fn new(writer: W, number_of_channels: usize, frames_per_second: u64) -> Self {
+        assert!(number_of_channels > 0);
+        Self {
+            writer,
+            number_of_channels,
+            frames_per_second,
+            bytes: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The sampling frequency in frames per second.
+    ///
+    /// _Note_: the sample rate is not stored in the raw data. It is only
+    /// kept here for reference.
+    pub fn frames_per_second(&self) -> u64 {
+        self.frames_per_second
+    }
+
+    /// Get back the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write, S> AudioWriter<S> for RawPcmWriter<W, S>
+where
+    S: RawPcmSample,
+{
+    type Err = std::io::Error;
+
+    fn write_buffer(&mut self, buffer: &AudioBufferIn<S>) -> Result<(), Self::Err> {
+        assert_eq!(buffer.number_of_channels(), self.number_of_channels);
+        let frame_size_in_bytes = self.number_of_channels * S::SIZE_IN_BYTES;
+        self.bytes
+            .resize(buffer.number_of_frames() * frame_size_in_bytes, 0);
+        for (channel_index, channel) in buffer.channels().iter().enumerate() {
+            for (frame_index, sample) in channel[..buffer.number_of_frames()].iter().enumerate() {
+                let start = frame_index * frame_size_in_bytes + channel_index * S::SIZE_IN_BYTES;
+                sample.write_le_bytes(&mut self.bytes[start..start + S::SIZE_IN_BYTES]);
+            }
+        }
+        self.writer.write_all(&self.bytes)
+    }
+
+    fn specifies_number_of_channels(&self) -> bool {
+        true
+    }
+
+    fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RawPcmReader, RawPcmWriter};
+    use crate::backend::combined::{AudioReader, AudioWriter};
+    use crate::buffer::{AudioBufferIn, AudioBufferOut};
+    use std::io::ErrorKind;
+
+    #[test]
+    fn reads_interleaved_i16_samples() {
+        let bytes: Vec<u8> = vec![1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 0xFF, 0xFF];
+        let mut reader = RawPcmReader::<_, i16>::new(&bytes[..], 2, 44100);
+        let mut left = vec![0; 4];
+        let mut right = vec![0; 4];
+        let frames_read = {
+            let mut channels = [left.as_mut_slice(), right.as_mut_slice()];
+            reader
+                .fill_buffer(&mut AudioBufferOut::new(&mut channels, 4))
+                .expect("Unexpected error")
+        };
+        assert_eq!(frames_read, 3);
+        assert_eq!(&left[..3], &[1, 3, 5]);
+        assert_eq!(&right[..3], &[2, 4, -1]);
+    }
+
+    #[test]
+    fn incomplete_frame_is_an_error() {
+        let bytes: Vec<u8> = vec![1, 0, 2, 0, 3, 0];
+        let mut reader = RawPcmReader::<_, i16>::new(&bytes[..], 2, 44100);
+        let mut left = vec![0; 4];
+        let mut right = vec![0; 4];
+        let mut channels = [left.as_mut_slice(), right.as_mut_slice()];
+        let error = reader
+            .fill_buffer(&mut AudioBufferOut::new(&mut channels, 4))
+            .expect_err("Expected an error");
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn written_f32_samples_can_be_read_back() {
+        let left: &[f32] = &[0.0, 0.25, -0.5];
+        let right: &[f32] = &[1.0, -1.0, 0.125];
+        let mut writer = RawPcmWriter::<_, f32>::new(Vec::new(), 2, 48000);
+        writer
+            .write_buffer(&AudioBufferIn::new(&[left, right], 3))
+            .expect("Unexpected error");
+        let bytes = writer.into_inner();
+        assert_eq!(bytes.len(), 3 * 2 * 4);
+
+        let mut reader = RawPcmReader::<_, f32>::new(&bytes[..], 2, 48000);
+        let mut read_left = vec![0.0; 3];
+        let mut read_right = vec![0.0; 3];
+        let frames_read = {
+            let mut channels = [read_left.as_mut_slice(), read_right.as_mut_slice()];
+            reader
+                .fill_buffer(&mut AudioBufferOut::new(&mut channels, 3))
+                .expect("Unexpected error")
+        };
+        assert_eq!(frames_read, 3);
+        assert_eq!(read_left, left);
+        assert_eq!(read_right, right);
+    }
+}
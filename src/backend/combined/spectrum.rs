@@ -0,0 +1,239 @@
+//! Backend for computing the spectrum of the audio that is written, based on the `rustfft` crate.
+//! This is mostly useful for making assertions in the frequency domain in tests.
+use super::AudioWriter;
+use crate::buffer::AudioBufferIn;
+use num_traits::AsPrimitive;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// Re-exports from the `rustfft` crate (version range 6.x.x).
+pub mod rustfft_6 {
+    pub use rustfft_6::*;
+}
+
+use self::rustfft_6::num_complex::Complex;
+use self::rustfft_6::{Fft, FftPlanner};
+
+/// An [`AudioWriter`] that computes the magnitude spectrum of the audio that is written to it.
+///
+/// The audio is mixed down to one channel and cut into non-overlapping frames of
+/// `frame_size` samples.
+/// Each frame is multiplied with a Hann window before its FFT is computed.
+/// Samples that do not fill a complete frame at the end of the audio are ignored.
+///
+/// _Note_: `AudioWriter` is also implemented for `&mut SpectrumWriter`, so you can pass
+/// a mutable reference to the [`run`] function and inspect the spectrum afterwards.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// The spectrum of every frame is stored, so this allocates memory.
+///
+/// [`AudioWriter`]: ../trait.AudioWriter.html
+/// [`run`]: ../fn.run.html
+pub struct SpectrumWriter {
+    number_of_channels: usize,
+    frames_per_second: u64,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    pending_samples: Vec<f32>,
+    spectra: Vec<Vec<f32>>,
+}
+
+impl SpectrumWriter {
+    /// Create a new `SpectrumWriter` for audio with the given number of channels and sample rate,
+    /// that computes the spectrum of every `frame_size` samples.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if `number_of_channels` or `frame_size` is `0`.
+    pub fn new(number_of_channels: usize, frames_per_second: u64, frame_size: usize) -> Self {
+        assert!(number_of_channels > 0);
+        assert!(frame_size > 0);
+        let window = (0..frame_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / frame_size as f32).cos())
+            .collect();
+        Self {
+            number_of_channels,
+            frames_per_second,
+            fft: FftPlanner::new().plan_fft_forward(frame_size),
+            window,
+            pending_samples: Vec::with_capacity(frame_size),
+            spectra: Vec::new(),
+        }
+    }
+
+    /// The number of samples in one frame.
+    pub fn frame_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// The width of one frequency bin, in Hz.
+    pub fn bin_width(&self) -> f64 {
+        self.frames_per_second as f64 / self.frame_size() as f64
+    }
+
+    /// The magnitude spectra, one for every frame that has been completed.
+    /// Each spectrum contains `frame_size / 2 + 1` bins, going from 0 Hz up to
+    /// the Nyquist frequency, in steps of [`bin_width`].
+    ///
+    /// [`bin_width`]: #method.bin_width
+    pub fn spectra(&self) -> &[Vec<f32>] {
+        &self.spectra
+    }
+
+    /// The frequency (in Hz) of the bin with the highest magnitude in the given frame,
+    /// ignoring the 0 Hz bin.
+    /// Returns `None` if no frame with this index has been completed.
+    pub fn dominant_frequency(&self, frame_index: usize) -> Option<f64> {
+        let spectrum = self.spectra.get(frame_index)?;
+        let (peak_bin, _) = spectrum.iter().enumerate().skip(1).fold(
+            (0, 0.0),
+            |(best_bin, best_magnitude), (bin, magnitude)| {
+                if *magnitude > best_magnitude {
+                    (bin, *magnitude)
+                } else {
+                    (best_bin, best_magnitude)
+                }
+            },
+        );
+        Some(peak_bin as f64 * self.bin_width())
+    }
+
+    /// The dominant frequency (see [`dominant_frequency`]) of every frame that has been completed.
+    ///
+    /// [`dominant_frequency`]: #method.dominant_frequency
+    pub fn dominant_frequencies(&self) -> Vec<f64> {
+        (0..self.spectra.len())
+            .filter_map(|frame_index| self.dominant_frequency(frame_index))
+            .collect()
+    }
+
+    fn analyse_pending_samples(&mut self) {
+        let mut buffer: Vec<Complex<f32>> = self
+            .pending_samples
+            .iter()
+            .zip(self.window.iter())
+            .map(|(sample, window)| Complex::new(sample * window, 0.0))
+            .collect();
+        self.fft.process(&mut buffer);
+        let number_of_bins = self.frame_size() / 2 + 1;
+        self.spectra
+            .push(buffer[..number_of_bins].iter().map(|c| c.norm()).collect());
+        self.pending_samples.clear();
+    }
+}
+
+impl<S> AudioWriter<S> for SpectrumWriter
+where
+    S: AsPrimitive<f32>,
+{
+    type Err = std::convert::Infallible;
+
+    fn write_buffer(&mut self, buffer: &AudioBufferIn<S>) -> Result<(), Self::Err> {
+        assert_eq!(buffer.number_of_channels(), self.number_of_channels);
+        for frame in 0..buffer.number_of_frames() {
+            let sum: f32 = buffer
+                .channels()
+                .iter()
+                .map(|channel| channel[frame].as_())
+                .sum();
+            self.pending_samples
+                .push(sum / self.number_of_channels as f32);
+            if self.pending_samples.len() == self.frame_size() {
+                self.analyse_pending_samples();
+            }
+        }
+        Ok(())
+    }
+
+    fn specifies_number_of_channels(&self) -> bool {
+        true
+    }
+
+    fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpectrumWriter;
+    use crate::backend::combined::dummy::AudioDummy;
+    use crate::backend::combined::{run, AudioWriter, RunConfig, TestMidiReader};
+    use crate::buffer::{AudioBufferIn, AudioBufferInOut};
+    use crate::event::{DeltaEvent, EventHandler, RawMidiEvent, Timed};
+    use crate::ContextualAudioRenderer;
+    use midi_consts::channel_event::NOTE_ON;
+    use std::f32::consts::PI;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    // A plugin that plays a sine wave at the frequency of the last note that was pressed.
+    struct SinePlugin {
+        frequency: f32,
+        phase: f32,
+    }
+
+    impl EventHandler<Timed<RawMidiEvent>> for SinePlugin {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+            let data = event.event.data();
+            if data[0] & 0xF0 == NOTE_ON && data[2] > 0 {
+                self.frequency = 440.0 * 2.0_f32.powf((data[1] as f32 - 69.0) / 12.0);
+            }
+        }
+    }
+
+    impl<C> ContextualAudioRenderer<f32, C> for SinePlugin {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut C) {
+            for frame in 0..buffer.number_of_frames() {
+                let value = (2.0 * PI * self.phase).sin();
+                for channel in buffer.outputs().channel_iter_mut() {
+                    channel[frame] = value;
+                }
+                self.phase = (self.phase + self.frequency / SAMPLE_RATE as f32).fract();
+            }
+        }
+    }
+
+    #[test]
+    fn sine_plugin_at_note_69_produces_energy_at_440_hz() {
+        let mut plugin = SinePlugin {
+            frequency: 0.0,
+            phase: 0.0,
+        };
+        let mut writer = SpectrumWriter::new(2, SAMPLE_RATE as u64, 4096);
+        run(
+            &mut plugin,
+            RunConfig::new(
+                AudioDummy::with_sample_rate_and_length(SAMPLE_RATE, SAMPLE_RATE as usize),
+                &mut writer,
+            )
+            .with_buffer_size(256)
+            .with_midi_in(TestMidiReader::new(vec![DeltaEvent {
+                microseconds_since_previous_event: 0,
+                event: RawMidiEvent::new(&[NOTE_ON, 69, 100]),
+            }])),
+        )
+        .expect("Unexpected error");
+
+        let dominant_frequencies = writer.dominant_frequencies();
+        assert_eq!(dominant_frequencies.len(), SAMPLE_RATE as usize / 4096);
+        for frequency in dominant_frequencies {
+            assert!(
+                (frequency - 440.0).abs() <= writer.bin_width(),
+                "Expected the peak at 440 Hz, but it is at {} Hz.",
+                frequency
+            );
+        }
+    }
+
+    #[test]
+    fn ignores_incomplete_frames() {
+        let mut writer = SpectrumWriter::new(1, 8, 4);
+        let samples: &[f32] = &[0.0, 1.0, 0.0];
+        let channels = [samples];
+        AudioWriter::<f32>::write_buffer(&mut writer, &AudioBufferIn::new(&channels, 3)).unwrap();
+        assert!(writer.spectra().is_empty());
+        assert_eq!(writer.dominant_frequency(0), None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Tempo automation for offline rendering, e.g. to test tempo-synced plugins with a
+//! ritardando or an accelerando.
+//!
+//! _Note_: the offline host does not yet report the musical position to the plugin;
+//! [`TempoTracker`] computes the musical position so that it can be reported once it does.
+//!
+//! [`TempoTracker`]: ./struct.TempoTracker.html
+
+const SECONDS_PER_MINUTE: f64 = 60.0;
+
+/// A tempo (in beats per minute) that changes over time.
+///
+/// The tempo is defined by a number of points (the time in frames and the tempo at that time).
+/// Between two points, the tempo changes linearly; before the first point and after the last
+/// point, the tempo is constant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TempoAutomation {
+    // Invariant: not empty and sorted by the time in frames.
+    points: Vec<(u64, f64)>,
+}
+
+impl TempoAutomation {
+    /// Create a new `TempoAutomation` from the given points: pairs of the time in frames
+    /// and the tempo in beats per minute at that time.
+    ///
+    /// # Panics
+    /// Panics if `points` is empty or if the points are not sorted by time.
+    pub fn from_points(points: Vec<(u64, f64)>) -> Self {
+        assert!(!points.is_empty());
+        assert!(
+            points.windows(2).all(|w| w[0].0 <= w[1].0),
+            "The points must be sorted by time."
+        );
+        Self { points }
+    }
+
+    /// Create a new `TempoAutomation` with a constant tempo.
+    pub fn constant(beats_per_minute: f64) -> Self {
+        Self::from_points(vec![(0, beats_per_minute)])
+    }
+
+    /// Create a new `TempoAutomation` that starts at `start_beats_per_minute` and
+    /// changes linearly to `end_beats_per_minute` in `ramp_length_in_frames` frames.
+    pub fn linear_ramp(
+        start_beats_per_minute: f64,
+        end_beats_per_minute: f64,
+        ramp_length_in_frames: u64,
+    ) -> Self {
+        Self::from_points(vec![
+            (0, start_beats_per_minute),
+            (ramp_length_in_frames, end_beats_per_minute),
+        ])
+    }
+
+    /// The tempo, in beats per minute, at the given time.
+    pub fn tempo_at(&self, time_in_frames: u64) -> f64 {
+        let next_index = self.points.iter().position(|p| p.0 > time_in_frames);
+        match next_index {
+            Some(0) => self.points[0].1,
+            Some(index) => {
+                let (start_time, start_tempo) = self.points[index - 1];
+                let (end_time, end_tempo) = self.points[index];
+                let fraction =
+                    (time_in_frames - start_time) as f64 / (end_time - start_time) as f64;
+                start_tempo + (end_tempo - start_tempo) * fraction
+            }
+            None => self.points[self.points.len() - 1].1,
+        }
+    }
+}
+
+/// Keeps track of the musical position (in beats) while rendering, following a [`TempoAutomation`].
+///
+/// The musical position is advanced buffer per buffer, using the tempo at the start
+/// of each buffer.
+///
+/// # Example
+/// ```
+/// use rsynth::backend::combined::tempo::{TempoAutomation, TempoTracker};
+///
+/// let mut tracker = TempoTracker::new(TempoAutomation::constant(120.0), 44100);
+/// tracker.step_frames(44100);
+/// assert_eq!(tracker.beat_position(), 2.0);
+/// ```
+///
+/// [`TempoAutomation`]: ./struct.TempoAutomation.html
+pub struct TempoTracker {
+    automation: TempoAutomation,
+    frames_per_second: u64,
+    current_time_in_frames: u64,
+    beat_position: f64,
+}
+
+impl TempoTracker {
+    /// Create a new `TempoTracker` that starts at beat position `0`.
+    ///
+    /// # Panics
+    /// Panics if `frames_per_second` is `0`.
+    pub fn new(automation: TempoAutomation, frames_per_second: u64) -> Self {
+        assert!(frames_per_second > 0);
+        Self {
+            automation,
+            frames_per_second,
+            current_time_in_frames: 0,
+            beat_position: 0.0,
+        }
+    }
+
+    /// The number of frames that have been rendered so far.
+    pub fn current_time_in_frames(&self) -> u64 {
+        self.current_time_in_frames
+    }
+
+    /// The current tempo, in beats per minute.
+    pub fn tempo(&self) -> f64 {
+        self.automation.tempo_at(self.current_time_in_frames)
+    }
+
+    /// The current musical position, in beats.
+    pub fn beat_position(&self) -> f64 {
+        self.beat_position
+    }
+
+    /// Advance by one buffer with the given number of frames.
+    pub fn step_frames(&mut self, number_of_frames: u64) {
+        let seconds = number_of_frames as f64 / self.frames_per_second as f64;
+        self.beat_position += self.tempo() * seconds / SECONDS_PER_MINUTE;
+        self.current_time_in_frames += number_of_frames;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TempoAutomation, TempoTracker};
+
+    const SAMPLE_RATE: u64 = 44100;
+
+    #[test]
+    fn tempo_is_interpolated_linearly() {
+        let automation = TempoAutomation::from_points(vec![(100, 60.0), (200, 120.0)]);
+        assert_eq!(automation.tempo_at(0), 60.0);
+        assert_eq!(automation.tempo_at(150), 90.0);
+        assert_eq!(automation.tempo_at(300), 120.0);
+    }
+
+    #[test]
+    fn beat_position_follows_a_linear_tempo_ramp() {
+        let automation = TempoAutomation::linear_ramp(120.0, 140.0, 4 * SAMPLE_RATE);
+        let mut tracker = TempoTracker::new(automation, SAMPLE_RATE);
+        let buffer_size = 100;
+        while tracker.current_time_in_frames() < 2 * SAMPLE_RATE {
+            tracker.step_frames(buffer_size);
+        }
+        assert_eq!(tracker.current_time_in_frames(), 2 * SAMPLE_RATE);
+        assert_eq!(tracker.tempo(), 130.0);
+        // The average tempo during the first two seconds is 125 beats per minute.
+        let expected_beat_position = 125.0 * 2.0 / 60.0;
+        assert!(
+            (tracker.beat_position() - expected_beat_position).abs() < 0.001,
+            "Expected beat position {}, but got {}.",
+            expected_beat_position,
+            tracker.beat_position()
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Real-time audio output with the [cpal] crate (behind the `backend-cpal` feature).
+//!
+//! This backend works without a JACK server, e.g. on Windows and MacOs, but it only supports
+//! audio output: there is no audio input and no midi input or output.
+//!
+//! # Usage
+//! See the documentation of the [`run`] function.
+//!
+//! [cpal]: https://crates.io/crates/cpal
+//! [`run`]: ./fn.run.html
+use crate::backend::combined::cpal::cpal_0_13;
+pub use crate::backend::combined::cpal::CpalError;
+use crate::backend::{HostInterface, Stop};
+use crate::buffer::BufferStorage;
+use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+use std::io;
+use std::iter;
+use std::sync::{Arc, Mutex};
+
+use self::cpal_0_13::traits::{DeviceTrait, HostTrait, StreamTrait};
+use self::cpal_0_13::{BufferSize, OutputCallbackInfo, StreamConfig};
+
+// The number of frames for which memory is reserved when the backend starts.
+// If the audio device asks for larger buffers, memory is allocated in the audio thread.
+const INITIAL_BUFFER_SIZE_IN_FRAMES: usize = 4096;
+
+/// Used to communicate with the cpal backend from within the plugin.
+///
+/// `CpalHost` implements [`Stop`]: when the plugin calls `stop()`, the backend outputs
+/// silence from then on.
+///
+/// [`Stop`]: ../trait.Stop.html
+pub struct CpalHost {
+    stop_requested: bool,
+}
+
+impl CpalHost {
+    fn new() -> Self {
+        Self {
+            stop_requested: false,
+        }
+    }
+}
+
+impl HostInterface for CpalHost {
+    fn stop(&mut self) {
+        self.stop_requested = true;
+    }
+}
+
+impl Stop for CpalHost {}
+
+// Renders the audio in the audio callback.
+struct CpalRenderer<P> {
+    plugin: Arc<Mutex<P>>,
+    host: CpalHost,
+    buffers: Vec<Vec<f32>>,
+    storage: BufferStorage<f32>,
+}
+
+impl<P> CpalRenderer<P>
+where
+    P: ContextualAudioRenderer<f32, CpalHost>,
+{
+    fn new(plugin: Arc<Mutex<P>>, number_of_channels: usize) -> Self {
+        Self {
+            plugin,
+            host: CpalHost::new(),
+            buffers: vec![vec![0.0; INITIAL_BUFFER_SIZE_IN_FRAMES]; number_of_channels],
+            storage: BufferStorage::new(0, number_of_channels),
+        }
+    }
+
+    // Render into `data`, which contains the interleaved samples of all channels.
+    fn render(&mut self, data: &mut [f32]) {
+        let number_of_channels = self.buffers.len();
+        let number_of_frames = data.len() / number_of_channels;
+        // We only use `try_lock` in order to not block the audio thread.
+        // The lock is only taken by another thread when the backend stops.
+        let mut plugin = match self.plugin.try_lock() {
+            Ok(plugin) => plugin,
+            Err(_) => {
+                for sample in data.iter_mut() {
+                    *sample = 0.0;
+                }
+                return;
+            }
+        };
+        if self.host.stop_requested {
+            for sample in data.iter_mut() {
+                *sample = 0.0;
+            }
+            return;
+        }
+        for buffer in self.buffers.iter_mut() {
+            if buffer.len() < number_of_frames {
+                buffer.resize(number_of_frames, 0.0);
+            }
+        }
+        let host = &mut self.host;
+        self.storage.with_buffers(
+            iter::empty(),
+            self.buffers
+                .iter_mut()
+                .map(|buffer| &mut buffer[..number_of_frames]),
+            number_of_frames,
+            |buffer| plugin.render_buffer(buffer, host),
+        );
+        for (frame_index, frame) in data.chunks_mut(number_of_channels).enumerate() {
+            for (sample, buffer) in frame.iter_mut().zip(self.buffers.iter()) {
+                *sample = buffer[frame_index];
+            }
+        }
+    }
+}
+
+/// Run the plugin on the default output device until the user presses a key on the
+/// computer keyboard.
+///
+/// The number of output channels is the maximum number of audio outputs of the plugin
+/// (see [`AudioHandlerMeta`]); the sample rate is the default sample rate of the output device
+/// and is passed to the plugin with [`AudioHandler::set_sample_rate`] before the
+/// output starts.
+/// When the plugin calls `stop()` on the [`CpalHost`], the backend outputs silence until the
+/// user presses a key.
+///
+/// Panics
+/// ======
+/// Panics if the plugin has no audio outputs.
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Memory is reserved for buffers of 4096 frames.
+/// If the output device uses larger buffers, memory is allocated in the audio thread.
+///
+/// [`AudioHandlerMeta`]: ../../trait.AudioHandlerMeta.html
+/// [`AudioHandler::set_sample_rate`]: ../../trait.AudioHandler.html#tymethod.set_sample_rate
+/// [`CpalHost`]: ./struct.CpalHost.html
+pub fn run<P>(mut plugin: P) -> Result<P, CpalError>
+where
+    P: AudioHandler + AudioHandlerMeta + ContextualAudioRenderer<f32, CpalHost> + Send + 'static,
+{
+    let number_of_channels = plugin.max_number_of_audio_outputs();
+    assert!(number_of_channels > 0);
+    let device = cpal_0_13::default_host()
+        .default_output_device()
+        .ok_or(CpalError::NoOutputDevice)?;
+    let sample_rate = device
+        .default_output_config()
+        .map_err(CpalError::DefaultStreamConfigError)?
+        .sample_rate();
+    plugin.set_sample_rate(sample_rate.0 as f64);
+    let config = StreamConfig {
+        channels: number_of_channels as u16,
+        sample_rate,
+        buffer_size: BufferSize::Default,
+    };
+
+    let plugin = Arc::new(Mutex::new(plugin));
+    let mut renderer = CpalRenderer::new(plugin.clone(), number_of_channels);
+    let stream = device
+        .build_output_stream(
+            &config,
+            move |data: &mut [f32], _: &OutputCallbackInfo| renderer.render(data),
+            |e| error!("Error in the output stream: {}", e),
+        )
+        .map_err(CpalError::BuildStreamError)?;
+    stream.play().map_err(CpalError::PlayStreamError)?;
+
+    println!("Press any key to quit");
+    let mut user_input = String::new();
+    io::stdin().read_line(&mut user_input).ok();
+
+    info!("Stopping the output stream...");
+    // Dropping the stream also drops the renderer, so that `plugin` is the only reference left.
+    drop(stream);
+    let plugin = match Arc::try_unwrap(plugin) {
+        Ok(plugin) => plugin,
+        Err(_) => unreachable!("The output stream has been dropped."),
+    };
+    Ok(plugin
+        .into_inner()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpalHost, CpalRenderer};
+    use crate::backend::HostInterface;
+    use crate::buffer::AudioBufferInOut;
+    use crate::ContextualAudioRenderer;
+    use std::sync::{Arc, Mutex};
+
+    // Outputs the channel index + 1 on every channel and stops after the given number of buffers.
+    struct ChannelIndex {
+        number_of_buffers_before_stopping: usize,
+    }
+
+    impl ContextualAudioRenderer<f32, CpalHost> for ChannelIndex {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut CpalHost) {
+            for (index, channel) in buffer.outputs().channel_iter_mut().enumerate() {
+                for sample in channel.iter_mut() {
+                    *sample = (index + 1) as f32;
+                }
+            }
+            self.number_of_buffers_before_stopping -= 1;
+            if self.number_of_buffers_before_stopping == 0 {
+                context.stop();
+            }
+        }
+    }
+
+    #[test]
+    fn renders_interleaved_output_until_the_plugin_stops() {
+        let plugin = Arc::new(Mutex::new(ChannelIndex {
+            number_of_buffers_before_stopping: 1,
+        }));
+        let mut renderer = CpalRenderer::new(plugin, 2);
+        let mut data = vec![-1.0; 6];
+        renderer.render(&mut data);
+        assert_eq!(data, vec![1.0, 2.0, 1.0, 2.0, 1.0, 2.0]);
+        renderer.render(&mut data);
+        assert_eq!(data, vec![0.0; 6]);
+    }
+
+    #[test]
+    fn renders_buffers_larger_than_the_initial_buffer_size() {
+        let plugin = Arc::new(Mutex::new(ChannelIndex {
+            number_of_buffers_before_stopping: 2,
+        }));
+        let mut renderer = CpalRenderer::new(plugin, 1);
+        let number_of_frames = super::INITIAL_BUFFER_SIZE_IN_FRAMES + 1;
+        let mut data = vec![0.0; number_of_frames];
+        renderer.render(&mut data);
+        assert!(data.iter().all(|sample| *sample == 1.0));
+    }
+}
@@ -13,16 +13,22 @@
 //! [JACK]: http://www.jackaudio.org/
 //! [the cargo reference]: https://doc.rust-lang.org/cargo/reference/manifest.html#the-features-section
 //! [`run`]: ./fn.run.html
-use crate::backend::{HostInterface, Stop};
-use crate::buffer::AudioBufferInOut;
+use crate::backend::{HostInterface, Stop, TimeSignature, Transport};
+use crate::buffer::BufferStorage;
 use crate::event::{
-    ContextualEventHandler, EventHandler, Indexed, RawMidiEvent, SysExEvent, Timed,
+    ContextualEventHandler, EventHandler, Indexed, RawMidiEvent, RawMidiEventBuf, SysExEvent, Timed,
 };
 use crate::{
     AudioHandler, CommonAudioPortMeta, CommonMidiPortMeta, CommonPluginMeta,
     ContextualAudioRenderer,
 };
+use event_queue::{AlwaysInsertNewAfterOld, EventQueue};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use vecstorage::VecStorage;
 
 /// Re-exports of the [`jack`](https://crates.io/crates/jack) crate.
@@ -32,19 +38,144 @@ pub mod jack {
 }
 
 use self::jack::{AudioIn, AudioOut, MidiIn, MidiOut, Port, ProcessScope, RawMidi};
-use self::jack::{Client, ClientOptions, Control, ProcessHandler};
+use self::jack::{
+    Client, ClientOptions, Control, Frames, LatencyType, NotificationHandler, ProcessHandler,
+    TransportState,
+};
+
+/// The error type when converting from jack's `RawMidi` to a `Timed<RawMidiEvent>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawMidiConversionError {
+    /// The `RawMidi` does not have length 1, 2 or 3. The actual length is given.
+    UnexpectedLength(usize),
+}
+
+impl Display for RawMidiConversionError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            RawMidiConversionError::UnexpectedLength(length) => write!(
+                f,
+                "Raw midi event is expected to have length 1, 2 or 3, but it has length {}.",
+                length
+            ),
+        }
+    }
+}
+
+impl Error for RawMidiConversionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+// Jack uses `u32` for the timing of midi events, so we convert from and to `Timed<_, u32>`.
+impl<'a> TryFrom<RawMidi<'a>> for Timed<RawMidiEvent, u32> {
+    type Error = RawMidiConversionError;
+
+    fn try_from(raw_midi: RawMidi<'a>) -> Result<Self, Self::Error> {
+        let event = RawMidiEvent::try_new(raw_midi.bytes).ok_or(
+            RawMidiConversionError::UnexpectedLength(raw_midi.bytes.len()),
+        )?;
+        Ok(Timed {
+            time_in_frames: raw_midi.time,
+            event,
+        })
+    }
+}
+
+impl<'a> From<&'a Timed<RawMidiEvent, u32>> for RawMidi<'a> {
+    fn from(timed: &'a Timed<RawMidiEvent, u32>) -> Self {
+        RawMidi {
+            time: timed.time_in_frames,
+            bytes: timed.event.bytes(),
+        }
+    }
+}
+
+// The maximum number of midi events that can be sent to one midi output port during one buffer.
+const MIDI_OUT_QUEUE_CAPACITY: usize = 1024;
+
+// Queue an outgoing midi event, so that it can be written in time order later on.
+// If the queue is full, an event is dropped and counted.
+fn schedule_midi_out_event(
+    queue: &mut EventQueue<u32, RawMidiEventBuf>,
+    event: Timed<RawMidiEventBuf>,
+    dropped_midi_count: &mut usize,
+) {
+    if let Some((time, dropped)) =
+        queue.queue_event((event.time_in_frames, event.event), AlwaysInsertNewAfterOld)
+    {
+        *dropped_midi_count += 1;
+        error!(
+            "Too many outgoing midi events; dropping event {:?} at time {}.",
+            dropped, time
+        );
+    }
+}
+
+// Write all queued outgoing midi events that happen before `time` (in time order).
+fn write_midi_out_events_before<F>(
+    queue: &mut EventQueue<u32, RawMidiEventBuf>,
+    time: u32,
+    mut write: F,
+) where
+    F: FnMut(&RawMidi),
+{
+    for (time, event) in queue.drain(time) {
+        write(&RawMidi {
+            time,
+            bytes: event.bytes(),
+        });
+    }
+}
+
+// Write one midi event to the midi output port. If the event cannot be written
+// (e.g. because the midi buffer of jack is full), the event is dropped and counted.
+fn write_midi_out_event(
+    midi_out_port: &mut jack::MidiWriter,
+    raw_midi: &RawMidi,
+    dropped_midi_count: &mut usize,
+) {
+    if let Err(e) = midi_out_port.write(raw_midi) {
+        *dropped_midi_count += 1;
+        error!(
+            "Could not write midi event at frame {}, dropping it: {}",
+            raw_midi.time, e
+        );
+    }
+}
+
+// Write all queued outgoing midi events (in time order).
+fn write_all_midi_out_events<F>(queue: &mut EventQueue<u32, RawMidiEventBuf>, mut write: F)
+where
+    F: FnMut(&RawMidi),
+{
+    for (time, event) in queue.drain_all() {
+        write(&RawMidi {
+            time,
+            bytes: event.bytes(),
+        });
+    }
+}
 
 /// Used to communicate with `Jack`.
 ///
 /// You don't need to instantiate this yourself: it is passed as the `context`
 /// parameter to the [`render_audio`] method when using the [`run`] function.
 ///
+/// Outgoing midi events do not need to be sent in time order: they are queued and
+/// written to the midi output port in time order at the end of the buffer.
+/// System exclusive events are the exception: they are written immediately
+/// (after the queued events that happen before them), so they should be sent in time order.
+///
 /// [`render_audio`]: ../../trait.ContextualAudioRenderer.html#tymethod.render_buffer
 /// [`run`]: ./fn.run.html
 pub struct JackHost<'c, 'mp, 'mw> {
     client: &'c Client,
     midi_out_ports: &'mp mut [jack::MidiWriter<'mw>],
+    midi_out_queues: &'mp mut [EventQueue<u32, RawMidiEventBuf>],
     control: jack::Control,
+    dropped_midi_count: usize,
 }
 
 impl<'c, 'mp, 'mw> JackHost<'c, 'mp, 'mw> {
@@ -54,16 +185,80 @@ impl<'c, 'mp, 'mw> JackHost<'c, 'mp, 'mw> {
     pub fn client(&self) -> &'c Client {
         self.client
     }
+
+    /// The sample rate, in frames per second.
+    pub fn sample_rate(&self) -> usize {
+        self.client.sample_rate()
+    }
+
+    /// The maximum number of frames in one buffer.
+    pub fn buffer_size(&self) -> u32 {
+        self.client.buffer_size()
+    }
+
+    /// The number of outgoing midi events that have been dropped since the plugin started,
+    /// because too many events were sent during one buffer or because they could not be
+    /// written to the midi output port (e.g. because the midi buffer of jack was full).
+    pub fn dropped_midi_count(&self) -> usize {
+        self.dropped_midi_count
+    }
+
+    // Write all queued outgoing midi events to the midi output ports, in time order.
+    fn flush_midi_out_events(&mut self) {
+        let dropped_midi_count = &mut self.dropped_midi_count;
+        for (midi_out_port, queue) in self
+            .midi_out_ports
+            .iter_mut()
+            .zip(self.midi_out_queues.iter_mut())
+        {
+            write_all_midi_out_events(queue, |raw_midi| {
+                write_midi_out_event(midi_out_port, raw_midi, dropped_midi_count);
+            });
+        }
+    }
+}
+
+impl<'c, 'mp, 'mw> Debug for JackHost<'c, 'mp, 'mw> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("JackHost")
+            .field("client_name", &self.client.name())
+            .field("number_of_midi_out_ports", &self.midi_out_ports.len())
+            .field("control", &self.control)
+            .field("dropped_midi_count", &self.dropped_midi_count)
+            .finish()
+    }
 }
 
 impl<'c, 'mp, 'mw> HostInterface for JackHost<'c, 'mp, 'mw> {
     fn output_initialized(&self) -> bool {
+        // Jack does not set the output buffers to zero.
         false
     }
 
     fn stop(&mut self) {
         self.control = jack::Control::Quit
     }
+
+    fn transport(&self) -> Option<Transport> {
+        let state_and_position = match self.client.transport().query() {
+            Ok(state_and_position) => state_and_position,
+            Err(e) => {
+                warn!("Could not query the jack transport: {}", e);
+                return None;
+            }
+        };
+        // The tempo and the time signature are only known if there is a timebase master.
+        let bbt = state_and_position.pos.bbt();
+        Some(Transport {
+            tempo_bpm: bbt.as_ref().map(|bbt| bbt.bpm),
+            position_in_frames: state_and_position.pos.frame() as u64,
+            time_signature: bbt.as_ref().map(|bbt| TimeSignature {
+                numerator: bbt.sig_num as u32,
+                denominator: bbt.sig_denom as u32,
+            }),
+            is_playing: state_and_position.state == TransportState::Rolling,
+        })
+    }
 }
 
 impl<'c, 'mp, 'mw> Stop for JackHost<'c, 'mp, 'mw> {}
@@ -71,17 +266,26 @@ impl<'c, 'mp, 'mw> Stop for JackHost<'c, 'mp, 'mw> {}
 impl<'c, 'mp, 'mw> EventHandler<Indexed<Timed<RawMidiEvent>>> for JackHost<'c, 'mp, 'mw> {
     fn handle_event(&mut self, event: Indexed<Timed<RawMidiEvent>>) {
         let Indexed { index, event } = event;
-        if let Some(ref mut midi_out_port) = self.midi_out_ports.get_mut(index).as_mut() {
-            let raw_midi = RawMidi {
-                time: event.time_in_frames,
-                bytes: event.event.bytes(),
-            };
-            midi_out_port.write(&raw_midi); // TODO: error handling.
+        self.handle_event(Indexed {
+            index,
+            event: Timed {
+                time_in_frames: event.time_in_frames,
+                event: RawMidiEventBuf::from(event.event),
+            },
+        });
+    }
+}
+
+impl<'c, 'mp, 'mw> EventHandler<Indexed<Timed<RawMidiEventBuf>>> for JackHost<'c, 'mp, 'mw> {
+    fn handle_event(&mut self, event: Indexed<Timed<RawMidiEventBuf>>) {
+        let Indexed { index, event } = event;
+        if let Some(queue) = self.midi_out_queues.get_mut(index) {
+            schedule_midi_out_event(queue, event, &mut self.dropped_midi_count);
         } else {
             error!(
                 "midi port out of bounds: port index is {}, but only {} ports are available",
                 index,
-                self.midi_out_ports.len()
+                self.midi_out_queues.len()
             );
         }
     }
@@ -90,12 +294,23 @@ impl<'c, 'mp, 'mw> EventHandler<Indexed<Timed<RawMidiEvent>>> for JackHost<'c, '
 impl<'c, 'mp, 'mw, 'e> EventHandler<Indexed<Timed<SysExEvent<'e>>>> for JackHost<'c, 'mp, 'mw> {
     fn handle_event(&mut self, event: Indexed<Timed<SysExEvent>>) {
         let Indexed { index, event } = event;
-        if let Some(ref mut midi_out_port) = self.midi_out_ports.get_mut(index).as_mut() {
+        let dropped_midi_count = &mut self.dropped_midi_count;
+        if let (Some(midi_out_port), Some(queue)) = (
+            self.midi_out_ports.get_mut(index),
+            self.midi_out_queues.get_mut(index),
+        ) {
+            write_midi_out_events_before(
+                queue,
+                event.time_in_frames.saturating_add(1),
+                |raw_midi| {
+                    write_midi_out_event(midi_out_port, raw_midi, dropped_midi_count);
+                },
+            );
             let raw_midi = RawMidi {
                 time: event.time_in_frames,
                 bytes: event.event.data(),
             };
-            midi_out_port.write(&raw_midi); // TODO: error handling.
+            write_midi_out_event(midi_out_port, &raw_midi, dropped_midi_count);
         } else {
             error!(
                 "midi port out of bounds: port index is {}, but only {} ports are available",
@@ -244,14 +459,50 @@ struct JackProcessHandler<P> {
     midi_in_ports: Vec<Port<MidiIn>>,
     midi_out_ports: Vec<Port<MidiOut>>,
     plugin: P,
-    inputs: VecStorage<&'static [f32]>,
-    outputs: VecStorage<&'static [f32]>,
+    buffers: BufferStorage<f32>,
     midi_writer: VecStorage<MidiWriterWrapper>, // We cannot use rsor for this one.
+    midi_out_queues: Vec<EventQueue<u32, RawMidiEventBuf>>,
+    // The sample rate that has last been passed to the plugin.
+    sample_rate: usize,
+    // The sample rate as reported by the `JackNotificationHandler`.
+    notified_sample_rate: Arc<AtomicUsize>,
+    // The total number of dropped outgoing midi events, see `JackHost::dropped_midi_count`.
+    dropped_midi_count: usize,
+}
+
+impl<P> Debug for JackProcessHandler<P>
+where
+    P: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("JackProcessHandler")
+            .field("plugin", &self.plugin)
+            .field("number_of_audio_in_ports", &self.audio_in_ports.len())
+            .field("number_of_audio_out_ports", &self.audio_out_ports.len())
+            .field("number_of_midi_in_ports", &self.midi_in_ports.len())
+            .field("number_of_midi_out_ports", &self.midi_out_ports.len())
+            .finish()
+    }
 }
 
 impl<P> JackProcessHandler<P>
 where
-    P: CommonAudioPortMeta + CommonMidiPortMeta + CommonPluginMeta + Send,
+    P: AudioHandler,
+{
+    // Call `set_sample_rate` on the plugin if the sample rate has changed since the
+    // last call, so that the plugin is informed before it renders the next buffer.
+    fn update_sample_rate(&mut self, sample_rate: usize) {
+        if sample_rate != self.sample_rate {
+            info!("Sample rate changed to {} Hz.", sample_rate);
+            self.sample_rate = sample_rate;
+            self.plugin.set_sample_rate(sample_rate as f64);
+        }
+    }
+}
+
+impl<P> JackProcessHandler<P>
+where
+    P: AudioHandler + CommonAudioPortMeta + CommonMidiPortMeta + CommonPluginMeta + Send,
     for<'c, 'mp, 'mw> P: ContextualAudioRenderer<f32, JackHost<'c, 'mp, 'mw>>
         + ContextualEventHandler<Indexed<Timed<RawMidiEvent>>, JackHost<'c, 'mp, 'mw>>,
     for<'c, 'mp, 'mw, 'a> P:
@@ -261,14 +512,19 @@ where
         trace!("JackProcessHandler::new()");
         let audio_in_ports = audio_in_ports::<P>(&client, &plugin);
         let audio_out_ports = audio_out_ports::<P>(&client, &plugin);
-
         let midi_in_ports = midi_in_ports::<P>(&client, &plugin);
         let midi_out_ports = midi_out_ports::<P>(&client, &plugin);
 
-        let inputs = VecStorage::with_capacity(plugin.max_number_of_audio_inputs());
-        let outputs = VecStorage::with_capacity(plugin.max_number_of_audio_outputs());
+        let buffers = BufferStorage::new(
+            plugin.max_number_of_audio_inputs(),
+            plugin.max_number_of_audio_outputs(),
+        );
 
         let midi_writer = VecStorage::with_capacity(plugin.max_number_of_midi_outputs());
+        let midi_out_queues = midi_out_ports
+            .iter()
+            .map(|_| EventQueue::new(MIDI_OUT_QUEUE_CAPACITY))
+            .collect();
 
         JackProcessHandler {
             audio_in_ports,
@@ -276,9 +532,12 @@ where
             midi_in_ports,
             midi_out_ports,
             plugin,
-            inputs,
-            outputs,
+            buffers,
             midi_writer,
+            midi_out_queues,
+            sample_rate: client.sample_rate(),
+            notified_sample_rate: Arc::new(AtomicUsize::new(client.sample_rate())),
+            dropped_midi_count: 0,
         }
     }
 
@@ -295,20 +554,13 @@ where
             for input_event in midi_in_port.iter(process_scope) {
                 trace!("handle_events found event: {:?}", &input_event.bytes);
                 if input_event.bytes.len() <= 3 {
-                    if let Some(raw_event) = RawMidiEvent::try_new(&input_event.bytes) {
-                        let event = Indexed {
-                            index,
-                            event: Timed {
-                                time_in_frames: input_event.time,
-                                event: raw_event,
-                            },
-                        };
-                        plugin.handle_event(event, jack_host);
-                    } else {
-                        warn!(
-                            "Strange event of length {}; ignoring this event.",
-                            input_event.bytes.len()
-                        );
+                    match Timed::<RawMidiEvent>::try_from(input_event) {
+                        Ok(event) => {
+                            plugin.handle_event(Indexed { index, event }, jack_host);
+                        }
+                        Err(e) => {
+                            warn!("{} Ignoring this event.", e);
+                        }
                     }
                 } else {
                     let event = Indexed {
@@ -327,13 +579,14 @@ where
 
 impl<P> ProcessHandler for JackProcessHandler<P>
 where
-    P: CommonAudioPortMeta + CommonMidiPortMeta + CommonPluginMeta + Send,
+    P: AudioHandler + CommonAudioPortMeta + CommonMidiPortMeta + CommonPluginMeta + Send,
     for<'c, 'mp, 'mw> P: ContextualAudioRenderer<f32, JackHost<'c, 'mp, 'mw>>
         + ContextualEventHandler<Indexed<Timed<RawMidiEvent>>, JackHost<'c, 'mp, 'mw>>,
     for<'c, 'mp, 'mw, 'a> P:
         ContextualEventHandler<Indexed<Timed<SysExEvent<'a>>>, JackHost<'c, 'mp, 'mw>>,
 {
     fn process(&mut self, client: &Client, process_scope: &ProcessScope) -> Control {
+        self.update_sample_rate(self.notified_sample_rate.load(Ordering::Acquire));
         let mut midi_writer_guard = self.midi_writer.vec_guard();
         for midi_output in self.midi_out_ports.iter_mut() {
             midi_writer_guard.push(midi_output.writer(process_scope));
@@ -341,7 +594,9 @@ where
         let mut jack_host: JackHost = JackHost {
             client,
             midi_out_ports: midi_writer_guard.as_mut_slice(),
+            midi_out_queues: self.midi_out_queues.as_mut_slice(),
             control: jack::Control::Continue,
+            dropped_midi_count: self.dropped_midi_count,
         };
         Self::handle_events(
             &self.midi_in_ports,
@@ -350,29 +605,163 @@ where
             &mut jack_host,
         );
 
-        let mut inputs = self.inputs.vec_guard();
-        for port in self.audio_in_ports.iter().take(inputs.capacity()) {
-            inputs.push(port.as_slice(process_scope));
+        let plugin = &mut self.plugin;
+        self.buffers.with_buffers(
+            self.audio_in_ports
+                .iter()
+                .map(|port| port.as_slice(process_scope)),
+            self.audio_out_ports
+                .iter_mut()
+                .map(|port| port.as_mut_slice(process_scope)),
+            client.buffer_size() as usize,
+            |buffer| plugin.render_buffer(buffer, &mut jack_host),
+        );
+        jack_host.flush_midi_out_events();
+        self.dropped_midi_count = jack_host.dropped_midi_count;
+        jack_host.control
+    }
+
+    fn buffer_size(&mut self, _client: &Client, size: Frames) -> Control {
+        // The buffers are not allocated in advance, so nothing needs to be done here.
+        info!("Buffer size changed to {} frames.", size);
+        Control::Continue
+    }
+}
+
+// Receives notifications from the jack server. Because notifications are not received in the
+// audio thread, the sample rate is passed to the `JackProcessHandler`, which passes it to the
+// plugin before rendering the next buffer.
+struct JackNotificationHandler {
+    sample_rate: Arc<AtomicUsize>,
+    // The latency of the plugin, as reported by `CommonPluginMeta::latency_in_frames`.
+    latency_in_frames: Frames,
+    // The full names of the audio ports, used to look up the ports in the `latency` callback.
+    audio_in_port_names: Vec<String>,
+    audio_out_port_names: Vec<String>,
+}
+
+impl JackNotificationHandler {
+    fn new<P>(process_handler: &JackProcessHandler<P>) -> Self
+    where
+        P: CommonPluginMeta,
+    {
+        let latency_in_frames = process_handler.plugin.latency_in_frames();
+        if latency_in_frames > 0 {
+            info!("Reporting a latency of {} frames.", latency_in_frames);
         }
+        JackNotificationHandler {
+            sample_rate: process_handler.notified_sample_rate.clone(),
+            latency_in_frames,
+            audio_in_port_names: port_names(&process_handler.audio_in_ports),
+            audio_out_port_names: port_names(&process_handler.audio_out_ports),
+        }
+    }
+}
+
+fn port_names<PS>(ports: &[Port<PS>]) -> Vec<String> {
+    ports
+        .iter()
+        .filter_map(|port| match port.name() {
+            Ok(name) => Some(name),
+            Err(e) => {
+                error!("Failed to get the name of a jack port: {}.", e);
+                None
+            }
+        })
+        .collect()
+}
 
-        let mut outputs = self.outputs.vec_guard();
-        for port in self.audio_out_ports.iter_mut().take(outputs.capacity()) {
-            outputs.push(port.as_mut_slice(process_scope));
+impl NotificationHandler for JackNotificationHandler {
+    fn sample_rate(&mut self, _client: &Client, sample_rate: Frames) -> Control {
+        self.sample_rate
+            .store(sample_rate as usize, Ordering::Release);
+        Control::Continue
+    }
+
+    // The latency of the ports on one side of the plugin is the latency of the ports on the
+    // other side of the plugin (the input ports for `Capture`, the output ports for `Playback`),
+    // increased with the latency of the plugin itself.
+    fn latency(&mut self, client: &Client, mode: LatencyType) {
+        let (from_port_names, to_port_names) = match mode {
+            LatencyType::Capture => (&self.audio_in_port_names, &self.audio_out_port_names),
+            LatencyType::Playback => (&self.audio_out_port_names, &self.audio_in_port_names),
+        };
+        let mut range: Option<(Frames, Frames)> = None;
+        for port in from_port_names
+            .iter()
+            .filter_map(|name| client.port_by_name(name))
+        {
+            let (min, max) = port.get_latency_range(mode);
+            range = Some(match range {
+                Some((old_min, old_max)) => (old_min.min(min), old_max.max(max)),
+                None => (min, max),
+            });
+        }
+        let (min, max) = range.unwrap_or((0, 0));
+        let range = (min + self.latency_in_frames, max + self.latency_in_frames);
+        for port in to_port_names
+            .iter()
+            .filter_map(|name| client.port_by_name(name))
+        {
+            port.set_latency_range(mode, range);
         }
+    }
+}
 
-        let mut buffer = AudioBufferInOut::new(
-            inputs.as_slice(),
-            outputs.as_mut_slice(),
-            client.buffer_size() as usize,
-        );
-        self.plugin.render_buffer(&mut buffer, &mut jack_host);
-        jack_host.control
+/// The name of the jack client for the plugin, as defined by the `plugin_name` method
+/// of the [`CommonPluginMeta`] trait.
+///
+/// Because the name is computed when the plugin is started (and not returned as a fixed
+/// `&str`), a plugin can e.g. store its name in a `String`, so that every instance
+/// of the plugin has a different name.
+///
+/// [`CommonPluginMeta`]: ../../trait.CommonPluginMeta.html
+pub fn client_name<P>(plugin: &P) -> String
+where
+    P: CommonPluginMeta,
+{
+    let mut name = String::new();
+    if let Err(e) = plugin.plugin_name(&mut name) {
+        error!("Failed to get the name of the plugin: {}.", e);
     }
+    name
 }
 
 /// Run the plugin until the user presses a key on the computer keyboard or the plugin
 /// requests the `JackHost` to stop.
-pub fn run<P>(mut plugin: P) -> Result<P, jack::Error>
+///
+/// The name of the jack client is determined by [`client_name`].
+/// Use [`run_with_client_name`] to use another name.
+///
+/// [`client_name`]: ./fn.client_name.html
+/// [`run_with_client_name`]: ./fn.run_with_client_name.html
+pub fn run<P>(plugin: P) -> Result<P, jack::Error>
+where
+    P: CommonPluginMeta
+        + AudioHandler
+        + CommonAudioPortMeta
+        + CommonMidiPortMeta
+        + Send
+        + Sync
+        + 'static,
+    for<'c, 'mp, 'mw> P: ContextualAudioRenderer<f32, JackHost<'c, 'mp, 'mw>>,
+    for<'c, 'mp, 'mw> P:
+        ContextualEventHandler<Indexed<Timed<RawMidiEvent>>, JackHost<'c, 'mp, 'mw>>,
+    for<'c, 'mp, 'mw, 'a> P:
+        ContextualEventHandler<Indexed<Timed<SysExEvent<'a>>>, JackHost<'c, 'mp, 'mw>>,
+{
+    let name = client_name(&plugin);
+    run_with_client_name(plugin, &name)
+}
+
+/// Run the plugin with the given name for the jack client, until the user presses a key
+/// on the computer keyboard or the plugin requests the `JackHost` to stop.
+///
+/// This allows to run multiple instances of the same plugin, each with a different name.
+///
+/// `set_sample_rate` is called on the plugin before the jack client is activated and again,
+/// before rendering the next buffer, whenever the sample rate of the jack server changes.
+pub fn run_with_client_name<P>(mut plugin: P, client_name: &str) -> Result<P, jack::Error>
 where
     P: CommonPluginMeta
         + AudioHandler
@@ -387,13 +776,14 @@ where
     for<'c, 'mp, 'mw, 'a> P:
         ContextualEventHandler<Indexed<Timed<SysExEvent<'a>>>, JackHost<'c, 'mp, 'mw>>,
 {
-    let (client, _status) = Client::new(plugin.name(), ClientOptions::NO_START_SERVER)?;
+    let (client, _status) = Client::new(client_name, ClientOptions::NO_START_SERVER)?;
 
     let sample_rate = client.sample_rate();
     plugin.set_sample_rate(sample_rate as f64);
 
     let jack_process_handler = JackProcessHandler::new(&client, plugin);
-    let active_client = client.activate_async((), jack_process_handler)?;
+    let jack_notification_handler = JackNotificationHandler::new(&jack_process_handler);
+    let active_client = client.activate_async(jack_notification_handler, jack_process_handler)?;
 
     println!("Press any key to quit");
     let mut user_input = String::new();
@@ -404,3 +794,229 @@ where
     let (_, _, plugin) = active_client.deactivate()?;
     return Ok(plugin.plugin);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::jack::{Client, ClientOptions, Control, RawMidi};
+    use super::{
+        client_name, schedule_midi_out_event, write_all_midi_out_events, JackHost,
+        JackProcessHandler, RawMidiConversionError,
+    };
+    use crate::backend::HostInterface;
+    use crate::buffer::BufferStorage;
+    use crate::event::{EventHandler, Indexed, RawMidiEvent, RawMidiEventBuf, Timed};
+    use crate::meta::{InOut, Meta, MetaData};
+    use crate::AudioHandler;
+    use event_queue::EventQueue;
+    use std::convert::TryFrom;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use vecstorage::VecStorage;
+
+    #[test]
+    fn raw_midi_with_three_bytes_is_converted() {
+        let raw_midi = RawMidi {
+            time: 5,
+            bytes: &[0x90, 69, 100],
+        };
+        assert_eq!(
+            Timed::<RawMidiEvent>::try_from(raw_midi),
+            Ok(Timed::new(5, RawMidiEvent::new(&[0x90, 69, 100])))
+        );
+    }
+
+    #[test]
+    fn raw_midi_that_is_too_long_gives_an_error() {
+        let raw_midi = RawMidi {
+            time: 5,
+            bytes: &[0xF0, 1, 2, 3, 0xF7],
+        };
+        assert_eq!(
+            Timed::<RawMidiEvent>::try_from(raw_midi),
+            Err(RawMidiConversionError::UnexpectedLength(5))
+        );
+    }
+
+    #[test]
+    fn timed_raw_midi_event_is_converted_to_raw_midi() {
+        let event = Timed::new(7, RawMidiEvent::new(&[0x80, 60, 0]));
+        let raw_midi = RawMidi::from(&event);
+        assert_eq!(raw_midi.time, 7);
+        assert_eq!(raw_midi.bytes, &[0x80, 60, 0]);
+    }
+
+    #[derive(Debug)]
+    struct DebuggablePlugin;
+
+    #[test]
+    fn jack_process_handler_debug_output_mentions_the_plugin() {
+        let handler = JackProcessHandler {
+            audio_in_ports: Vec::new(),
+            audio_out_ports: Vec::new(),
+            midi_in_ports: Vec::new(),
+            midi_out_ports: Vec::new(),
+            plugin: DebuggablePlugin,
+            buffers: BufferStorage::new(0, 0),
+            midi_writer: VecStorage::with_capacity(0),
+            midi_out_queues: Vec::new(),
+            sample_rate: 44100,
+            notified_sample_rate: Arc::new(AtomicUsize::new(44100)),
+            dropped_midi_count: 0,
+        };
+        let output = format!("{:?}", handler);
+        assert!(output.contains("DebuggablePlugin"));
+        assert!(output.contains("number_of_audio_in_ports: 0"));
+    }
+
+    struct SampleRateRecorder {
+        sample_rates: Vec<f64>,
+    }
+
+    impl AudioHandler for SampleRateRecorder {
+        fn set_sample_rate(&mut self, sample_rate: f64) {
+            self.sample_rates.push(sample_rate);
+        }
+    }
+
+    #[test]
+    fn jack_process_handler_only_passes_a_changed_sample_rate() {
+        let mut handler = JackProcessHandler {
+            audio_in_ports: Vec::new(),
+            audio_out_ports: Vec::new(),
+            midi_in_ports: Vec::new(),
+            midi_out_ports: Vec::new(),
+            plugin: SampleRateRecorder {
+                sample_rates: Vec::new(),
+            },
+            buffers: BufferStorage::new(0, 0),
+            midi_writer: VecStorage::with_capacity(0),
+            midi_out_queues: Vec::new(),
+            sample_rate: 44100,
+            notified_sample_rate: Arc::new(AtomicUsize::new(44100)),
+            dropped_midi_count: 0,
+        };
+        handler.update_sample_rate(44100);
+        handler.update_sample_rate(48000);
+        handler.update_sample_rate(48000);
+        assert_eq!(handler.plugin.sample_rates, vec![48000.0]);
+    }
+
+    struct NamedPlugin {
+        meta: MetaData<String, &'static str, &'static str>,
+    }
+
+    impl NamedPlugin {
+        fn new(name: &str) -> Self {
+            Self {
+                meta: MetaData {
+                    general_meta: name.to_string(),
+                    audio_port_meta: InOut {
+                        inputs: vec![],
+                        outputs: vec!["out"],
+                    },
+                    midi_port_meta: InOut {
+                        inputs: vec![],
+                        outputs: vec![],
+                    },
+                },
+            }
+        }
+    }
+
+    impl Meta for NamedPlugin {
+        type MetaData = MetaData<String, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    #[test]
+    fn instances_of_the_same_plugin_get_different_client_names() {
+        let first = NamedPlugin::new("synth 1");
+        let second = NamedPlugin::new("synth 2");
+        assert_eq!(client_name(&first), "synth 1");
+        assert_eq!(client_name(&second), "synth 2");
+    }
+
+    #[test]
+    fn midi_out_events_are_written_in_time_order() {
+        let mut queue = EventQueue::new(4);
+        let mut dropped_midi_count = 0;
+        schedule_midi_out_event(
+            &mut queue,
+            Timed::new(8, RawMidiEventBuf::new(&[0x80, 60, 0])),
+            &mut dropped_midi_count,
+        );
+        schedule_midi_out_event(
+            &mut queue,
+            Timed::new(3, RawMidiEventBuf::new(&[0x90, 60, 100])),
+            &mut dropped_midi_count,
+        );
+        let mut written = Vec::new();
+        write_all_midi_out_events(&mut queue, |raw_midi| {
+            written.push((raw_midi.time, raw_midi.bytes.to_vec()));
+        });
+        assert_eq!(
+            written,
+            vec![(3, vec![0x90, 60, 100]), (8, vec![0x80, 60, 0])]
+        );
+        assert!(queue.is_empty());
+        assert_eq!(dropped_midi_count, 0);
+    }
+
+    #[test]
+    fn scheduling_a_midi_out_event_in_a_full_queue_counts_it_as_dropped() {
+        let mut queue = EventQueue::new(2);
+        // The queue may have room for more events than requested.
+        let capacity = queue.capacity() as u32;
+        let mut dropped_midi_count = 0;
+        for time in 0..capacity + 3 {
+            schedule_midi_out_event(
+                &mut queue,
+                Timed::new(time, RawMidiEventBuf::new(&[0x90, 60, 100])),
+                &mut dropped_midi_count,
+            );
+        }
+        assert_eq!(dropped_midi_count, 3);
+        assert_eq!(queue.len(), capacity as usize);
+    }
+
+    #[test]
+    #[ignore] // Needs a running jack server.
+    fn jack_host_does_not_report_initialized_output() {
+        let (client, _status) = Client::new("rsynth_test", ClientOptions::NO_START_SERVER)
+            .expect("Could not connect to the jack server.");
+        let host = JackHost {
+            client: &client,
+            midi_out_ports: &mut [],
+            midi_out_queues: &mut [],
+            control: Control::Continue,
+            dropped_midi_count: 0,
+        };
+        assert!(!host.output_initialized());
+    }
+
+    #[test]
+    #[ignore] // Needs a running jack server.
+    fn midi_out_events_that_do_not_fit_in_the_queue_are_counted_as_dropped() {
+        let (client, _status) = Client::new("rsynth_test", ClientOptions::NO_START_SERVER)
+            .expect("Could not connect to the jack server.");
+        let mut midi_out_queues = vec![EventQueue::new(2)];
+        // The queue may have room for more events than requested.
+        let capacity = midi_out_queues[0].capacity() as u32;
+        let mut host = JackHost {
+            client: &client,
+            midi_out_ports: &mut [],
+            midi_out_queues: &mut midi_out_queues,
+            control: Control::Continue,
+            dropped_midi_count: 0,
+        };
+        for time in 0..capacity + 3 {
+            host.handle_event(Indexed {
+                index: 0,
+                event: Timed::new(time, RawMidiEventBuf::new(&[0x90, 60, 100])),
+            });
+        }
+        assert_eq!(host.dropped_midi_count(), 3);
+    }
+}
@@ -7,6 +7,9 @@
 //!     midi output, mostly for offline rendering and testing (behind various features)
 //! * [`jack`] (behind the `backend-jack` feature)
 //! * [`vst`] (behind the `backend-vst` feature)
+//! * [`cpal`] real-time audio output without a JACK server (behind the `backend-cpal` feature)
+//! * [`alsa`] real-time audio output and midi input on Linux without a JACK server
+//!     (behind the `backend-alsa` feature)
 //!
 //! These backends are currently in the `rsynth` crate, but we may eventually move them to
 //! separate crates.
@@ -26,19 +29,57 @@
 //! [`jack`]: ./jack_backend/index.html
 //! [`vst`]: ./vst_backend/index.html
 //! [`combined`]: ./combined/index.html
+//! [`cpal`]: ./cpal_backend/index.html
+//! [`alsa`]: ./alsa_backend/index.html
+#[cfg(feature = "backend-alsa")]
+pub mod alsa_backend;
 #[cfg(feature = "backend-combined")]
 pub mod combined;
+#[cfg(feature = "backend-cpal")]
+pub mod cpal_backend;
 #[cfg(feature = "backend-jack")]
 pub mod jack_backend;
 #[cfg(feature = "backend-vst")]
 pub mod vst_backend;
 
+/// A time signature, e.g. 3/4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeSignature {
+    /// The number of beats per bar, e.g. `3` for 3/4.
+    pub numerator: u32,
+    /// The note value of one beat, e.g. `4` for 3/4.
+    pub denominator: u32,
+}
+
+/// Information about the transport of the host: the play head and the tempo.
+///
+/// See [`HostInterface::transport`].
+///
+/// [`HostInterface::transport`]: ./trait.HostInterface.html#method.transport
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transport {
+    /// The tempo in beats per minute, if known.
+    pub tempo_bpm: Option<f64>,
+    /// The position of the play head at the start of the current buffer, in frames.
+    pub position_in_frames: u64,
+    /// The time signature, if known.
+    pub time_signature: Option<TimeSignature>,
+    /// `true` if the transport is playing (rolling).
+    pub is_playing: bool,
+}
+
 /// Defines an interface for communicating with the host or server of the backend,
 /// e.g. the VST host when using VST or the  Jack server when using Jack.
 pub trait HostInterface {
     /// Return whether the output buffers are zero-initialized.
     /// Returns `false` when in doubt.
     ///
+    /// Plugins that add to the output buffers (e.g. a polyphonic synthesizer in which every
+    /// voice adds its own sound to the output), must set the output buffers to zero themselves
+    /// when this method returns `false`.
+    ///
+    /// The default implementation returns `false`.
+    ///
     /// # Example
     ///
     /// The following example illustrates how `output_initialized()` can be used in
@@ -68,13 +109,26 @@ pub trait HostInterface {
     ///
     /// [`ContextualEventHandler`]: ../event/trait.ContextualEventHandler.html
     /// [`rsynth::utilities::zero_init`]: ../utilities/fn.initialize_to_zero.html
-    fn output_initialized(&self) -> bool;
+    fn output_initialized(&self) -> bool {
+        false
+    }
 
     /// Stop processing.
     /// For backends that do not support stopping, this is a no-op.
     /// For back-ends that do support stopping and that implement the `Stop` trait,
     /// this stops the processing.
     fn stop(&mut self) {}
+
+    /// Information about the transport of the host (tempo, play head position, ...),
+    /// e.g. for tempo-synced delays and LFOs.
+    ///
+    /// Returns `None` if the backend does not support this or if the host does not provide
+    /// this information.
+    ///
+    /// The default implementation returns `None`.
+    fn transport(&self) -> Option<Transport> {
+        None
+    }
 }
 
 /// A marker trait that indicates that the backend can be stopped.
@@ -105,3 +159,29 @@ pub trait HostInterface {
 /// }
 /// ```
 pub trait Stop: HostInterface {}
+
+#[cfg(test)]
+mod tests {
+    use super::HostInterface;
+
+    // Uses the default implementations of all methods.
+    struct DefaultHost;
+    impl HostInterface for DefaultHost {}
+
+    #[test]
+    fn transport_defaults_to_none() {
+        assert_eq!(DefaultHost.transport(), None);
+    }
+
+    #[test]
+    fn output_initialized_defaults_to_false() {
+        assert!(!DefaultHost.output_initialized());
+    }
+
+    #[cfg(feature = "backend-vst")]
+    #[test]
+    fn vst_host_callback_does_not_report_initialized_output() {
+        use crate::backend::vst_backend::vst::plugin::HostCallback;
+        assert!(!HostCallback::default().output_initialized());
+    }
+}
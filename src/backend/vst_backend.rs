@@ -12,14 +12,16 @@
 //!
 //! [`vst_init`]: ../../macro.vst_init.html
 //! [the cargo reference]: https://doc.rust-lang.org/cargo/reference/manifest.html#the-features-section
-use crate::backend::HostInterface;
-use crate::buffer::AudioBufferInOut;
+use crate::backend::{HostInterface, TimeSignature, Transport};
+use crate::buffer::BufferStorage;
 use crate::event::{ContextualEventHandler, RawMidiEvent, SysExEvent, Timed};
 use crate::{
     AudioHandler, AudioHandlerMeta, CommonAudioPortMeta, CommonPluginMeta, ContextualAudioRenderer,
 };
 use core::cmp;
-use vecstorage::VecStorage;
+use num_traits::Float;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
 /// Re-exports from the [`vst-rs`](https://github.com/RustAudio/vst-rs) crate.
 /// Use this in libraries so that your library does not break when `rsynth` upgrades to another
@@ -29,17 +31,122 @@ pub mod vst {
 }
 
 use self::vst::{
-    api::Events,
+    api::{Events, TimeInfoFlags},
     buffer::AudioBuffer,
     channels::ChannelInfo,
     event::{Event as VstEvent, MidiEvent as VstMidiEvent, SysExEvent as VstSysExEvent},
-    plugin::{Category, HostCallback, Info},
+    host::Host,
+    plugin::{Category, HostCallback, Info, PluginParameters},
 };
 
 /// Define some VST-specific meta-data for a VST plugin.
 pub trait VstPluginMeta: CommonPluginMeta + AudioHandlerMeta {
     fn plugin_id(&self) -> i32;
     fn category(&self) -> Category;
+
+    /// The parameters of the plugin that the host can show and automate.
+    ///
+    /// This method is called once, when the plugin is created.
+    /// The default implementation returns `None`, so that the plugin has no parameters.
+    fn parameters(&self) -> Option<Arc<dyn VstParameters>> {
+        None
+    }
+
+    /// Called when the host suspends the plugin, e.g. when the plugin is bypassed.
+    ///
+    /// The default implementation does nothing.
+    fn suspend(&mut self) {}
+
+    /// Called when the host resumes the plugin after suspending it.
+    /// The plugin can e.g. clear its delay lines here.
+    ///
+    /// The default implementation does nothing.
+    fn resume(&mut self) {}
+
+    /// The number of frames the output may still be non-silent after the input has become
+    /// silent, e.g. the length of the tail of a reverb.
+    /// The host uses this to decide how long it keeps calling `render_buffer`.
+    ///
+    /// `Some(0)` means that the plugin has no tail.
+    /// `None` means that the tail size is unknown and the host uses its default.
+    ///
+    /// The default implementation returns `None`.
+    fn tail_size_in_frames(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Parameters of a VST plugin that the host can show and automate.
+///
+/// The values of the parameters are normalized: they are in the range `[0, 1]`.
+/// The parameters are typically stored in atomics (e.g. an `AtomicU32` with the bits of an `f32`)
+/// that are shared with the plugin: the host can get and set the parameters from another thread
+/// than the thread that renders the audio.
+/// See [`VstPluginMeta::parameters`] for how to pass the parameters to the host.
+///
+/// The index of a parameter is always lower than [`number_of_parameters`].
+///
+/// [`VstPluginMeta::parameters`]: ./trait.VstPluginMeta.html#method.parameters
+/// [`number_of_parameters`]: #tymethod.number_of_parameters
+pub trait VstParameters: Send + Sync {
+    /// The number of parameters.
+    /// This method should return the same value every time it is called.
+    fn number_of_parameters(&self) -> usize;
+
+    /// The name of the parameter with the given index.
+    fn parameter_name(&self, index: usize) -> String;
+
+    /// The value of the parameter with the given index, as text to display in the host.
+    ///
+    /// The default implementation displays the normalized value.
+    fn parameter_text(&self, index: usize) -> String {
+        format!("{:.3}", self.get_parameter(index))
+    }
+
+    /// Get the normalized value of the parameter with the given index.
+    fn get_parameter(&self, index: usize) -> f32;
+
+    /// Set the normalized value of the parameter with the given index.
+    fn set_parameter(&self, index: usize, value: f32);
+}
+
+// Passes the parameters to the host via the `vst` crate.
+struct VstParametersAdapter {
+    parameters: Option<Arc<dyn VstParameters>>,
+}
+
+impl VstParametersAdapter {
+    fn get(&self, index: i32) -> Option<(&dyn VstParameters, usize)> {
+        let parameters = self.parameters.as_ref()?;
+        if index < 0 || index as usize >= parameters.number_of_parameters() {
+            warn!("Parameter index {} is out of range.", index);
+            return None;
+        }
+        Some((&**parameters, index as usize))
+    }
+}
+
+impl PluginParameters for VstParametersAdapter {
+    fn get_parameter_name(&self, index: i32) -> String {
+        self.get(index)
+            .map_or_else(String::new, |(p, index)| p.parameter_name(index))
+    }
+
+    fn get_parameter_text(&self, index: i32) -> String {
+        self.get(index)
+            .map_or_else(String::new, |(p, index)| p.parameter_text(index))
+    }
+
+    fn get_parameter(&self, index: i32) -> f32 {
+        self.get(index)
+            .map_or(0.0, |(p, index)| p.get_parameter(index))
+    }
+
+    fn set_parameter(&self, index: i32, value: f32) {
+        if let Some((p, index)) = self.get(index) {
+            p.set_parameter(index, value);
+        }
+    }
 }
 
 /// A struct used internally by the [`vst_init`] macro. Normally, plugin's do not need to use this.
@@ -47,10 +154,35 @@ pub trait VstPluginMeta: CommonPluginMeta + AudioHandlerMeta {
 pub struct VstPluginWrapper<P> {
     plugin: P,
     host: HostCallback,
-    inputs_f32: VecStorage<&'static [f32]>,
-    outputs_f32: VecStorage<&'static [f32]>,
-    inputs_f64: VecStorage<&'static [f64]>,
-    outputs_f64: VecStorage<&'static [f64]>,
+    buffers_f32: BufferStorage<f32>,
+    buffers_f64: BufferStorage<f64>,
+    parameters: Option<Arc<dyn VstParameters>>,
+}
+
+impl<P> Debug for VstPluginWrapper<P>
+where
+    P: Debug + AudioHandlerMeta,
+{
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        // The current (normalized) values of the parameters, if any.
+        let parameter_values: Option<Vec<f32>> = self.parameters.as_ref().map(|parameters| {
+            (0..parameters.number_of_parameters())
+                .map(|index| parameters.get_parameter(index))
+                .collect()
+        });
+        f.debug_struct("VstPluginWrapper")
+            .field("plugin", &self.plugin)
+            .field(
+                "number_of_audio_inputs",
+                &self.plugin.max_number_of_audio_inputs(),
+            )
+            .field(
+                "number_of_audio_outputs",
+                &self.plugin.max_number_of_audio_outputs(),
+            )
+            .field("parameter_values", &parameter_values)
+            .finish()
+    }
 }
 
 impl<P> VstPluginWrapper<P>
@@ -65,12 +197,22 @@ where
 {
     pub fn get_info(&self) -> Info {
         trace!("get_info");
+        let mut name = String::new();
+        if let Err(e) = self.plugin.plugin_name(&mut name) {
+            error!("Failed to get the name of the plugin: {}.", e);
+        }
         Info {
-            name: self.plugin.name().to_string(),
+            name,
             inputs: self.plugin.max_number_of_audio_inputs() as i32,
             outputs: self.plugin.max_number_of_audio_outputs() as i32,
             unique_id: self.plugin.plugin_id(),
             category: self.plugin.category(),
+            initial_delay: self.plugin.latency_in_frames() as i32,
+            parameters: self
+                .parameters
+                .as_ref()
+                .map_or(0, |parameters| parameters.number_of_parameters())
+                as i32,
             ..Info::default()
         }
     }
@@ -83,10 +225,15 @@ where
     /// [`VstPluginWrapper`]: ./
     pub fn new(plugin: P, host: HostCallback) -> Self {
         Self {
-            inputs_f32: VecStorage::with_capacity(plugin.max_number_of_audio_inputs()),
-            outputs_f32: VecStorage::with_capacity(plugin.max_number_of_audio_outputs()),
-            inputs_f64: VecStorage::with_capacity(plugin.max_number_of_audio_inputs()),
-            outputs_f64: VecStorage::with_capacity(plugin.max_number_of_audio_outputs()),
+            buffers_f32: BufferStorage::new(
+                plugin.max_number_of_audio_inputs(),
+                plugin.max_number_of_audio_outputs(),
+            ),
+            buffers_f64: BufferStorage::new(
+                plugin.max_number_of_audio_inputs(),
+                plugin.max_number_of_audio_outputs(),
+            ),
+            parameters: plugin.parameters(),
             plugin,
             host,
         }
@@ -96,42 +243,67 @@ where
         &self.host
     }
 
-    pub fn process<'b>(&mut self, buffer: &mut AudioBuffer<'b, f32>) {
-        let number_of_frames = buffer.samples();
-        let (input_buffers, mut output_buffers) = buffer.split();
+    pub fn suspend(&mut self) {
+        trace!("suspend");
+        self.plugin.suspend();
+    }
 
-        let mut inputs = self.inputs_f32.vec_guard();
-        for input_buffer in input_buffers.into_iter().take(inputs.capacity()) {
-            inputs.push(input_buffer);
-        }
+    pub fn resume(&mut self) {
+        trace!("resume");
+        self.plugin.resume();
+    }
 
-        let mut outputs = self.outputs_f32.vec_guard();
-        for output_buffer in output_buffers.into_iter().take(outputs.capacity()) {
-            outputs.push(output_buffer);
+    pub fn get_tail_size(&self) -> isize {
+        // For VST, `0` means "unknown" and `1` means "no tail".
+        match self.plugin.tail_size_in_frames() {
+            None => 0,
+            Some(0) => 1,
+            Some(tail_size) => tail_size as isize,
         }
+    }
 
-        let mut audio_buffer =
-            AudioBufferInOut::new(inputs.as_slice(), outputs.as_mut_slice(), number_of_frames);
-        self.plugin.render_buffer(&mut audio_buffer, &mut self.host);
+    pub fn get_parameter_object(&self) -> Arc<dyn PluginParameters> {
+        Arc::new(VstParametersAdapter {
+            parameters: self.parameters.clone(),
+        })
+    }
+
+    pub fn process<'b>(&mut self, buffer: &mut AudioBuffer<'b, f32>) {
+        Self::process_generic(
+            &mut self.plugin,
+            &mut self.host,
+            &mut self.buffers_f32,
+            buffer,
+        );
     }
 
     pub fn process_f64<'b>(&mut self, buffer: &mut AudioBuffer<'b, f64>) {
+        Self::process_generic(
+            &mut self.plugin,
+            &mut self.host,
+            &mut self.buffers_f64,
+            buffer,
+        );
+    }
+
+    // Shared by `process` and `process_f64`, so that both sample types are handled the same way.
+    fn process_generic<'b, S>(
+        plugin: &mut P,
+        host: &mut HostCallback,
+        buffers: &mut BufferStorage<S>,
+        buffer: &mut AudioBuffer<'b, S>,
+    ) where
+        S: Float + 'static,
+        P: ContextualAudioRenderer<S, HostCallback>,
+    {
         let number_of_frames = buffer.samples();
         let (input_buffers, mut output_buffers) = buffer.split();
-
-        let mut inputs = self.inputs_f64.vec_guard();
-        for input_buffer in input_buffers.into_iter().take(inputs.capacity()) {
-            inputs.push(input_buffer);
-        }
-
-        let mut outputs = self.outputs_f64.vec_guard();
-        for output_buffer in output_buffers.into_iter().take(outputs.capacity()) {
-            outputs.push(output_buffer);
-        }
-
-        let mut audio_buffer =
-            AudioBufferInOut::new(inputs.as_slice(), outputs.as_mut_slice(), number_of_frames);
-        self.plugin.render_buffer(&mut audio_buffer, &mut self.host);
+        buffers.with_buffers(
+            input_buffers.into_iter(),
+            output_buffers.into_iter(),
+            number_of_frames,
+            |audio_buffer| plugin.render_buffer(audio_buffer, host),
+        );
     }
 
     pub fn get_input_info(&self, input_index: i32) -> ChannelInfo {
@@ -176,6 +348,8 @@ where
                 VstEvent::Midi(VstMidiEvent {
                     data, delta_frames, ..
                 }) => {
+                    // Vst midi events always have exactly three bytes, so they always
+                    // fit in a `RawMidiEvent`. Longer messages are sent as SysEx events.
                     let event = Timed {
                         time_in_frames: delta_frames as u32,
                         event: RawMidiEvent::new(&data),
@@ -199,6 +373,29 @@ impl HostInterface for HostCallback {
         // TODO: Return true for these hosts.
         false
     }
+
+    fn transport(&self) -> Option<Transport> {
+        let mask = TimeInfoFlags::TEMPO_VALID | TimeInfoFlags::TIME_SIG_VALID;
+        let time_info = self.get_time_info(mask.bits())?;
+        let flags = TimeInfoFlags::from_bits_truncate(time_info.flags);
+        Some(Transport {
+            tempo_bpm: if flags.contains(TimeInfoFlags::TEMPO_VALID) {
+                Some(time_info.tempo)
+            } else {
+                None
+            },
+            position_in_frames: time_info.sample_pos.max(0.0) as u64,
+            time_signature: if flags.contains(TimeInfoFlags::TIME_SIG_VALID) {
+                Some(TimeSignature {
+                    numerator: time_info.time_sig_numerator as u32,
+                    denominator: time_info.time_sig_denominator as u32,
+                })
+            } else {
+                None
+            },
+            is_playing: flags.contains(TimeInfoFlags::TRANSPORT_PLAYING),
+        })
+    }
 }
 
 /// A wrapper around the `plugin_main!` macro from the `vst` crate.
@@ -524,8 +721,373 @@ macro_rules! vst_init {
             fn process_events(&mut self, events: &vst::api::Events) {
                 self.wrapper.process_events(events)
             }
+
+            fn suspend(&mut self) {
+                self.wrapper.suspend()
+            }
+
+            fn resume(&mut self) {
+                self.wrapper.resume()
+            }
+
+            fn get_tail_size(&self) -> isize {
+                self.wrapper.get_tail_size()
+            }
+
+            fn get_parameter_object(&mut self) -> std::sync::Arc<dyn vst::plugin::PluginParameters> {
+                self.wrapper.get_parameter_object()
+            }
         }
 
         plugin_main!(VstWrapperWrapper);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::vst::buffer::AudioBuffer;
+    use super::vst::plugin::{Category, HostCallback};
+    use super::{VstParameters, VstPluginMeta, VstPluginWrapper};
+    use crate::buffer::AudioBufferInOut;
+    use crate::event::{ContextualEventHandler, RawMidiEvent, SysExEvent, Timed};
+    use crate::meta::{DynamicName, InOut, Meta, MetaData, Name, NameAndLatency};
+    use crate::{AudioHandler, ContextualAudioRenderer};
+    use num_traits::{AsPrimitive, Float};
+    use std::fmt::{Debug, Formatter, Write};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    struct LookAheadPlugin<N> {
+        meta: MetaData<NameAndLatency<N>, &'static str, &'static str>,
+    }
+
+    impl<N> LookAheadPlugin<N> {
+        fn new(name: N) -> Self {
+            LookAheadPlugin {
+                meta: MetaData {
+                    general_meta: NameAndLatency {
+                        name,
+                        latency_in_frames: 128,
+                    },
+                    audio_port_meta: InOut {
+                        inputs: vec!["in"],
+                        outputs: vec!["out"],
+                    },
+                    midi_port_meta: InOut {
+                        inputs: vec![],
+                        outputs: vec![],
+                    },
+                },
+            }
+        }
+    }
+
+    impl<N> Meta for LookAheadPlugin<N> {
+        type MetaData = MetaData<NameAndLatency<N>, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    impl<N> VstPluginMeta for LookAheadPlugin<N>
+    where
+        N: Name,
+    {
+        fn plugin_id(&self) -> i32 {
+            1234
+        }
+        fn category(&self) -> Category {
+            Category::Effect
+        }
+        fn tail_size_in_frames(&self) -> Option<usize> {
+            Some(256)
+        }
+    }
+
+    impl<N> AudioHandler for LookAheadPlugin<N> {
+        fn set_sample_rate(&mut self, _sample_rate: f64) {}
+    }
+
+    impl<N, S> ContextualAudioRenderer<S, HostCallback> for LookAheadPlugin<N>
+    where
+        S: Copy + 'static,
+    {
+        fn render_buffer(
+            &mut self,
+            _buffer: &mut AudioBufferInOut<S>,
+            _context: &mut HostCallback,
+        ) {
+        }
+    }
+
+    impl<N> ContextualEventHandler<Timed<RawMidiEvent>, HostCallback> for LookAheadPlugin<N> {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut HostCallback) {}
+    }
+
+    impl<'a, N> ContextualEventHandler<Timed<SysExEvent<'a>>, HostCallback> for LookAheadPlugin<N> {
+        fn handle_event(&mut self, _event: Timed<SysExEvent<'a>>, _context: &mut HostCallback) {}
+    }
+
+    #[test]
+    fn latency_is_reported_in_the_info() {
+        let plugin = LookAheadPlugin::new("Look-ahead");
+        let wrapper = VstPluginWrapper::new(plugin, HostCallback::default());
+        let info = wrapper.get_info();
+        assert_eq!(info.initial_delay, 128);
+        assert_eq!(info.name, "Look-ahead");
+        assert_eq!(info.parameters, 0);
+        assert_eq!(wrapper.get_tail_size(), 256);
+    }
+
+    #[test]
+    fn dynamic_name_is_reported_in_the_info() {
+        let instance_number = 3;
+        let plugin = LookAheadPlugin::new(DynamicName(move |buffer: &mut dyn Write| {
+            write!(buffer, "Look-ahead #{}", instance_number)
+        }));
+        let wrapper = VstPluginWrapper::new(plugin, HostCallback::default());
+        assert_eq!(wrapper.get_info().name, "Look-ahead #3");
+    }
+
+    // One parameter, stored as the bits of an `f32`.
+    struct GainParameter(AtomicU32);
+
+    impl VstParameters for GainParameter {
+        fn number_of_parameters(&self) -> usize {
+            1
+        }
+
+        fn parameter_name(&self, _index: usize) -> String {
+            "gain".to_string()
+        }
+
+        fn get_parameter(&self, _index: usize) -> f32 {
+            f32::from_bits(self.0.load(Ordering::Relaxed))
+        }
+
+        fn set_parameter(&self, _index: usize, value: f32) {
+            self.0.store(value.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    struct PluginWithParameters {
+        meta: MetaData<&'static str, &'static str, &'static str>,
+        gain: Arc<GainParameter>,
+    }
+
+    impl Debug for PluginWithParameters {
+        fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+            f.write_str("PluginWithParameters")
+        }
+    }
+
+    impl Meta for PluginWithParameters {
+        type MetaData = MetaData<&'static str, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    impl VstPluginMeta for PluginWithParameters {
+        fn plugin_id(&self) -> i32 {
+            4321
+        }
+        fn category(&self) -> Category {
+            Category::Effect
+        }
+        fn parameters(&self) -> Option<Arc<dyn VstParameters>> {
+            Some(self.gain.clone())
+        }
+    }
+
+    impl AudioHandler for PluginWithParameters {
+        fn set_sample_rate(&mut self, _sample_rate: f64) {}
+    }
+
+    impl<S> ContextualAudioRenderer<S, HostCallback> for PluginWithParameters
+    where
+        S: Copy + 'static,
+    {
+        fn render_buffer(
+            &mut self,
+            _buffer: &mut AudioBufferInOut<S>,
+            _context: &mut HostCallback,
+        ) {
+        }
+    }
+
+    impl ContextualEventHandler<Timed<RawMidiEvent>, HostCallback> for PluginWithParameters {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut HostCallback) {}
+    }
+
+    impl<'a> ContextualEventHandler<Timed<SysExEvent<'a>>, HostCallback> for PluginWithParameters {
+        fn handle_event(&mut self, _event: Timed<SysExEvent<'a>>, _context: &mut HostCallback) {}
+    }
+
+    fn plugin_with_gain(gain: Arc<GainParameter>) -> PluginWithParameters {
+        PluginWithParameters {
+            meta: MetaData {
+                general_meta: "Gain",
+                audio_port_meta: InOut {
+                    inputs: vec!["in"],
+                    outputs: vec!["out"],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec![],
+                },
+            },
+            gain,
+        }
+    }
+
+    #[test]
+    fn parameters_are_forwarded_to_the_plugin() {
+        let gain = Arc::new(GainParameter(AtomicU32::new(0.5f32.to_bits())));
+        let plugin = plugin_with_gain(gain.clone());
+        let wrapper = VstPluginWrapper::new(plugin, HostCallback::default());
+        assert_eq!(wrapper.get_info().parameters, 1);
+        // The default tail size is "unknown".
+        assert_eq!(wrapper.get_tail_size(), 0);
+        let parameters = wrapper.get_parameter_object();
+        assert_eq!(parameters.get_parameter_name(0), "gain");
+        assert_eq!(parameters.get_parameter(0), 0.5);
+        parameters.set_parameter(0, 0.25);
+        assert_eq!(gain.get_parameter(0), 0.25);
+        assert_eq!(parameters.get_parameter_text(0), "0.250");
+        // Out of range indices are ignored.
+        parameters.set_parameter(1, 1.0);
+        assert_eq!(parameters.get_parameter(1), 0.0);
+    }
+
+    #[test]
+    fn debug_output_mentions_the_ports_and_the_parameter_values() {
+        let gain = Arc::new(GainParameter(AtomicU32::new(0.5f32.to_bits())));
+        let wrapper = VstPluginWrapper::new(plugin_with_gain(gain), HostCallback::default());
+        let output = format!("{:?}", wrapper);
+        assert!(output.contains("PluginWithParameters"));
+        assert!(output.contains("number_of_audio_inputs: 1"));
+        assert!(output.contains("number_of_audio_outputs: 1"));
+        assert!(output.contains("parameter_values: Some([0.5])"));
+    }
+
+    // Records the inputs and writes the first input, multiplied by two, to every output.
+    struct RecordingPlugin {
+        meta: MetaData<&'static str, &'static str, &'static str>,
+        recorded_inputs: Vec<Vec<f64>>,
+    }
+
+    impl Meta for RecordingPlugin {
+        type MetaData = MetaData<&'static str, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    impl VstPluginMeta for RecordingPlugin {
+        fn plugin_id(&self) -> i32 {
+            5678
+        }
+        fn category(&self) -> Category {
+            Category::Effect
+        }
+    }
+
+    impl AudioHandler for RecordingPlugin {
+        fn set_sample_rate(&mut self, _sample_rate: f64) {}
+    }
+
+    impl<S> ContextualAudioRenderer<S, HostCallback> for RecordingPlugin
+    where
+        S: Copy + AsPrimitive<f64>,
+        f64: AsPrimitive<S>,
+    {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>, _context: &mut HostCallback) {
+            let number_of_frames = buffer.number_of_frames();
+            for input in buffer.inputs().channels() {
+                self.recorded_inputs
+                    .push(input[..number_of_frames].iter().map(|s| s.as_()).collect());
+            }
+            let first_input: Vec<f64> = buffer.inputs().channels()[0][..number_of_frames]
+                .iter()
+                .map(|s| s.as_())
+                .collect();
+            for output in buffer.outputs().channel_iter_mut() {
+                for (o, i) in output.iter_mut().zip(first_input.iter()) {
+                    *o = (2.0 * i).as_();
+                }
+            }
+        }
+    }
+
+    impl ContextualEventHandler<Timed<RawMidiEvent>, HostCallback> for RecordingPlugin {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut HostCallback) {}
+    }
+
+    impl<'a> ContextualEventHandler<Timed<SysExEvent<'a>>, HostCallback> for RecordingPlugin {
+        fn handle_event(&mut self, _event: Timed<SysExEvent<'a>>, _context: &mut HostCallback) {}
+    }
+
+    // Returns the inputs as seen by the plugin and the outputs as seen by the host.
+    fn process_with<S>(
+        process: fn(&mut VstPluginWrapper<RecordingPlugin>, &mut AudioBuffer<S>),
+    ) -> (Vec<Vec<f64>>, Vec<Vec<f64>>)
+    where
+        S: Float + AsPrimitive<f64>,
+        f64: AsPrimitive<S>,
+    {
+        let plugin = RecordingPlugin {
+            meta: MetaData {
+                general_meta: "Recording",
+                audio_port_meta: InOut {
+                    inputs: vec!["left in", "right in"],
+                    outputs: vec!["left out", "right out"],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec![],
+                },
+            },
+            recorded_inputs: Vec::new(),
+        };
+        let mut wrapper = VstPluginWrapper::new(plugin, HostCallback::default());
+        let left_in: Vec<S> = [0.0, 0.25, -0.5, 1.0]
+            .iter()
+            .map(|s: &f64| s.as_())
+            .collect();
+        let right_in: Vec<S> = [1.0, 0.5, 0.0, -0.25]
+            .iter()
+            .map(|s: &f64| s.as_())
+            .collect();
+        let mut left_out = vec![S::zero(); 4];
+        let mut right_out = vec![S::zero(); 4];
+        let inputs = [left_in.as_ptr(), right_in.as_ptr()];
+        let mut outputs = [left_out.as_mut_ptr(), right_out.as_mut_ptr()];
+        {
+            let mut buffer =
+                unsafe { AudioBuffer::from_raw(2, 2, inputs.as_ptr(), outputs.as_mut_ptr(), 4) };
+            process(&mut wrapper, &mut buffer);
+        }
+        let outputs = vec![
+            left_out.iter().map(|s| s.as_()).collect(),
+            right_out.iter().map(|s| s.as_()).collect(),
+        ];
+        (wrapper.plugin.recorded_inputs, outputs)
+    }
+
+    #[test]
+    fn f32_and_f64_are_processed_in_the_same_way() {
+        let (inputs_f32, outputs_f32) = process_with::<f32>(VstPluginWrapper::process);
+        let (inputs_f64, outputs_f64) = process_with::<f64>(VstPluginWrapper::process_f64);
+        assert_eq!(
+            inputs_f64,
+            vec![vec![0.0, 0.25, -0.5, 1.0], vec![1.0, 0.5, 0.0, -0.25]]
+        );
+        assert_eq!(
+            outputs_f64,
+            vec![vec![0.0, 0.5, -1.0, 2.0], vec![0.0, 0.5, -1.0, 2.0]]
+        );
+        assert_eq!(inputs_f32, inputs_f64);
+        assert_eq!(outputs_f32, outputs_f64);
+    }
+}
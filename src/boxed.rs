@@ -0,0 +1,221 @@
+//! Select a plugin at run-time.
+//!
+//! A host application that lets the user choose among several plugins cannot store
+//! the plugins in a variable of one concrete type.
+//! [`BoxedRenderer`] erases the type of the plugin, so that plugins of different types
+//! can be stored e.g. in one `Vec`.
+//!
+//! [`BoxedRenderer`]: ./struct.BoxedRenderer.html
+use crate::buffer::AudioBufferInOut;
+use crate::event::{ContextualEventHandler, RawMidiEvent, Timed};
+use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+
+/// The traits a plugin needs to implement in order to be stored in a [`BoxedRenderer`].
+///
+/// This trait is implemented for all types that implement the required traits,
+/// you do not need to implement it yourself.
+///
+/// [`BoxedRenderer`]: ./struct.BoxedRenderer.html
+pub trait BoxablePlugin<C>:
+    ContextualAudioRenderer<f32, C>
+    + ContextualEventHandler<Timed<RawMidiEvent>, C>
+    + AudioHandler
+    + AudioHandlerMeta
+{
+}
+
+impl<P, C> BoxablePlugin<C> for P where
+    P: ContextualAudioRenderer<f32, C>
+        + ContextualEventHandler<Timed<RawMidiEvent>, C>
+        + AudioHandler
+        + AudioHandlerMeta
+{
+}
+
+/// A plugin of which the type has been erased, for a fixed context `C`.
+///
+/// `BoxedRenderer<C>` renders `f32` samples, handles [`Timed`]`<`[`RawMidiEvent`]`>` events
+/// and forwards the methods of [`AudioHandler`] and [`AudioHandlerMeta`] to the
+/// underlying plugin.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::boxed::BoxedRenderer;
+/// use rsynth::buffer::AudioBufferInOut;
+/// use rsynth::event::{ContextualEventHandler, RawMidiEvent, Timed};
+/// use rsynth::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+///
+/// struct Silence;
+/// # impl AudioHandler for Silence {
+/// #     fn set_sample_rate(&mut self, _sample_rate: f64) {}
+/// # }
+/// # impl AudioHandlerMeta for Silence {
+/// #     fn max_number_of_audio_inputs(&self) -> usize { 0 }
+/// #     fn max_number_of_audio_outputs(&self) -> usize { 1 }
+/// # }
+/// # impl<C> ContextualEventHandler<Timed<RawMidiEvent>, C> for Silence {
+/// #     fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut C) {}
+/// # }
+/// # impl<C> ContextualAudioRenderer<f32, C> for Silence {
+/// #     fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut C) {
+/// #         buffer.outputs().set(0.0);
+/// #     }
+/// # }
+/// // `Silence` implements `AudioHandler`, `AudioHandlerMeta`, `ContextualAudioRenderer<f32, C>`
+/// // and `ContextualEventHandler<Timed<RawMidiEvent>, C>`.
+/// let plugins: Vec<BoxedRenderer<()>> = vec![BoxedRenderer::new(Silence)];
+/// ```
+///
+/// [`Timed`]: ../event/struct.Timed.html
+/// [`RawMidiEvent`]: ../event/struct.RawMidiEvent.html
+/// [`AudioHandler`]: ../trait.AudioHandler.html
+/// [`AudioHandlerMeta`]: ../trait.AudioHandlerMeta.html
+pub struct BoxedRenderer<C> {
+    plugin: Box<dyn BoxablePlugin<C> + Send>,
+}
+
+impl<C> BoxedRenderer<C> {
+    /// Create a new `BoxedRenderer` from the given plugin.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn new<P>(plugin: P) -> Self
+    where
+        P: BoxablePlugin<C> + Send + 'static,
+    {
+        Self {
+            plugin: Box::new(plugin),
+        }
+    }
+}
+
+impl<C> AudioHandler for BoxedRenderer<C> {
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.plugin.set_sample_rate(sample_rate)
+    }
+}
+
+impl<C> AudioHandlerMeta for BoxedRenderer<C> {
+    fn max_number_of_audio_inputs(&self) -> usize {
+        self.plugin.max_number_of_audio_inputs()
+    }
+
+    fn max_number_of_audio_outputs(&self) -> usize {
+        self.plugin.max_number_of_audio_outputs()
+    }
+}
+
+impl<C> ContextualAudioRenderer<f32, C> for BoxedRenderer<C> {
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut C) {
+        self.plugin.render_buffer(buffer, context)
+    }
+}
+
+impl<C> ContextualEventHandler<Timed<RawMidiEvent>, C> for BoxedRenderer<C> {
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>, context: &mut C) {
+        self.plugin.handle_event(event, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoxedRenderer;
+    use crate::buffer::{AudioBufferInOut, AudioChunk};
+    use crate::event::{ContextualEventHandler, RawMidiEvent, Timed};
+    use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+
+    struct SomeHost {
+        number_of_buffers_rendered: usize,
+    }
+
+    // Outputs a constant value.
+    struct Constant(f32);
+
+    impl AudioHandler for Constant {
+        fn set_sample_rate(&mut self, _sample_rate: f64) {}
+    }
+
+    impl AudioHandlerMeta for Constant {
+        fn max_number_of_audio_inputs(&self) -> usize {
+            0
+        }
+
+        fn max_number_of_audio_outputs(&self) -> usize {
+            1
+        }
+    }
+
+    impl ContextualEventHandler<Timed<RawMidiEvent>, SomeHost> for Constant {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut SomeHost) {}
+    }
+
+    impl ContextualAudioRenderer<f32, SomeHost> for Constant {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut SomeHost) {
+            buffer.outputs().set(self.0);
+            context.number_of_buffers_rendered += 1;
+        }
+    }
+
+    // Outputs the velocity of the last note that was received, divided by 100.
+    struct Velocity(f32);
+
+    impl AudioHandler for Velocity {
+        fn set_sample_rate(&mut self, _sample_rate: f64) {}
+    }
+
+    impl AudioHandlerMeta for Velocity {
+        fn max_number_of_audio_inputs(&self) -> usize {
+            0
+        }
+
+        fn max_number_of_audio_outputs(&self) -> usize {
+            1
+        }
+    }
+
+    impl ContextualEventHandler<Timed<RawMidiEvent>, SomeHost> for Velocity {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>, _context: &mut SomeHost) {
+            self.0 = event.event.data()[2] as f32 / 100.0;
+        }
+    }
+
+    impl ContextualAudioRenderer<f32, SomeHost> for Velocity {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, context: &mut SomeHost) {
+            buffer.outputs().set(self.0);
+            context.number_of_buffers_rendered += 1;
+        }
+    }
+
+    #[test]
+    fn different_plugins_can_be_stored_in_one_vec_and_rendered() {
+        let mut plugins: Vec<BoxedRenderer<SomeHost>> = vec![
+            BoxedRenderer::new(Constant(0.25)),
+            BoxedRenderer::new(Velocity(0.0)),
+        ];
+        let mut host = SomeHost {
+            number_of_buffers_rendered: 0,
+        };
+        let mut outputs = Vec::new();
+        for plugin in plugins.iter_mut() {
+            assert_eq!(plugin.max_number_of_audio_outputs(), 1);
+            plugin.set_sample_rate(44100.0);
+            plugin.handle_event(Timed::new(0, RawMidiEvent::new(&[0x90, 60, 50])), &mut host);
+            let mut output = AudioChunk::zero(1, 3);
+            {
+                let mut output_slices = output.as_mut_slices();
+                let mut buffer = AudioBufferInOut::new(&[], &mut output_slices, 3);
+                plugin.render_buffer(&mut buffer, &mut host);
+            }
+            outputs.push(output);
+        }
+        assert_eq!(host.number_of_buffers_rendered, 2);
+        assert_eq!(
+            outputs,
+            vec![
+                audio_chunk![[0.25, 0.25, 0.25]],
+                audio_chunk![[0.5, 0.5, 0.5]]
+            ]
+        );
+    }
+}
@@ -39,9 +39,10 @@ use crate::rsor::Slice;
 use crate::test_utilities::{DummyEventHandler, TestPlugin};
 use crate::vecstorage::VecStorage;
 use crate::ContextualAudioRenderer;
-use num_traits::Zero;
+use num_traits::{AsPrimitive, Float, Zero};
+use std::marker::PhantomData;
 use std::mem;
-use std::ops::{Bound, Index, IndexMut, RangeBounds};
+use std::ops::{Bound, Index, IndexMut, Mul, Range, RangeBounds};
 use std::slice::SliceIndex;
 
 fn number_of_frames_in_range<R: RangeBounds<usize>>(number_of_frames: usize, range: R) -> usize {
@@ -586,6 +587,51 @@ where
         }
     }
 
+    /// Set all samples to zero.
+    ///
+    /// This iterates over every frame of every channel. It is typically used to initialize
+    /// the outputs before adding to them (e.g. in a polyphonic synthesizer, where every
+    /// voice adds its own sound to the output).
+    pub fn silence(&mut self)
+    where
+        S: Zero,
+    {
+        self.set(S::zero())
+    }
+
+    /// Multiply all samples with the given gain.
+    pub fn apply_gain(&mut self, gain: S)
+    where
+        S: Mul<Output = S>,
+    {
+        for channel in self.channels.iter_mut() {
+            for sample in channel.iter_mut() {
+                *sample = *sample * gain;
+            }
+        }
+    }
+
+    /// Multiply the samples of each channel with the gain for that channel:
+    /// the samples of the channel with index `i` are multiplied with `gains[i]`.
+    ///
+    /// # Panics
+    /// Panics if `gains.len()` is not equal to the number of channels.
+    pub fn apply_gain_per_channel(&mut self, gains: &[S])
+    where
+        S: Mul<Output = S>,
+    {
+        assert_eq!(
+            gains.len(),
+            self.channels.len(),
+            "The number of gains must be equal to the number of channels."
+        );
+        for (channel, gain) in self.channels.iter_mut().zip(gains.iter()) {
+            for sample in channel.iter_mut() {
+                *sample = *sample * *gain;
+            }
+        }
+    }
+
     /// Get an iterator over the channels.
     pub fn channel_iter_mut<'a>(&'a mut self) -> AudioBufferOutChannelIteratorMut<'a, 'samples, S> {
         AudioBufferOutChannelIteratorMut {
@@ -593,6 +639,35 @@ where
         }
     }
 
+    /// Get an iterator over the frames, for frame-by-frame processing.
+    ///
+    /// Each frame can be indexed by the channel index.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::buffer::AudioBufferOut;
+    ///
+    /// let mut left = vec![0; 3];
+    /// let mut right = vec![0; 3];
+    /// let mut channels = [left.as_mut_slice(), right.as_mut_slice()];
+    /// let mut buffer = AudioBufferOut::new(&mut channels, 3);
+    /// for (index, mut frame) in buffer.frames_mut().enumerate() {
+    ///     frame[0] = index;
+    ///     frame[1] = 10 * index;
+    /// }
+    /// assert_eq!(left, vec![0, 1, 2]);
+    /// assert_eq!(right, vec![0, 10, 20]);
+    /// ```
+    pub fn frames_mut<'a>(&'a mut self) -> AudioBufferOutFrameIteratorMut<'a, 'samples, S> {
+        AudioBufferOutFrameIteratorMut {
+            channels: self.channels.as_mut_ptr(),
+            number_of_channels: self.channels.len(),
+            frame_index: 0,
+            number_of_frames: self.length,
+            _phantom: PhantomData,
+        }
+    }
+
     /// Convert to an [`AudioBufferIn`].
     ///
     /// [`AudioBufferIn`]: struct.AudioBufferIn.html
@@ -626,6 +701,164 @@ impl<'channels, 'samples, S> Iterator for AudioBufferOutChannelIteratorMut<'chan
     }
 }
 
+/// An iterator over the frames of an [`AudioBufferOut`], created by the [`frames_mut`] method.
+///
+/// [`AudioBufferOut`]: ./struct.AudioBufferOut.html
+/// [`frames_mut`]: ./struct.AudioBufferOut.html#method.frames_mut
+pub struct AudioBufferOutFrameIteratorMut<'channels, 'samples, S> {
+    // Invariant: points to `number_of_channels` channels, that all have `number_of_frames`
+    // samples and that are borrowed mutably for the lifetime `'channels`.
+    channels: *mut &'samples mut [S],
+    number_of_channels: usize,
+    frame_index: usize,
+    number_of_frames: usize,
+    _phantom: PhantomData<&'channels mut [&'samples mut [S]]>,
+}
+
+impl<'channels, 'samples, S> Iterator for AudioBufferOutFrameIteratorMut<'channels, 'samples, S> {
+    type Item = FrameMut<'channels, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frame_index >= self.number_of_frames {
+            return None;
+        }
+        let frame = FrameMut {
+            channels: self.channels as *const &'samples mut [S] as *const *mut [S],
+            number_of_channels: self.number_of_channels,
+            frame_index: self.frame_index,
+            _phantom: PhantomData,
+        };
+        self.frame_index += 1;
+        Some(frame)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.number_of_frames - self.frame_index;
+        (remaining, Some(remaining))
+    }
+}
+
+/// The samples of all channels of an [`AudioBufferOut`] at one frame, as yielded by the
+/// [`frames_mut`] method.
+///
+/// Index a `FrameMut` with the index of the channel to get the corresponding sample.
+///
+/// [`AudioBufferOut`]: ./struct.AudioBufferOut.html
+/// [`frames_mut`]: ./struct.AudioBufferOut.html#method.frames_mut
+pub struct FrameMut<'channels, S> {
+    // The channels are only accessed at `frame_index`, which is different for every
+    // `FrameMut`, so different `FrameMut`s never access the same sample.
+    channels: *const *mut [S],
+    number_of_channels: usize,
+    frame_index: usize,
+    _phantom: PhantomData<&'channels mut S>,
+}
+
+impl<'channels, S> FrameMut<'channels, S> {
+    /// Get the number of channels.
+    pub fn number_of_channels(&self) -> usize {
+        self.number_of_channels
+    }
+
+    fn sample_pointer(&self, channel_index: usize) -> *mut S {
+        assert!(
+            channel_index < self.number_of_channels,
+            "Channel index out of bounds."
+        );
+        // This is safe because `channel_index` is in bounds and every channel has more than
+        // `frame_index` samples (see the invariant of `AudioBufferOutFrameIteratorMut`).
+        unsafe { (*self.channels.add(channel_index) as *mut S).add(self.frame_index) }
+    }
+}
+
+impl<'channels, S> Index<usize> for FrameMut<'channels, S> {
+    type Output = S;
+
+    fn index(&self, channel_index: usize) -> &Self::Output {
+        unsafe { &*self.sample_pointer(channel_index) }
+    }
+}
+
+impl<'channels, S> IndexMut<usize> for FrameMut<'channels, S> {
+    fn index_mut(&mut self, channel_index: usize) -> &mut Self::Output {
+        unsafe { &mut *self.sample_pointer(channel_index) }
+    }
+}
+
+#[test]
+fn buffer_out_silence_sets_all_samples_to_zero() {
+    let mut channel1 = vec![1.0, 2.0];
+    let mut channel2 = vec![-4.0, 0.5];
+    {
+        let mut chunk = [channel1.as_mut_slice(), channel2.as_mut_slice()];
+        AudioBufferOut::new(&mut chunk, 2).silence();
+    }
+    assert_eq!(channel1, vec![0.0, 0.0]);
+    assert_eq!(channel2, vec![0.0, 0.0]);
+}
+
+#[test]
+fn buffer_out_apply_gain_scales_all_channels() {
+    let mut channel1 = vec![1.0, 2.0];
+    let mut channel2 = vec![-4.0, 0.5];
+    {
+        let mut chunk = [channel1.as_mut_slice(), channel2.as_mut_slice()];
+        AudioBufferOut::new(&mut chunk, 2).apply_gain(0.5);
+    }
+    assert_eq!(channel1, vec![0.5, 1.0]);
+    assert_eq!(channel2, vec![-2.0, 0.25]);
+}
+
+#[test]
+fn buffer_out_apply_gain_per_channel_scales_each_channel_independently() {
+    let mut channel1 = vec![1.0, 2.0];
+    let mut channel2 = vec![-4.0, 0.5];
+    {
+        let mut chunk = [channel1.as_mut_slice(), channel2.as_mut_slice()];
+        AudioBufferOut::new(&mut chunk, 2).apply_gain_per_channel(&[2.0, 0.25]);
+    }
+    assert_eq!(channel1, vec![2.0, 4.0]);
+    assert_eq!(channel2, vec![-1.0, 0.125]);
+}
+
+#[test]
+#[should_panic]
+fn buffer_out_apply_gain_per_channel_panics_when_number_of_gains_is_wrong() {
+    let mut channel1 = vec![1.0, 2.0];
+    let mut channel2 = vec![-4.0, 0.5];
+    let mut chunk = [channel1.as_mut_slice(), channel2.as_mut_slice()];
+    AudioBufferOut::new(&mut chunk, 2).apply_gain_per_channel(&[2.0]);
+}
+
+#[test]
+fn buffer_out_frames_mut_yields_every_frame() {
+    let mut channel1 = vec![0; 3];
+    let mut channel2 = vec![0; 3];
+    {
+        let mut chunk = [channel1.as_mut_slice(), channel2.as_mut_slice()];
+        let mut buffer = AudioBufferOut::new(&mut chunk, 3);
+        let mut frames: Vec<_> = buffer.frames_mut().collect();
+        assert_eq!(frames.len(), 3);
+        for (index, frame) in frames.iter_mut().enumerate() {
+            assert_eq!(frame.number_of_channels(), 2);
+            frame[0] = index + 1;
+            frame[1] = frame[0] * 10;
+        }
+    }
+    assert_eq!(channel1, vec![1, 2, 3]);
+    assert_eq!(channel2, vec![10, 20, 30]);
+}
+
+#[test]
+#[should_panic]
+fn buffer_out_frames_mut_panics_when_channel_index_is_out_of_bounds() {
+    let mut channel = vec![0; 3];
+    let mut chunk = [channel.as_mut_slice()];
+    let mut buffer = AudioBufferOut::new(&mut chunk, 3);
+    let mut frame = buffer.frames_mut().next().unwrap();
+    frame[1] = 1;
+}
+
 #[test]
 fn buffer_out_index_frames_works() {
     let mut channel1 = vec![11, 12, 13, 14];
@@ -1288,6 +1521,211 @@ fn interleave_works_with_empty_event_iterator() {
     assert_eq!(provided_output, expected_output);
 }
 
+#[test]
+fn buffer_in_out_reports_the_number_of_channels_of_the_constructed_slices() {
+    let input = audio_chunk![[11, 12, 13]];
+    let mut output = audio_chunk![[0, 0, 0], [0, 0, 0]];
+    let input = input.as_slices();
+    let mut output = output.as_mut_slices();
+    let buffer = AudioBufferInOut::new(&input, &mut output, 3);
+    assert_eq!(buffer.number_of_input_channels(), 1);
+    assert_eq!(buffer.number_of_output_channels(), 2);
+}
+
+/// Keeps track of the number of channels that the host actually provides.
+///
+/// Some hosts (e.g. VST hosts) may provide fewer channels than the plugin supports.
+/// By calling [`observe`] at the start of each call to `render_buffer`, the plugin can find out
+/// how many channels it really gets and adapt (e.g. skip stereo processing when given mono).
+///
+/// # Example
+/// ```
+/// use rsynth::buffer::{AudioBufferInOut, ChannelCountTracker};
+///
+/// struct MyPlugin {
+///     channel_count_tracker: ChannelCountTracker,
+/// }
+///
+/// impl MyPlugin {
+///     fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>) {
+///         if self.channel_count_tracker.observe(buffer) {
+///             // The number of channels has changed, react accordingly.
+///         }
+///         if self.channel_count_tracker.number_of_output_channels() == Some(1) {
+///             // Mono processing.
+///         }
+///     }
+/// }
+/// ```
+///
+/// [`observe`]: #method.observe
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChannelCountTracker {
+    channel_counts: Option<(usize, usize)>,
+}
+
+impl ChannelCountTracker {
+    /// Create a new `ChannelCountTracker` that has not observed any buffer yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the number of input and output channels of the given buffer.
+    ///
+    /// Return `true` if this is the first buffer that is observed or if the number of
+    /// channels differs from that of the previously observed buffer.
+    pub fn observe<S>(&mut self, buffer: &AudioBufferInOut<S>) -> bool
+    where
+        S: 'static + Copy,
+    {
+        let channel_counts = Some((
+            buffer.number_of_input_channels(),
+            buffer.number_of_output_channels(),
+        ));
+        let changed = self.channel_counts != channel_counts;
+        self.channel_counts = channel_counts;
+        changed
+    }
+
+    /// The number of input channels of the last buffer that has been observed,
+    /// or `None` if no buffer has been observed yet.
+    pub fn number_of_input_channels(&self) -> Option<usize> {
+        self.channel_counts.map(|(inputs, _)| inputs)
+    }
+
+    /// The number of output channels of the last buffer that has been observed,
+    /// or `None` if no buffer has been observed yet.
+    pub fn number_of_output_channels(&self) -> Option<usize> {
+        self.channel_counts.map(|(_, outputs)| outputs)
+    }
+}
+
+#[test]
+fn channel_count_tracker_reports_changes_in_channel_count() {
+    let mut tracker = ChannelCountTracker::new();
+    assert_eq!(tracker.number_of_input_channels(), None);
+    assert_eq!(tracker.number_of_output_channels(), None);
+
+    let stereo = audio_chunk![[1, 2], [3, 4]];
+    let mono = audio_chunk![[1, 2]];
+    let mut output = audio_chunk![[0, 0], [0, 0]];
+    let stereo = stereo.as_slices();
+    let mono = mono.as_slices();
+    let mut output = output.as_mut_slices();
+
+    assert!(tracker.observe(&AudioBufferInOut::new(&stereo, &mut output, 2)));
+    assert_eq!(tracker.number_of_input_channels(), Some(2));
+    assert_eq!(tracker.number_of_output_channels(), Some(2));
+    assert!(!tracker.observe(&AudioBufferInOut::new(&stereo, &mut output, 2)));
+    assert!(tracker.observe(&AudioBufferInOut::new(&mono, &mut output, 2)));
+    assert_eq!(tracker.number_of_input_channels(), Some(1));
+    assert_eq!(tracker.number_of_output_channels(), Some(2));
+}
+
+/// Memory for the channels of an [`AudioBufferInOut`], so that backends can create an
+/// [`AudioBufferInOut`] from the input and output buffers they receive without allocating memory.
+///
+/// # Example
+/// ```
+/// use rsynth::audio_chunk;
+/// use rsynth::buffer::{AudioBufferInOut, BufferStorage};
+///
+/// let mut storage = BufferStorage::new(2, 2);
+/// let input = audio_chunk![[1, 2], [3, 4]];
+/// let mut output = audio_chunk![[0, 0], [0, 0]];
+/// storage.with_buffers(
+///     input.as_slices(),
+///     output.as_mut_slices(),
+///     2,
+///     |buffer: &mut AudioBufferInOut<i32>| {
+///         // Render the buffer.
+///     },
+/// );
+/// ```
+///
+/// [`AudioBufferInOut`]: ./struct.AudioBufferInOut.html
+pub struct BufferStorage<S>
+where
+    S: 'static,
+{
+    inputs: VecStorage<&'static [S]>,
+    outputs: VecStorage<&'static [S]>,
+}
+
+impl<S> BufferStorage<S>
+where
+    S: 'static + Copy,
+{
+    /// Create a new `BufferStorage` with room for the given maximum number of input and
+    /// output channels.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn new(max_number_of_inputs: usize, max_number_of_outputs: usize) -> Self {
+        Self {
+            inputs: VecStorage::with_capacity(max_number_of_inputs),
+            outputs: VecStorage::with_capacity(max_number_of_outputs),
+        }
+    }
+
+    /// Create an [`AudioBufferInOut`] with the given `inputs` and `outputs` and call `f` with it.
+    ///
+    /// Inputs and outputs beyond the maximum number of inputs and outputs that was passed to
+    /// [`new`] are ignored, so that no memory is allocated.
+    ///
+    /// # Panics
+    /// Panics if not all inputs and outputs have length `number_of_frames`.
+    ///
+    /// [`AudioBufferInOut`]: ./struct.AudioBufferInOut.html
+    /// [`new`]: #method.new
+    pub fn with_buffers<'i, 'o, I, O, F, R>(
+        &mut self,
+        inputs: I,
+        outputs: O,
+        number_of_frames: usize,
+        f: F,
+    ) -> R
+    where
+        I: IntoIterator<Item = &'i [S]>,
+        O: IntoIterator<Item = &'o mut [S]>,
+        F: FnOnce(&mut AudioBufferInOut<S>) -> R,
+    {
+        let mut input_guard = self.inputs.vec_guard();
+        for input in inputs.into_iter().take(input_guard.capacity()) {
+            input_guard.push(input);
+        }
+        let mut output_guard = self.outputs.vec_guard();
+        for output in outputs.into_iter().take(output_guard.capacity()) {
+            output_guard.push(output);
+        }
+        let mut buffer = AudioBufferInOut::new(
+            input_guard.as_slice(),
+            output_guard.as_mut_slice(),
+            number_of_frames,
+        );
+        f(&mut buffer)
+    }
+}
+
+#[test]
+fn buffer_storage_ignores_channels_beyond_the_capacity() {
+    let mut storage = BufferStorage::new(1, 2);
+    let input = audio_chunk![[1, 2], [3, 4]];
+    let mut output = audio_chunk![[0, 0], [0, 0], [0, 0]];
+    let number_of_channels =
+        storage.with_buffers(input.as_slices(), output.as_mut_slices(), 2, |buffer| {
+            for channel in buffer.outputs().channel_iter_mut() {
+                channel.copy_from_slice(&[5, 6]);
+            }
+            (
+                buffer.number_of_input_channels(),
+                buffer.number_of_output_channels(),
+            )
+        });
+    assert_eq!(number_of_channels, (1, 2));
+    assert_eq!(output, audio_chunk![[5, 6], [5, 6], [0, 0]]);
+}
+
 // Alternative name: "packet"?
 /// A buffer representing a fixed amount of samples for a fixed amount of audio channels.
 ///
@@ -1390,15 +1828,21 @@ impl<S> AudioChunk<S> {
         Self { channels }
     }
 
+    /// Create a new `AudioChunk` from an iterator that yields the samples of the frames
+    /// one after the other (the first sample of every channel, then the second sample of
+    /// every channel, etc.).
+    ///
     /// # Panics
     /// Panics if `number_of_channels == 0`.
     /// Panics if the number of elements yielded by the iterator is not an
-    /// integer multiple of `number_of_channels`.
+    /// integer multiple of `number_of_channels`: an incomplete last frame is not silently
+    /// dropped.
     pub fn from_interlaced_iterator<I>(iterator: I, number_of_channels: usize) -> Self
     where
         I: Iterator<Item = S>,
         S: Copy,
     {
+        assert!(number_of_channels > 0);
         let mut result = Self::new(number_of_channels);
         let mut index = 0;
         for s in iterator {
@@ -1452,6 +1896,27 @@ impl<S> AudioChunk<S> {
         &self.channels
     }
 
+    /// Reserve capacity for at least `additional_frames` more frames in each channel,
+    /// so that appending these frames (e.g. by calling `append_sliced_chunk`) does not
+    /// allocate memory.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn reserve(&mut self, additional_frames: usize) {
+        for channel in self.channels.iter_mut() {
+            channel.reserve(additional_frames);
+        }
+    }
+
+    /// The number of frames the `AudioChunk` can hold without allocating memory.
+    pub fn capacity(&self) -> usize {
+        self.channels
+            .iter()
+            .map(|channel| channel.capacity())
+            .min()
+            .unwrap_or(0)
+    }
+
     /// Return the number of channels.
     pub fn number_of_channels(&self) -> usize {
         self.channels().len()
@@ -1474,6 +1939,65 @@ impl<S> AudioChunk<S> {
         }
     }
 
+    /// Add a channel with the given samples after the existing channels.
+    /// When the chunk has no channels yet, a channel of any length can be added.
+    ///
+    /// # Panics
+    /// Panics if the number of samples is not equal to the number of frames in the existing
+    /// channels.
+    ///
+    /// # Note about using in a real-time context
+    /// This method will allocate memory if the capacity for the channels is exceeded and
+    /// cannot be used in a real-time context in that case.
+    pub fn push_channel(&mut self, samples: Vec<S>) {
+        if let Some(first_channel) = self.channels.first() {
+            assert_eq!(
+                first_channel.len(),
+                samples.len(),
+                "The new channel must have the same length as the existing channels."
+            );
+        }
+        self.channels.push(samples);
+    }
+
+    /// Create a new `AudioChunk` with the channels of all the given chunks, in order.
+    /// This can be used e.g. to combine mono chunks that are rendered separately
+    /// into one multi-channel chunk.
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use]
+    /// extern crate rsynth;
+    /// # use rsynth::buffer::AudioChunk;
+    /// # fn main() {
+    /// let left = audio_chunk![[1, 2, 3]];
+    /// let right = audio_chunk![[4, 5, 6]];
+    /// let stereo = AudioChunk::merge_channels(&[left, right]);
+    /// assert_eq!(stereo, audio_chunk![[1, 2, 3], [4, 5, 6]]);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `chunks` is empty or if not all chunks have the same number of frames.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn merge_channels(chunks: &[AudioChunk<S>]) -> AudioChunk<S>
+    where
+        S: Clone,
+    {
+        assert!(!chunks.is_empty());
+        let mut result = AudioChunk {
+            channels: Vec::with_capacity(chunks.iter().map(|c| c.number_of_channels()).sum()),
+        };
+        for chunk in chunks {
+            for channel in chunk.channels() {
+                result.push_channel(channel.clone());
+            }
+        }
+        result
+    }
+
     pub fn inner(self) -> Vec<Vec<S>> {
         self.channels
     }
@@ -1531,6 +2055,181 @@ impl<S> AudioChunk<S> {
         }
         result.drain(..).map(AudioChunk::from_channels).collect()
     }
+
+    /// Create a new `AudioChunk` with the frames of all the given chunks, one after the other.
+    /// This is the inverse of [`split`].
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use]
+    /// extern crate rsynth;
+    /// # use rsynth::buffer::AudioChunk;
+    /// # fn main() {
+    /// let first = audio_chunk![[1, 2], [5, 6]];
+    /// let second = audio_chunk![[3], [7]];
+    /// let joined = AudioChunk::concat(&[first, second]);
+    /// assert_eq!(joined, audio_chunk![[1, 2, 3], [5, 6, 7]]);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if one of the following happens:
+    /// * `chunks` is empty,
+    /// * the chunks have no channels,
+    /// * not all chunks have the same number of channels.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    ///
+    /// [`split`]: #method.split
+    pub fn concat(chunks: &[AudioChunk<S>]) -> AudioChunk<S>
+    where
+        S: Clone,
+    {
+        assert!(!chunks.is_empty());
+        let number_of_channels = chunks[0].number_of_channels();
+        assert!(number_of_channels > 0);
+        let number_of_frames = chunks.iter().map(|c| c.channels[0].len()).sum();
+        let mut result = Self::with_capacity(number_of_channels, number_of_frames);
+        for chunk in chunks {
+            assert_eq!(
+                chunk.number_of_channels(),
+                number_of_channels,
+                "All chunks must have the same number of channels."
+            );
+            result.append_sliced_chunk(&chunk.as_slices());
+        }
+        result
+    }
+
+    /// Create a new `AudioChunk` with the frames in the given range.
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use]
+    /// extern crate rsynth;
+    /// # fn main() {
+    /// let chunk = audio_chunk![[1, 2, 3, 4], [5, 6, 7, 8]];
+    /// assert_eq!(chunk.slice_frames(1..3), audio_chunk![[2, 3], [6, 7]]);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn slice_frames(&self, range: Range<usize>) -> AudioChunk<S>
+    where
+        S: Clone,
+    {
+        Self {
+            channels: self
+                .channels
+                .iter()
+                .map(|channel| channel[range.clone()].to_vec())
+                .collect(),
+        }
+    }
+
+    /// The number of samples of which the absolute value is strictly larger than `threshold`.
+    ///
+    /// This can be used in tests to check that rendering did not clip.
+    pub fn count_clipped(&self, threshold: f32) -> usize
+    where
+        S: AsPrimitive<f32>,
+    {
+        self.channels
+            .iter()
+            .flat_map(|channel| channel.iter())
+            .filter(|sample| sample.as_().abs() > threshold)
+            .count()
+    }
+
+    /// The location `(channel_index, frame_index)` of the first sample of which the absolute
+    /// value is strictly larger than `threshold`, or `None` if there is no such sample.
+    ///
+    /// "First" refers to the frame index; when more samples clip in the same frame,
+    /// the one in the channel with the lowest index is returned.
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use]
+    /// extern crate rsynth;
+    /// # fn main() {
+    /// let chunk = audio_chunk![[0.0, 0.5, 0.0], [0.0, 0.0, 1.5]];
+    /// assert_eq!(chunk.first_clip_frame(1.0), Some((1, 2)));
+    /// # }
+    /// ```
+    pub fn first_clip_frame(&self, threshold: f32) -> Option<(usize, usize)>
+    where
+        S: AsPrimitive<f32>,
+    {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter_map(|(channel_index, channel)| {
+                channel
+                    .iter()
+                    .position(|sample| sample.as_().abs() > threshold)
+                    .map(|frame_index| (channel_index, frame_index))
+            })
+            .min_by_key(|(channel_index, frame_index)| (*frame_index, *channel_index))
+    }
+
+    /// Create a new `AudioChunk` with the audio of this chunk, resampled from `from_hz` to `to_hz`.
+    ///
+    /// The resulting chunk has `number_of_frames * to_hz / from_hz` frames (rounded down).
+    /// Every channel is resampled separately with linear interpolation between
+    /// neighbouring samples; the last sample is repeated when interpolating beyond the end.
+    ///
+    /// _Note_: this is a naive resampler that is intended for testing (e.g. to mix audio
+    /// with different sample rates in offline tests). It does no filtering at all, so
+    /// it introduces aliasing and is not suitable for production quality audio.
+    ///
+    /// # Example
+    /// ```
+    /// #[macro_use]
+    /// extern crate rsynth;
+    /// # fn main() {
+    /// let chunk = audio_chunk![[0.0, 1.0, 2.0]];
+    /// let resampled = chunk.resample_linear(22050, 44100);
+    /// assert_eq!(resampled, audio_chunk![[0.0, 0.5, 1.0, 1.5, 2.0, 2.0]]);
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `from_hz == 0` or `to_hz == 0`.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn resample_linear(&self, from_hz: u64, to_hz: u64) -> AudioChunk<S>
+    where
+        S: Float,
+    {
+        assert!(from_hz > 0);
+        assert!(to_hz > 0);
+        let channels = self
+            .channels
+            .iter()
+            .map(|channel| {
+                let number_of_frames =
+                    (channel.len() as u128 * to_hz as u128 / from_hz as u128) as usize;
+                (0..number_of_frames)
+                    .map(|frame| {
+                        let position = frame as f64 * from_hz as f64 / to_hz as f64;
+                        let index = position as usize;
+                        let fraction = S::from(position - index as f64)
+                            .expect("A fraction between 0 and 1 can be represented.");
+                        let current = channel[index];
+                        let next = channel.get(index + 1).cloned().unwrap_or(current);
+                        current + (next - current) * fraction
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { channels }
+    }
 }
 
 #[macro_export]
@@ -1617,6 +2316,110 @@ fn split_works_with_non_dividing_input_length() {
     )
 }
 
+#[test]
+fn push_channel_adds_a_channel() {
+    let mut chunk = audio_chunk![[1, 2, 3]];
+    chunk.push_channel(vec![4, 5, 6]);
+    assert_eq!(chunk, audio_chunk![[1, 2, 3], [4, 5, 6]]);
+}
+
+#[test]
+fn push_channel_accepts_any_length_when_there_are_no_channels() {
+    let mut chunk = AudioChunk::<i32>::zero(0, 4);
+    chunk.push_channel(vec![1, 2]);
+    assert_eq!(chunk, audio_chunk![[1, 2]]);
+}
+
+#[test]
+#[should_panic]
+fn push_channel_rejects_a_channel_with_a_different_length() {
+    let mut chunk = audio_chunk![[1, 2, 3]];
+    chunk.push_channel(vec![4, 5]);
+}
+
+#[test]
+fn merge_channels_merges_two_mono_chunks_into_a_stereo_chunk() {
+    let left = audio_chunk![[1, 2, 3]];
+    let right = audio_chunk![[4, 5, 6]];
+    let stereo = AudioChunk::merge_channels(&[left, right]);
+    assert_eq!(stereo.number_of_channels(), 2);
+    assert_eq!(stereo, audio_chunk![[1, 2, 3], [4, 5, 6]]);
+}
+
+#[test]
+#[should_panic]
+fn merge_channels_rejects_chunks_with_different_lengths() {
+    let left = audio_chunk![[1, 2, 3]];
+    let right = audio_chunk![[4, 5]];
+    AudioChunk::merge_channels(&[left, right]);
+}
+
+#[test]
+fn concat_is_the_inverse_of_split() {
+    let input = audio_chunk![[0, 1, 2, 3, 4], [5, 6, 7, 8, 9]];
+    let observed = AudioChunk::concat(&input.clone().split(2));
+    assert_eq!(observed, input);
+}
+
+#[test]
+#[should_panic]
+fn concat_rejects_chunks_with_different_numbers_of_channels() {
+    AudioChunk::concat(&[audio_chunk![[1, 2]], audio_chunk![[3], [4]]]);
+}
+
+#[test]
+#[should_panic]
+fn concat_rejects_an_empty_slice_of_chunks() {
+    AudioChunk::<i32>::concat(&[]);
+}
+
+#[test]
+#[should_panic]
+fn concat_rejects_chunks_without_channels() {
+    AudioChunk::<i32>::concat(&[AudioChunk::new(0), AudioChunk::new(0)]);
+}
+
+#[test]
+fn slice_frames_returns_the_frames_in_the_range() {
+    let input = audio_chunk![[0, 1, 2, 3, 4], [5, 6, 7, 8, 9]];
+    assert_eq!(input.slice_frames(1..4), audio_chunk![[1, 2, 3], [6, 7, 8]]);
+    assert_eq!(input.slice_frames(0..5), input);
+}
+
+#[test]
+fn count_clipped_counts_the_samples_above_the_threshold() {
+    let chunk = audio_chunk![[0.0, 0.5, 1.0], [-0.5, 1.5, 0.0]];
+    assert_eq!(chunk.count_clipped(1.0), 1);
+    assert_eq!(chunk.count_clipped(0.4), 4);
+}
+
+#[test]
+fn first_clip_frame_finds_the_clipping_sample() {
+    let chunk = audio_chunk![[0.0, 0.5, 1.0], [-0.5, 1.5, 0.0]];
+    assert_eq!(chunk.first_clip_frame(1.0), Some((1, 1)));
+    assert_eq!(chunk.first_clip_frame(2.0), None);
+}
+
+#[test]
+fn resample_linear_interpolates_when_upsampling() {
+    let input = audio_chunk![[0.0, 1.0, 2.0], [2.0, 0.0, -2.0]];
+    let observed = input.resample_linear(1, 2);
+    assert_eq!(
+        observed,
+        audio_chunk![
+            [0.0, 0.5, 1.0, 1.5, 2.0, 2.0],
+            [2.0, 1.0, 0.0, -1.0, -2.0, -2.0]
+        ]
+    );
+}
+
+#[test]
+fn resample_linear_skips_samples_when_downsampling() {
+    let input = audio_chunk![[0.0, 1.0, 2.0, 3.0, 4.0]];
+    let observed = input.resample_linear(44100, 22050);
+    assert_eq!(observed, audio_chunk![[0.0, 2.0]]);
+}
+
 #[test]
 fn from_interlaced_iterator_works() {
     let input = vec![1, 2, 3, 4, 5, 6];
@@ -1624,6 +2427,22 @@ fn from_interlaced_iterator_works() {
     assert_eq!(chunk.channels, vec![vec![1, 3, 5], vec![2, 4, 6]]);
 }
 
+#[test]
+#[should_panic(
+    expected = "Number of elements must be an integer multiple of the number of channels."
+)]
+fn from_interlaced_iterator_rejects_an_incomplete_last_frame() {
+    let input = vec![1, 2, 3, 4, 5];
+    AudioChunk::from_interlaced_iterator(input.iter().cloned(), 2);
+}
+
+#[test]
+#[should_panic]
+fn from_interlaced_iterator_rejects_zero_channels() {
+    let input: Vec<i32> = Vec::new();
+    AudioChunk::from_interlaced_iterator(input.iter().cloned(), 0);
+}
+
 pub fn buffers_as_slice<'a, S>(buffers: &'a [Vec<S>], slice_len: usize) -> Vec<&'a [S]> {
     buffers.iter().map(|b| &b[0..slice_len]).collect()
 }
@@ -0,0 +1,281 @@
+//! Biquad filters (second order IIR filters).
+//!
+//! The coefficients are computed with the formulas from the
+//! [Audio EQ Cookbook](https://www.w3.org/TR/audio-eq-cookbook/) by Robert Bristow-Johnson.
+//!
+//! # Example
+//! ```
+//! use rsynth::dsp::biquad::{Biquad, BiquadCoefficients};
+//! use std::f64::consts::FRAC_1_SQRT_2;
+//!
+//! // Low-pass filter with a cutoff frequency of 1000 Hz.
+//! let coefficients = BiquadCoefficients::low_pass(1000.0, FRAC_1_SQRT_2, 44100.0);
+//! let mut filter: Biquad<f32> = Biquad::new(coefficients);
+//! let filtered = filter.process(1.0);
+//! ```
+use crate::buffer::AudioBufferOut;
+use num_traits::Float;
+use std::f64::consts::PI;
+
+/// The coefficients of a [`Biquad`] filter, normalized so that `a0` is `1`.
+///
+/// [`Biquad`]: ./struct.Biquad.html
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoefficients {
+    /// Coefficient of the current input sample.
+    pub b0: f64,
+    /// Coefficient of the previous input sample.
+    pub b1: f64,
+    /// Coefficient of the input sample before the previous one.
+    pub b2: f64,
+    /// Coefficient of the previous output sample.
+    pub a1: f64,
+    /// Coefficient of the output sample before the previous one.
+    pub a2: f64,
+}
+
+impl BiquadCoefficients {
+    // Normalize the coefficients so that `a0` is `1`.
+    fn normalized(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+        }
+    }
+
+    // The angular frequency (in radians per sample) and `alpha` from the cookbook.
+    fn omega_and_alpha(frequency: f64, q: f64, sample_rate: f64) -> (f64, f64) {
+        let omega = 2.0 * PI * frequency / sample_rate;
+        (omega, omega.sin() / (2.0 * q))
+    }
+
+    /// The coefficients of a filter that lets all frequencies pass unchanged.
+    pub fn identity() -> Self {
+        Self {
+            b0: 1.0,
+            b1: 0.0,
+            b2: 0.0,
+            a1: 0.0,
+            a2: 0.0,
+        }
+    }
+
+    /// The coefficients of a low-pass filter with the given cutoff frequency (in Hz) and quality
+    /// factor `q`.
+    ///
+    /// A `q` of `1/sqrt(2)` gives a Butterworth filter (no resonance).
+    pub fn low_pass(cutoff_frequency: f64, q: f64, sample_rate: f64) -> Self {
+        let (omega, alpha) = Self::omega_and_alpha(cutoff_frequency, q, sample_rate);
+        let cos = omega.cos();
+        Self::normalized(
+            (1.0 - cos) / 2.0,
+            1.0 - cos,
+            (1.0 - cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// The coefficients of a high-pass filter with the given cutoff frequency (in Hz) and quality
+    /// factor `q`.
+    pub fn high_pass(cutoff_frequency: f64, q: f64, sample_rate: f64) -> Self {
+        let (omega, alpha) = Self::omega_and_alpha(cutoff_frequency, q, sample_rate);
+        let cos = omega.cos();
+        Self::normalized(
+            (1.0 + cos) / 2.0,
+            -(1.0 + cos),
+            (1.0 + cos) / 2.0,
+            1.0 + alpha,
+            -2.0 * cos,
+            1.0 - alpha,
+        )
+    }
+
+    /// The coefficients of a band-pass filter with the given center frequency (in Hz) and
+    /// quality factor `q`, with a gain of `1` (0 dB) at the center frequency.
+    pub fn band_pass(center_frequency: f64, q: f64, sample_rate: f64) -> Self {
+        let (omega, alpha) = Self::omega_and_alpha(center_frequency, q, sample_rate);
+        let cos = omega.cos();
+        Self::normalized(alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos, 1.0 - alpha)
+    }
+
+    /// The coefficients of a peaking equalizer with the given center frequency (in Hz),
+    /// quality factor `q` and gain (in dB) at the center frequency.
+    pub fn peaking(center_frequency: f64, q: f64, gain_in_db: f64, sample_rate: f64) -> Self {
+        let (omega, alpha) = Self::omega_and_alpha(center_frequency, q, sample_rate);
+        let cos = omega.cos();
+        let amplitude = 10.0_f64.powf(gain_in_db / 40.0);
+        Self::normalized(
+            1.0 + alpha * amplitude,
+            -2.0 * cos,
+            1.0 - alpha * amplitude,
+            1.0 + alpha / amplitude,
+            -2.0 * cos,
+            1.0 - alpha / amplitude,
+        )
+    }
+}
+
+impl Default for BiquadCoefficients {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// A biquad filter for one channel, implemented in the transposed direct form II.
+///
+/// To filter more than one channel, use one `Biquad` per channel, e.g. with [`process_buffer`].
+///
+/// [`process_buffer`]: ./fn.process_buffer.html
+#[derive(Clone, Copy, Debug)]
+pub struct Biquad<S> {
+    b0: S,
+    b1: S,
+    b2: S,
+    a1: S,
+    a2: S,
+    z1: S,
+    z2: S,
+}
+
+impl<S> Biquad<S>
+where
+    S: Float,
+{
+    /// Create a new `Biquad` with the given coefficients.
+    pub fn new(coefficients: BiquadCoefficients) -> Self {
+        let mut biquad = Self {
+            b0: S::zero(),
+            b1: S::zero(),
+            b2: S::zero(),
+            a1: S::zero(),
+            a2: S::zero(),
+            z1: S::zero(),
+            z2: S::zero(),
+        };
+        biquad.set_coefficients(coefficients);
+        biquad
+    }
+
+    /// Change the coefficients, e.g. when the cutoff frequency changes.
+    ///
+    /// The state of the filter is kept, so that the output stays continuous.
+    pub fn set_coefficients(&mut self, coefficients: BiquadCoefficients) {
+        self.b0 = S::from(coefficients.b0).unwrap();
+        self.b1 = S::from(coefficients.b1).unwrap();
+        self.b2 = S::from(coefficients.b2).unwrap();
+        self.a1 = S::from(coefficients.a1).unwrap();
+        self.a2 = S::from(coefficients.a2).unwrap();
+    }
+
+    /// Clear the state of the filter, e.g. when a voice starts a new note.
+    pub fn reset(&mut self) {
+        self.z1 = S::zero();
+        self.z2 = S::zero();
+    }
+
+    /// Filter one sample.
+    pub fn process(&mut self, sample: S) -> S {
+        let output = self.b0 * sample + self.z1;
+        self.z1 = self.b1 * sample - self.a1 * output + self.z2;
+        self.z2 = self.b2 * sample - self.a2 * output;
+        output
+    }
+
+    /// Filter the samples of one channel in place.
+    pub fn process_channel(&mut self, channel: &mut [S]) {
+        for sample in channel.iter_mut() {
+            *sample = self.process(*sample);
+        }
+    }
+}
+
+/// Filter every channel of the buffer in place, with one filter per channel.
+///
+/// # Panics
+/// Panics if the number of filters differs from the number of channels of the buffer.
+///
+/// # Example
+/// ```
+/// use rsynth::buffer::AudioBufferOut;
+/// use rsynth::dsp::biquad::{process_buffer, Biquad, BiquadCoefficients};
+///
+/// struct MyPlugin {
+///     // One filter for the left and one for the right channel.
+///     filters: [Biquad<f32>; 2],
+/// }
+///
+/// impl MyPlugin {
+///     fn filter_output(&mut self, outputs: &mut AudioBufferOut<f32>) {
+///         process_buffer(&mut self.filters, outputs);
+///     }
+/// }
+/// ```
+pub fn process_buffer<S>(filters: &mut [Biquad<S>], buffer: &mut AudioBufferOut<S>)
+where
+    S: Float + 'static,
+{
+    assert_eq!(filters.len(), buffer.number_of_channels());
+    for (filter, channel) in filters.iter_mut().zip(buffer.channel_iter_mut()) {
+        filter.process_channel(channel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{process_buffer, Biquad, BiquadCoefficients};
+    use crate::buffer::AudioBufferOut;
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    const SAMPLE_RATE: f64 = 44100.0;
+
+    // The gain of the filter for a constant signal, after the filter has settled.
+    fn dc_gain(coefficients: BiquadCoefficients) -> f64 {
+        let mut filter: Biquad<f64> = Biquad::new(coefficients);
+        let mut output = 0.0;
+        for _ in 0..10000 {
+            output = filter.process(1.0);
+        }
+        output
+    }
+
+    #[test]
+    fn low_pass_with_cutoff_at_nyquist_has_unity_dc_gain() {
+        let coefficients =
+            BiquadCoefficients::low_pass(SAMPLE_RATE / 2.0, FRAC_1_SQRT_2, SAMPLE_RATE);
+        assert!((dc_gain(coefficients) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn low_pass_passes_and_high_pass_blocks_dc() {
+        let low_pass = BiquadCoefficients::low_pass(1000.0, FRAC_1_SQRT_2, SAMPLE_RATE);
+        assert!((dc_gain(low_pass) - 1.0).abs() < 1e-6);
+        let high_pass = BiquadCoefficients::high_pass(1000.0, FRAC_1_SQRT_2, SAMPLE_RATE);
+        assert!(dc_gain(high_pass).abs() < 1e-6);
+        let band_pass = BiquadCoefficients::band_pass(1000.0, 1.0, SAMPLE_RATE);
+        assert!(dc_gain(band_pass).abs() < 1e-6);
+        let peaking = BiquadCoefficients::peaking(1000.0, 1.0, 6.0, SAMPLE_RATE);
+        assert!((dc_gain(peaking) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn process_buffer_uses_one_filter_per_channel() {
+        let mut filters = [
+            Biquad::new(BiquadCoefficients::identity()),
+            Biquad::new(BiquadCoefficients {
+                b0: 0.5,
+                ..BiquadCoefficients::identity()
+            }),
+        ];
+        let mut left = [1.0f32, 2.0];
+        let mut right = [1.0f32, 2.0];
+        let mut channels = [&mut left[..], &mut right[..]];
+        process_buffer(&mut filters, &mut AudioBufferOut::new(&mut channels, 2));
+        assert_eq!(left, [1.0, 2.0]);
+        assert_eq!(right, [0.5, 1.0]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Digital signal processing building blocks.
+pub mod biquad;
+pub mod pan;
+mod smoothed_value;
+
+pub use self::smoothed_value::SmoothedValue;
@@ -0,0 +1,90 @@
+//! Constant-power (equal-power) stereo panning.
+use crate::buffer::AudioBufferOut;
+use num_traits::Float;
+
+/// Compute the gains of the left and the right channel for the given pan, so that the total
+/// power stays the same for every pan.
+///
+/// A pan of `-1` is fully left, `0` is the center and `1` is fully right.
+/// In the center, both gains are `1/sqrt(2)`.
+pub fn constant_power_pan<S>(pan: S) -> (S, S)
+where
+    S: Float,
+{
+    let two = S::one() + S::one();
+    let quarter_turn = S::from(std::f64::consts::FRAC_PI_2).unwrap();
+    // From the range `[-1, 1]` to the range `[0, pi/2]`.
+    let angle = (pan + S::one()) / two * quarter_turn;
+    (angle.cos(), angle.sin())
+}
+
+/// Pan a stereo buffer: multiply the left (first) channel and the right (second) channel with
+/// the gains computed by [`constant_power_pan`].
+///
+/// # Panics
+/// Panics if the buffer does not have exactly two channels.
+///
+/// # Example
+/// ```
+/// use rsynth::buffer::AudioBufferOut;
+/// use rsynth::dsp::pan::pan_stereo;
+///
+/// let mut left = [1.0, 1.0];
+/// let mut right = [1.0, 1.0];
+/// let mut channels = [&mut left[..], &mut right[..]];
+/// // Fully left.
+/// pan_stereo(&mut AudioBufferOut::new(&mut channels, 2), -1.0);
+/// assert_eq!(right, [0.0, 0.0]);
+/// ```
+///
+/// [`constant_power_pan`]: ./fn.constant_power_pan.html
+pub fn pan_stereo<S>(buffer: &mut AudioBufferOut<S>, pan: S)
+where
+    S: Float + 'static,
+{
+    assert_eq!(
+        buffer.number_of_channels(),
+        2,
+        "Only a buffer with two channels can be panned."
+    );
+    let (left, right) = constant_power_pan(pan);
+    buffer.apply_gain_per_channel(&[left, right]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{constant_power_pan, pan_stereo};
+    use crate::buffer::AudioBufferOut;
+
+    #[test]
+    fn constant_power_pan_keeps_the_power_constant() {
+        for &pan in [-1.0, -0.5, 0.0, 0.3, 1.0].iter() {
+            let (left, right): (f64, f64) = constant_power_pan(pan);
+            assert!((left * left + right * right - 1.0).abs() < 1e-12);
+        }
+        let (left, right) = constant_power_pan(0.0f64);
+        assert!((left - right).abs() < 1e-12);
+        let (left, right) = constant_power_pan(1.0f64);
+        assert!(left.abs() < 1e-12);
+        assert!((right - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn pan_stereo_applies_the_gains_to_the_left_and_right_channel() {
+        let mut left = [2.0f64, 4.0];
+        let mut right = [2.0f64, 4.0];
+        let mut channels = [&mut left[..], &mut right[..]];
+        pan_stereo(&mut AudioBufferOut::new(&mut channels, 2), 0.0);
+        let center_gain = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((left[1] - 4.0 * center_gain).abs() < 1e-12);
+        assert!((right[0] - 2.0 * center_gain).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only a buffer with two channels can be panned.")]
+    fn pan_stereo_panics_when_the_buffer_is_not_stereo() {
+        let mut mono = [1.0f32, 1.0];
+        let mut channels = [&mut mono[..]];
+        pan_stereo(&mut AudioBufferOut::new(&mut channels, 2), 0.0);
+    }
+}
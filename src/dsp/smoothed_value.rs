@@ -0,0 +1,135 @@
+//! Smooth parameter changes to avoid zipper noise.
+use crate::AudioHandler;
+use num_traits::Float;
+
+// The sample rate that is used until `set_sample_rate` is called.
+const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
+// The fraction of a change that remains after the smoothing time.
+const REMAINING_FRACTION_AFTER_SMOOTHING_TIME: f64 = 0.001;
+
+/// A value that follows its target with a one-pole low-pass filter, e.g. to change the gain
+/// or the pan without zipper noise.
+///
+/// Every call to `next()` moves the current value one frame closer to the target.
+/// After the smoothing time, less than 0.1% of a change of the target remains.
+/// `SmoothedValue` is an iterator that never ends.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::dsp::SmoothedValue;
+/// use rsynth::AudioHandler;
+///
+/// // Smooth changes in 20 ms.
+/// let mut gain = SmoothedValue::new(1.0, 0.02);
+/// gain.set_sample_rate(48000.0);
+/// gain.set_target(0.5);
+/// let mut samples = vec![1.0; 64];
+/// for (sample, gain) in samples.iter_mut().zip(&mut gain) {
+///     *sample *= gain;
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct SmoothedValue<S> {
+    current: S,
+    target: S,
+    smoothing_time_in_seconds: f64,
+    // The fraction of the remaining distance to the target that is covered every frame.
+    coefficient: S,
+}
+
+impl<S> SmoothedValue<S>
+where
+    S: Float,
+{
+    /// Create a new `SmoothedValue` that starts at `initial_value` (without smoothing)
+    /// and that smoothes changes in the given time (in seconds).
+    ///
+    /// # Panics
+    /// Panics if `smoothing_time_in_seconds` is negative.
+    pub fn new(initial_value: S, smoothing_time_in_seconds: f64) -> Self {
+        assert!(smoothing_time_in_seconds >= 0.0);
+        let mut result = Self {
+            current: initial_value,
+            target: initial_value,
+            smoothing_time_in_seconds,
+            coefficient: S::one(),
+        };
+        result.set_sample_rate(DEFAULT_SAMPLE_RATE);
+        result
+    }
+
+    /// Set the value to move to.
+    pub fn set_target(&mut self, target: S) {
+        self.target = target;
+    }
+
+    /// Set the current value and the target, without smoothing.
+    pub fn set_immediately(&mut self, value: S) {
+        self.current = value;
+        self.target = value;
+    }
+
+    /// The value to move to.
+    pub fn target(&self) -> S {
+        self.target
+    }
+
+    /// The current value.
+    pub fn current(&self) -> S {
+        self.current
+    }
+}
+
+impl<S> AudioHandler for SmoothedValue<S>
+where
+    S: Float,
+{
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        let number_of_frames = self.smoothing_time_in_seconds * sample_rate;
+        let coefficient = if number_of_frames >= 1.0 {
+            1.0 - REMAINING_FRACTION_AFTER_SMOOTHING_TIME.powf(1.0 / number_of_frames)
+        } else {
+            1.0
+        };
+        self.coefficient = S::from(coefficient).unwrap();
+    }
+}
+
+impl<S> Iterator for SmoothedValue<S>
+where
+    S: Float,
+{
+    type Item = S;
+
+    fn next(&mut self) -> Option<S> {
+        self.current = self.current + (self.target - self.current) * self.coefficient;
+        Some(self.current)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmoothedValue;
+    use crate::AudioHandler;
+
+    #[test]
+    fn reaches_the_target_within_the_smoothing_time() {
+        let mut value = SmoothedValue::new(0.0f64, 0.01);
+        value.set_sample_rate(1000.0);
+        value.set_target(1.0);
+        let values: Vec<f64> = value.by_ref().take(10).collect();
+        assert!(values.windows(2).all(|w| w[0] < w[1]));
+        assert!(values[0] > 0.0);
+        assert!(values[8] < 0.999);
+        assert!(1.0 - values[9] <= 0.001 + 1e-9);
+        assert_eq!(value.target(), 1.0);
+    }
+
+    #[test]
+    fn zero_smoothing_time_jumps_to_the_target() {
+        let mut value = SmoothedValue::new(0.0f32, 0.0);
+        value.set_target(0.5);
+        assert_eq!(value.next(), Some(0.5));
+    }
+}
@@ -0,0 +1,365 @@
+//! An ADSR (attack, decay, sustain, release) envelope.
+//!
+//! When a note starts, the envelope rises linearly from its current value to `1` during the
+//! attack time, then falls linearly to the sustain level during the decay time and stays at the
+//! sustain level until the note is released.
+//! When the note is released, the envelope falls linearly from its current value to `0`
+//! during the release time.
+//!
+//! This module is only available when the "event-queue" feature is enabled.
+use super::{Envelope, EnvelopeIteratorItem};
+use crate::event::Timed;
+use crate::AudioHandler;
+use event_queue::{AlwaysInsertNewAfterOld, EventQueue};
+use num_traits::Float;
+
+// The maximum number of events that can be queued before `forget_past` is called.
+const EVENT_QUEUE_CAPACITY: usize = 16;
+// The sample rate that is used until `set_sample_rate` is called.
+const DEFAULT_SAMPLE_RATE: f64 = 44100.0;
+
+/// An event that starts or releases an [`AdsrEnvelope`].
+///
+/// [`AdsrEnvelope`]: ./struct.AdsrEnvelope.html
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AdsrEvent {
+    /// Start the attack, from the current value of the envelope.
+    NoteOn,
+    /// Start the release, from the current value of the envelope.
+    NoteOff,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Stage {
+    Idle,
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct AdsrState<T> {
+    stage: Stage,
+    value: T,
+    // By how much the value decreases every frame during the release.
+    release_step: T,
+}
+
+/// An ADSR envelope with linear segments, see the [module level documentation] for more details.
+///
+/// Use [`insert_event`] to start and release a note and [`iter`] to get the value of the
+/// envelope for every frame, starting from the current position.
+/// Call [`forget_past`] after every buffer, with the number of frames in the buffer.
+///
+/// Example
+/// -------
+/// ```
+/// # #![allow(deprecated)]
+/// use rsynth::envelope::Envelope;
+/// use rsynth::envelope::adsr_envelope::{AdsrEnvelope, AdsrEvent};
+/// use rsynth::event::Timed;
+/// use rsynth::AudioHandler;
+///
+/// // Attack time: 10 ms, decay time: 100 ms, sustain level: 0.5, release time: 200 ms.
+/// let mut envelope = AdsrEnvelope::new(0.01, 0.1, 0.5, 0.2);
+/// envelope.set_sample_rate(48000.0);
+/// envelope.insert_event(Timed::new(0, AdsrEvent::NoteOn));
+/// for frame in envelope.iter().take(64) {
+///     // Multiply the samples of the frame with `frame.item`.
+/// }
+/// envelope.forget_past(64);
+/// ```
+///
+/// [module level documentation]: ./index.html
+/// [`insert_event`]: ../trait.Envelope.html#tymethod.insert_event
+/// [`iter`]: ../trait.Envelope.html#tymethod.iter
+/// [`forget_past`]: ../trait.Envelope.html#tymethod.forget_past
+pub struct AdsrEnvelope<T> {
+    attack_time: T,
+    decay_time: T,
+    sustain_level: T,
+    release_time: T,
+    sample_rate: T,
+    // The state at the current position.
+    state: AdsrState<T>,
+    event_queue: EventQueue<u32, AdsrEvent>,
+}
+
+impl<T> AdsrEnvelope<T>
+where
+    T: Float,
+{
+    /// Create a new `AdsrEnvelope` with the given attack, decay and release times (in seconds)
+    /// and the given sustain level.
+    /// The envelope starts at `0`, waiting for an [`AdsrEvent::NoteOn`].
+    ///
+    /// # Panics
+    /// Panics if one of the times is negative or if `sustain_level` is not in the range `[0, 1]`.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    ///
+    /// [`AdsrEvent::NoteOn`]: ./enum.AdsrEvent.html#variant.NoteOn
+    pub fn new(attack_time: T, decay_time: T, sustain_level: T, release_time: T) -> Self {
+        assert!(attack_time >= T::zero());
+        assert!(decay_time >= T::zero());
+        assert!(release_time >= T::zero());
+        assert!(sustain_level >= T::zero() && sustain_level <= T::one());
+        Self {
+            attack_time,
+            decay_time,
+            sustain_level,
+            release_time,
+            sample_rate: T::from(DEFAULT_SAMPLE_RATE).unwrap(),
+            state: AdsrState {
+                stage: Stage::Idle,
+                value: T::zero(),
+                release_step: T::zero(),
+            },
+            event_queue: EventQueue::new(EVENT_QUEUE_CAPACITY),
+        }
+    }
+
+    // The step that is needed every frame to cover `distance` in `time` seconds.
+    fn step_size(&self, time: T, distance: T) -> T {
+        let number_of_frames = time * self.sample_rate;
+        if number_of_frames >= T::one() {
+            distance / number_of_frames
+        } else {
+            distance
+        }
+    }
+
+    fn handle_event(&self, state: &mut AdsrState<T>, event: AdsrEvent) {
+        match event {
+            AdsrEvent::NoteOn => {
+                state.stage = Stage::Attack;
+            }
+            AdsrEvent::NoteOff => {
+                if state.stage != Stage::Idle {
+                    state.stage = Stage::Release;
+                    state.release_step = self.step_size(self.release_time, state.value);
+                }
+            }
+        }
+    }
+
+    // Advance the state by one frame.
+    fn step(&self, state: &mut AdsrState<T>) {
+        match state.stage {
+            Stage::Idle | Stage::Sustain => {}
+            Stage::Attack => {
+                state.value = state.value + self.step_size(self.attack_time, T::one());
+                if state.value >= T::one() {
+                    state.value = T::one();
+                    state.stage = Stage::Decay;
+                }
+            }
+            Stage::Decay => {
+                state.value =
+                    state.value - self.step_size(self.decay_time, T::one() - self.sustain_level);
+                if state.value <= self.sustain_level {
+                    state.value = self.sustain_level;
+                    state.stage = Stage::Sustain;
+                }
+            }
+            Stage::Release => {
+                state.value = state.value - state.release_step;
+                if state.value <= T::zero() {
+                    state.value = T::zero();
+                    state.stage = Stage::Idle;
+                }
+            }
+        }
+    }
+}
+
+impl<T> AudioHandler for AdsrEnvelope<T>
+where
+    T: Float,
+{
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = T::from(sample_rate).unwrap();
+    }
+}
+
+/// Iterator over the values of an [`AdsrEnvelope`], one value per frame.
+///
+/// `has_updated` is `true` when the value differs from the value of the previous frame.
+///
+/// [`AdsrEnvelope`]: ./struct.AdsrEnvelope.html
+pub struct AdsrEnvelopeIterator<'a, T> {
+    envelope: &'a AdsrEnvelope<T>,
+    state: AdsrState<T>,
+    // The index of the next event in the event queue.
+    index: usize,
+    time_in_frames: u32,
+    previous_value: T,
+}
+
+impl<'a, T> Iterator for AdsrEnvelopeIterator<'a, T>
+where
+    T: Float,
+{
+    type Item = EnvelopeIteratorItem<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((time_in_frames, event)) = self.envelope.event_queue.get(self.index) {
+            if *time_in_frames > self.time_in_frames {
+                break;
+            }
+            self.envelope.handle_event(&mut self.state, *event);
+            self.index += 1;
+        }
+        let value = self.state.value;
+        let has_updated = value != self.previous_value;
+        self.previous_value = value;
+        self.envelope.step(&mut self.state);
+        self.time_in_frames = self.time_in_frames.saturating_add(1);
+        Some(EnvelopeIteratorItem {
+            item: value,
+            has_updated,
+        })
+    }
+}
+
+impl<'a, T> Envelope<'a, T> for AdsrEnvelope<T>
+where
+    T: Float + 'a,
+{
+    type Iter = AdsrEnvelopeIterator<'a, T>;
+    type EventType = Timed<AdsrEvent>;
+
+    fn iter(&'a self) -> Self::Iter {
+        AdsrEnvelopeIterator {
+            envelope: self,
+            state: self.state,
+            index: 0,
+            time_in_frames: 0,
+            previous_value: self.state.value,
+        }
+    }
+
+    fn insert_event(&mut self, new_event: Timed<AdsrEvent>) {
+        if let Some((time, dropped)) = self.event_queue.queue_event(
+            (new_event.time_in_frames, new_event.event),
+            AlwaysInsertNewAfterOld,
+        ) {
+            warn!(
+                "Too many envelope events; dropping event {:?} at time {}.",
+                dropped, time
+            );
+        }
+    }
+
+    fn forget_past(&mut self, number_of_frames_to_forget: u32) {
+        let state = {
+            let mut iterator = self.iter();
+            for _ in 0..number_of_frames_to_forget {
+                iterator.next();
+            }
+            iterator.state
+        };
+        self.state = state;
+        self.event_queue.forget_before(number_of_frames_to_forget);
+        self.event_queue.shift_time(number_of_frames_to_forget);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.state.stage == Stage::Idle && self.event_queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdsrEnvelope, AdsrEvent};
+    use crate::envelope::Envelope;
+    use crate::event::Timed;
+    use crate::AudioHandler;
+
+    // With a sample rate of 100 Hz, every 10 ms is one frame.
+    fn envelope() -> AdsrEnvelope<f64> {
+        let mut envelope = AdsrEnvelope::new(0.04, 0.02, 0.5, 0.04);
+        envelope.set_sample_rate(100.0);
+        envelope
+    }
+
+    fn values(envelope: &AdsrEnvelope<f64>, number_of_frames: usize) -> Vec<f64> {
+        envelope
+            .iter()
+            .take(number_of_frames)
+            .map(|frame| frame.item)
+            .collect()
+    }
+
+    fn assert_approximately_equal(observed: Vec<f64>, expected: Vec<f64>) {
+        assert_eq!(observed.len(), expected.len());
+        for (o, e) in observed.iter().zip(expected.iter()) {
+            assert!(
+                (o - e).abs() < 1e-9,
+                "Expected {:?}, but got {:?}.",
+                expected,
+                observed
+            );
+        }
+    }
+
+    #[test]
+    fn attack_rises_linearly_and_decays_to_the_sustain_level() {
+        let mut envelope = envelope();
+        envelope.insert_event(Timed::new(2, AdsrEvent::NoteOn));
+        assert_approximately_equal(
+            values(&envelope, 10),
+            vec![0.0, 0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 0.75, 0.5, 0.5],
+        );
+    }
+
+    #[test]
+    fn release_falls_linearly_to_zero() {
+        let mut envelope = envelope();
+        envelope.insert_event(Timed::new(0, AdsrEvent::NoteOn));
+        envelope.insert_event(Timed::new(10, AdsrEvent::NoteOff));
+        assert_approximately_equal(
+            values(&envelope, 16)[9..].to_vec(),
+            vec![0.5, 0.5, 0.375, 0.25, 0.125, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn forget_past_continues_where_the_previous_buffer_stopped() {
+        let mut envelope = envelope();
+        envelope.insert_event(Timed::new(0, AdsrEvent::NoteOn));
+        envelope.insert_event(Timed::new(10, AdsrEvent::NoteOff));
+        let all_values = values(&envelope, 16);
+        envelope.forget_past(4);
+        assert_approximately_equal(values(&envelope, 6), all_values[4..10].to_vec());
+        envelope.forget_past(6);
+        assert_approximately_equal(values(&envelope, 6), all_values[10..].to_vec());
+    }
+
+    #[test]
+    fn is_finished_after_the_release() {
+        let mut envelope = envelope();
+        envelope.insert_event(Timed::new(0, AdsrEvent::NoteOn));
+        envelope.insert_event(Timed::new(10, AdsrEvent::NoteOff));
+        envelope.forget_past(8);
+        assert!(!envelope.is_finished());
+        envelope.forget_past(5);
+        assert!(!envelope.is_finished());
+        envelope.forget_past(1);
+        assert!(envelope.is_finished());
+    }
+
+    #[test]
+    fn has_updated_is_only_set_when_the_value_changes() {
+        let mut envelope = envelope();
+        envelope.insert_event(Timed::new(1, AdsrEvent::NoteOn));
+        let has_updated: Vec<bool> = envelope
+            .iter()
+            .take(4)
+            .map(|frame| frame.has_updated)
+            .collect();
+        assert_eq!(has_updated, vec![false, false, true, true]);
+    }
+}
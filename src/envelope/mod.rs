@@ -24,6 +24,46 @@ pub trait Envelope<'a, T> {
     fn iter(&'a self) -> Self::Iter;
     fn insert_event(&mut self, event: Self::EventType);
     fn forget_past(&mut self, number_of_frames_to_forget: u32);
+    /// Return `true` if the envelope has finished at the current position: it has reached its
+    /// final value and no events are pending that would change it.
+    ///
+    /// A voice can use this to report that it is idle at the end of the release,
+    /// so that the polyphonic event dispatcher can reuse it.
+    ///
+    /// The default implementation returns `false`, for envelopes that never finish.
+    ///
+    /// Example
+    /// -------
+    /// ```
+    /// # #![allow(deprecated)]
+    /// use rsynth::envelope::Envelope;
+    /// use rsynth::utilities::polyphony::{ToneIdentifier, Voice};
+    /// use rsynth::utilities::polyphony::simple_event_dispatching::SimpleVoiceState;
+    /// struct MyVoice<E> {
+    ///     state: SimpleVoiceState<ToneIdentifier>,
+    ///     envelope: E,
+    ///     // ...
+    /// }
+    ///
+    /// impl<E> Voice<SimpleVoiceState<ToneIdentifier>> for MyVoice<E>
+    /// where
+    ///     E: for<'a> Envelope<'a, f32>,
+    /// {
+    ///     fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+    ///         match self.state {
+    ///             SimpleVoiceState::Releasing(_) if self.envelope.is_finished() => {
+    ///                 SimpleVoiceState::Idle
+    ///             }
+    ///             state => state,
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn is_finished(&self) -> bool {
+        false
+    }
 }
 
+#[cfg(feature = "event-queue")]
+pub mod adsr_envelope;
 pub mod staircase_envelope;
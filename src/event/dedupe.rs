@@ -0,0 +1,143 @@
+//! Suppress redundant note on and note off events.
+use super::{ContextualEventHandler, EventHandler, MidiMessage, RawMidiEvent};
+
+const NUMBER_OF_CHANNELS: usize = 16;
+
+/// Filter for outgoing midi events that suppresses note on events for notes that are already
+/// sounding and note off events for notes that are not sounding.
+///
+/// This avoids stuck notes e.g. when an arpeggiator emits a note on event for a note
+/// that is already held.
+///
+/// `DedupeNotes` keeps track of the notes that are sounding, so you should keep it
+/// between calls to `render_buffer`.
+/// It implements [`ContextualEventHandler`], where the context is the event handler
+/// that receives the events that are not suppressed (e.g. the host).
+/// Events other than note on and note off events are always passed.
+///
+/// _Note_: a note on event with velocity `0` is treated as a note off event.
+///
+/// # Example
+/// ```
+/// use rsynth::event::dedupe::DedupeNotes;
+/// use rsynth::event::{ContextualEventHandler, EventHandler, RawMidiEvent, Timed};
+///
+/// struct Recorder(Vec<Timed<RawMidiEvent>>);
+/// impl EventHandler<Timed<RawMidiEvent>> for Recorder {
+///     fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+///         self.0.push(event);
+///     }
+/// }
+///
+/// let mut dedupe = DedupeNotes::new();
+/// let mut midi_out = Recorder(Vec::new());
+/// dedupe.handle_event(Timed::new(0, RawMidiEvent::new(&[0x90, 60, 100])), &mut midi_out);
+/// dedupe.handle_event(Timed::new(3, RawMidiEvent::new(&[0x90, 60, 100])), &mut midi_out);
+/// assert_eq!(midi_out.0.len(), 1);
+/// ```
+///
+/// [`ContextualEventHandler`]: ../trait.ContextualEventHandler.html
+pub struct DedupeNotes {
+    // One bit per note for each channel.
+    sounding_notes: [u128; NUMBER_OF_CHANNELS],
+}
+
+impl DedupeNotes {
+    /// Create a new `DedupeNotes` for which no notes are sounding.
+    pub fn new() -> Self {
+        Self {
+            sounding_notes: [0; NUMBER_OF_CHANNELS],
+        }
+    }
+
+    /// Return `true` if a note on event has been passed for the given note on the given channel,
+    /// but the corresponding note off event not yet.
+    pub fn is_sounding(&self, channel: u8, note: u8) -> bool {
+        self.sounding_notes[channel as usize % NUMBER_OF_CHANNELS] & (1 << (note & 0x7F)) != 0
+    }
+
+    /// Forget all sounding notes, e.g. after sending an "all notes off" message.
+    pub fn reset(&mut self) {
+        self.sounding_notes = [0; NUMBER_OF_CHANNELS];
+    }
+
+    /// Return `true` if the event should be passed and update the sounding notes.
+    fn should_pass(&mut self, event: &RawMidiEvent) -> bool {
+        let (channel, note, note_on) = match event.parse() {
+            Some(MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            }) => (channel, note, velocity > 0),
+            Some(MidiMessage::NoteOff { channel, note, .. }) => (channel, note, false),
+            _ => return true,
+        };
+        if self.is_sounding(channel, note) == note_on {
+            return false;
+        }
+        self.sounding_notes[channel as usize] ^= 1 << note;
+        true
+    }
+}
+
+impl Default for DedupeNotes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E, H> ContextualEventHandler<E, H> for DedupeNotes
+where
+    E: AsRef<RawMidiEvent>,
+    H: EventHandler<E>,
+{
+    fn handle_event(&mut self, event: E, context: &mut H) {
+        if self.should_pass(event.as_ref()) {
+            context.handle_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DedupeNotes;
+    use crate::event::{ContextualEventHandler, EventHandler, RawMidiEvent, Timed};
+
+    struct Recorder(Vec<Timed<RawMidiEvent>>);
+
+    impl EventHandler<Timed<RawMidiEvent>> for Recorder {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+            self.0.push(event);
+        }
+    }
+
+    fn dedupe(events: &[[u8; 3]]) -> Vec<[u8; 3]> {
+        let mut dedupe = DedupeNotes::new();
+        let mut recorder = Recorder(Vec::new());
+        for (time, data) in events.iter().enumerate() {
+            dedupe.handle_event(
+                Timed::new(time as u32, RawMidiEvent::new(data)),
+                &mut recorder,
+            );
+        }
+        recorder.0.iter().map(|e| *e.event.data()).collect()
+    }
+
+    #[test]
+    fn second_note_on_for_held_note_is_suppressed() {
+        let observed = dedupe(&[[0x90, 60, 100], [0x90, 60, 90], [0x80, 60, 0]]);
+        assert_eq!(observed, vec![[0x90, 60, 100], [0x80, 60, 0]]);
+    }
+
+    #[test]
+    fn note_off_for_note_that_is_not_held_is_suppressed() {
+        let observed = dedupe(&[[0x80, 60, 0], [0x90, 61, 100], [0x91, 61, 0], [0x90, 61, 0]]);
+        assert_eq!(observed, vec![[0x90, 61, 100], [0x90, 61, 0]]);
+    }
+
+    #[test]
+    fn other_events_are_passed() {
+        let observed = dedupe(&[[0xB0, 7, 100], [0xB0, 7, 100]]);
+        assert_eq!(observed, vec![[0xB0, 7, 100], [0xB0, 7, 100]]);
+    }
+}
@@ -1,7 +1,9 @@
 //! Event handling
 //!
-//! This module defines the `EventHandler` trait and some event types: `RawMidiEvent`,
+//! This module defines the `EventHandler` trait and some event types: `RawMidiEvent`, `RawMidiEventBuf`,
 //! `SysExEvent`, ...
+//! It also defines the `SysExReassembler`, which reassembles SysEx messages that are split
+//! over several fragments.
 //!
 //! Custom events
 //! =============
@@ -16,15 +18,20 @@ use crate::backend::combined::midly::midly_0_5::TrackEventKind;
 #[cfg(all(test, feature = "backend-combined-midly-0-5"))]
 use crate::backend::combined::midly::midly_0_5::{
     num::{u4, u7},
-    MidiMessage,
+    MidiMessage as MidlyMidiMessage,
 };
+#[cfg(feature = "event-queue")]
+use ::event_queue::{EventQueue, HandleEventCollision};
 use core::num::NonZeroU64;
 use gcd::Gcd;
+use std::cmp::Ordering;
 use std::convert::{AsMut, AsRef, TryFrom};
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter, Write};
 
+pub mod dedupe;
 pub mod event_queue;
+pub mod recording;
 
 /// The trait that plugins should implement in order to handle the given type of events.
 ///
@@ -66,149 +73,1290 @@ pub trait EventHandlerExt<E> {
             function,
         }
     }
+
+    /// Create a new event handler that only lets the "self" event handler handle the events
+    /// for which the given predicate returns `true`.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::EventHandler;
+    /// use rsynth::event::EventHandlerExt;
+    ///
+    /// struct Printer;
+    /// impl EventHandler<u32> for Printer {
+    ///     fn handle_event(&mut self,event: u32) {
+    ///         println!("{}", event)
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut printer = Printer;
+    ///     printer.handle_event(3); // Prints "3"
+    ///     let mut even_printer = printer.filter(|i: &u32| i % 2 == 0);
+    ///     even_printer.handle_event(3); // Prints nothing
+    ///     even_printer.handle_event(4); // Prints "4"
+    /// }
+    /// ```
+    fn filter<F>(&mut self, predicate: F) -> Filter<Self, F>
+    where
+        F: Fn(&E) -> bool,
+    {
+        Filter {
+            inner: self,
+            predicate,
+        }
+    }
+
+    /// Create a new event handler that lets both the "self" event handler and the `other`
+    /// event handler handle each event (first "self", then `other`).
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::EventHandler;
+    /// use rsynth::event::EventHandlerExt;
+    ///
+    /// struct Printer;
+    /// impl EventHandler<u32> for Printer {
+    ///     fn handle_event(&mut self,event: u32) {
+    ///         println!("{}", event)
+    ///     }
+    /// }
+    ///
+    /// struct Recorder(Vec<u32>);
+    /// impl EventHandler<u32> for Recorder {
+    ///     fn handle_event(&mut self,event: u32) {
+    ///         self.0.push(event);
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut printer = Printer;
+    ///     let mut recorder = Recorder(Vec::new());
+    ///     let mut both = printer.tee(&mut recorder);
+    ///     both.handle_event(3); // Prints "3"
+    ///     assert_eq!(recorder.0, vec![3]);
+    /// }
+    /// ```
+    fn tee<'a, H2>(&'a mut self, other: &'a mut H2) -> Tee<'a, Self, H2>
+    where
+        H2: EventHandler<E> + ?Sized,
+        E: Clone,
+    {
+        Tee {
+            first: self,
+            second: other,
+        }
+    }
+}
+
+impl<T, E> EventHandlerExt<E> for T where T: EventHandler<E> + ?Sized {}
+
+/// An [`EventHandler`] from the [`EventHandlerExt::map`] method.
+pub struct Map<'a, H, F>
+where
+    H: ?Sized,
+{
+    inner: &'a mut H,
+    function: F,
+}
+
+impl<'a, E, EE, F, H> EventHandler<EE> for Map<'a, H, F>
+where
+    H: EventHandler<E>,
+    F: FnMut(EE) -> E,
+{
+    fn handle_event(&mut self, event: EE) {
+        self.inner.handle_event((self.function)(event))
+    }
+}
+
+/// An [`EventHandler`] from the [`EventHandlerExt::filter`] method.
+pub struct Filter<'a, H, F>
+where
+    H: ?Sized,
+{
+    inner: &'a mut H,
+    predicate: F,
+}
+
+impl<'a, E, F, H> EventHandler<E> for Filter<'a, H, F>
+where
+    H: EventHandler<E>,
+    F: Fn(&E) -> bool,
+{
+    fn handle_event(&mut self, event: E) {
+        if (self.predicate)(&event) {
+            self.inner.handle_event(event)
+        }
+    }
+}
+
+/// An [`EventHandler`] from the [`EventHandlerExt::tee`] method.
+pub struct Tee<'a, H1, H2>
+where
+    H1: ?Sized,
+    H2: ?Sized,
+{
+    first: &'a mut H1,
+    second: &'a mut H2,
+}
+
+impl<'a, E, H1, H2> EventHandler<E> for Tee<'a, H1, H2>
+where
+    H1: EventHandler<E> + ?Sized,
+    H2: EventHandler<E> + ?Sized,
+    E: Clone,
+{
+    fn handle_event(&mut self, event: E) {
+        self.first.handle_event(event.clone());
+        self.second.handle_event(event)
+    }
+}
+
+/// Let every event handler in the slice handle (a clone of) each event, e.g. to broadcast
+/// an event to all voices.
+///
+/// # Example
+/// ```
+/// use rsynth::event::EventHandler;
+///
+/// struct Recorder(Vec<u32>);
+/// impl EventHandler<u32> for Recorder {
+///     fn handle_event(&mut self, event: u32) {
+///         self.0.push(event);
+///     }
+/// }
+///
+/// let mut voices = vec![Recorder(Vec::new()), Recorder(Vec::new())];
+/// voices.handle_event(3);
+/// assert_eq!(voices[0].0, vec![3]);
+/// assert_eq!(voices[1].0, vec![3]);
+/// ```
+impl<E, H> EventHandler<E> for [H]
+where
+    H: EventHandler<E>,
+    E: Clone,
+{
+    fn handle_event(&mut self, event: E) {
+        for handler in self.iter_mut() {
+            handler.handle_event(event.clone());
+        }
+    }
+}
+
+/// Let every event handler in the `Vec` handle (a clone of) each event.
+/// See the implementation for slices for more information.
+impl<E, H> EventHandler<E> for Vec<H>
+where
+    H: EventHandler<E>,
+    E: Clone,
+{
+    fn handle_event(&mut self, event: E) {
+        self.as_mut_slice().handle_event(event)
+    }
+}
+
+/// Let the event handler handle the event if there is one; ignore the event otherwise.
+impl<E, H> EventHandler<E> for Option<H>
+where
+    H: EventHandler<E>,
+{
+    fn handle_event(&mut self, event: E) {
+        if let Some(handler) = self {
+            handler.handle_event(event)
+        }
+    }
+}
+
+#[test]
+fn slice_of_event_handlers_broadcasts_events() {
+    struct Recorder(Vec<u32>);
+    impl EventHandler<u32> for Recorder {
+        fn handle_event(&mut self, event: u32) {
+            self.0.push(event);
+        }
+    }
+    let mut handlers = vec![Recorder(Vec::new()), Recorder(Vec::new())];
+    handlers[..].handle_event(1);
+    handlers.handle_event(2);
+    for handler in handlers.iter() {
+        assert_eq!(handler.0, vec![1, 2]);
+    }
+    let mut some = Some(Recorder(Vec::new()));
+    some.handle_event(3);
+    assert_eq!(some.map(|r| r.0), Some(vec![3]));
+    let mut none: Option<Recorder> = None;
+    none.handle_event(4);
+}
+
+/// An [`EventHandler`] that queues the events in an `EventQueue` from the `event_queue` crate.
+///
+/// This makes it easy to queue events when they are received and handle them later,
+/// when rendering the audio.
+/// This type is only available when `rsynth` is compiled with the `event-queue` feature
+/// (which is enabled by the `backend-combined` feature).
+///
+/// _Note_: when the queue is full, an event is removed from the queue to make room
+/// (see `EventQueue::queue_event`); this event is dropped.
+///
+/// # Example
+/// ```
+/// use event_queue::{AlwaysInsertNewAfterOld, EventQueue};
+/// use rsynth::event::{EventHandler, QueueingHandler, Timed};
+///
+/// let mut queue = EventQueue::new(16);
+/// let mut handler = QueueingHandler::new(&mut queue, AlwaysInsertNewAfterOld);
+/// handler.handle_event(Timed::new(5, 'b'));
+/// handler.handle_event(Timed::new(2, 'a'));
+/// assert_eq!(queue.drain_all().collect::<Vec<_>>(), vec![(2, 'a'), (5, 'b')]);
+/// ```
+///
+/// [`EventHandler`]: ./trait.EventHandler.html
+#[cfg(feature = "event-queue")]
+pub struct QueueingHandler<'q, E, H, T = u32> {
+    queue: &'q mut EventQueue<T, E>,
+    collision_decider: H,
+}
+
+#[cfg(feature = "event-queue")]
+impl<'q, E, H, T> QueueingHandler<'q, E, H, T>
+where
+    H: HandleEventCollision<E>,
+{
+    /// Create a new `QueueingHandler` that queues events in the given `queue`,
+    /// using `collision_decider` for events with the same timing.
+    pub fn new(queue: &'q mut EventQueue<T, E>, collision_decider: H) -> Self {
+        Self {
+            queue,
+            collision_decider,
+        }
+    }
+}
+
+#[cfg(feature = "event-queue")]
+impl<'q, E, H, T> EventHandler<Timed<E, T>> for QueueingHandler<'q, E, H, T>
+where
+    H: HandleEventCollision<E>,
+    T: Ord,
+{
+    fn handle_event(&mut self, event: Timed<E, T>) {
+        self.queue
+            .queue_event((event.time_in_frames, event.event), &self.collision_decider);
+    }
+}
+
+#[cfg(feature = "event-queue")]
+#[test]
+fn queueing_handler_queues_events_in_time_order() {
+    use ::event_queue::AlwaysIgnoreNew;
+    let mut queue = EventQueue::new(4);
+    {
+        let mut handler = QueueingHandler::new(&mut queue, AlwaysIgnoreNew);
+        handler.handle_event(Timed::new(7, 'c'));
+        handler.handle_event(Timed::new(3, 'a'));
+        handler.handle_event(Timed::new(3, 'x'));
+        handler.handle_event(Timed::new(5, 'b'));
+    }
+    assert_eq!(
+        queue.drain_all().collect::<Vec<_>>(),
+        vec![(3, 'a'), (5, 'b'), (7, 'c')]
+    );
+}
+
+/// The trait that plugins should implement in order to handle the given type of events.
+///
+/// The type parameter `E` corresponds to the type of the event.
+/// The type parameter `Context` refers to the context that is passed to the event handler.
+pub trait ContextualEventHandler<E, Context> {
+    fn handle_event(&mut self, event: E, context: &mut Context);
+}
+
+/// An extension trait for [`ContextualEventHandler`] providing some convenient combinator functions.
+///
+/// This is the counterpart of [`EventHandlerExt`] for event handlers that need a context.
+///
+/// [`ContextualEventHandler`]: ./trait.ContextualEventHandler.html
+/// [`EventHandlerExt`]: ./trait.EventHandlerExt.html
+pub trait ContextualEventHandlerExt<E, Context> {
+    /// Create a new contextual event handler that first applies the given function to the event
+    /// and then lets the "self" event handler handle the event, with the same context.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::{ContextualEventHandler, ContextualEventHandlerExt};
+    ///
+    /// struct Printer;
+    /// impl ContextualEventHandler<u32, String> for Printer {
+    ///     fn handle_event(&mut self, event: u32, context: &mut String) {
+    ///         println!("{}{}", context, event)
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut printer = Printer;
+    ///     let mut context = String::from("Event: ");
+    ///     printer.handle_event(3, &mut context); // Prints "Event: 3"
+    ///     let mut increased_printer = printer.map(|i| i+1_u32);
+    ///     increased_printer.handle_event(3, &mut context); // Prints "Event: 4"
+    /// }
+    /// ```
+    fn map<EE, F>(&mut self, function: F) -> ContextualMap<Self, F>
+    where
+        F: FnMut(EE) -> E,
+    {
+        ContextualMap {
+            inner: self,
+            function,
+        }
+    }
+
+    /// Create a new contextual event handler that only lets the "self" event handler handle the
+    /// events for which the given predicate returns `true`.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::{ContextualEventHandler, ContextualEventHandlerExt};
+    ///
+    /// struct Printer;
+    /// impl ContextualEventHandler<u32, String> for Printer {
+    ///     fn handle_event(&mut self, event: u32, context: &mut String) {
+    ///         println!("{}{}", context, event)
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let mut printer = Printer;
+    ///     let mut context = String::from("Event: ");
+    ///     let mut even_printer = printer.filter(|i: &u32| i % 2 == 0);
+    ///     even_printer.handle_event(3, &mut context); // Prints nothing
+    ///     even_printer.handle_event(4, &mut context); // Prints "Event: 4"
+    /// }
+    /// ```
+    fn filter<F>(&mut self, predicate: F) -> ContextualFilter<Self, F>
+    where
+        F: Fn(&E) -> bool,
+    {
+        ContextualFilter {
+            inner: self,
+            predicate,
+        }
+    }
+}
+
+impl<T, E, Context> ContextualEventHandlerExt<E, Context> for T where
+    T: ContextualEventHandler<E, Context> + ?Sized
+{
+}
+
+/// A [`ContextualEventHandler`] from the [`ContextualEventHandlerExt::map`] method.
+pub struct ContextualMap<'a, H, F>
+where
+    H: ?Sized,
+{
+    inner: &'a mut H,
+    function: F,
+}
+
+impl<'a, E, EE, F, H, Context> ContextualEventHandler<EE, Context> for ContextualMap<'a, H, F>
+where
+    H: ContextualEventHandler<E, Context>,
+    F: FnMut(EE) -> E,
+{
+    fn handle_event(&mut self, event: EE, context: &mut Context) {
+        self.inner.handle_event((self.function)(event), context)
+    }
+}
+
+/// A [`ContextualEventHandler`] from the [`ContextualEventHandlerExt::filter`] method.
+pub struct ContextualFilter<'a, H, F>
+where
+    H: ?Sized,
+{
+    inner: &'a mut H,
+    predicate: F,
+}
+
+impl<'a, E, F, H, Context> ContextualEventHandler<E, Context> for ContextualFilter<'a, H, F>
+where
+    H: ContextualEventHandler<E, Context>,
+    F: Fn(&E) -> bool,
+{
+    fn handle_event(&mut self, event: E, context: &mut Context) {
+        if (self.predicate)(&event) {
+            self.inner.handle_event(event, context)
+        }
+    }
+}
+
+#[test]
+fn contextual_combinators_pass_the_context() {
+    #[derive(Debug, PartialEq)]
+    enum NoteEvent {
+        NoteOn(u8),
+        Other,
+    }
+
+    // A plugin that handles a custom event type and uses the context (here: a `Vec`
+    // in which it records what it has handled).
+    struct Plugin;
+    impl ContextualEventHandler<Timed<NoteEvent>, Vec<Timed<NoteEvent>>> for Plugin {
+        fn handle_event(&mut self, event: Timed<NoteEvent>, context: &mut Vec<Timed<NoteEvent>>) {
+            context.push(event);
+        }
+    }
+
+    let mut plugin = Plugin;
+    let mut context = Vec::new();
+    let mut mapped = plugin.map(|timed: Timed<RawMidiEvent>| Timed {
+        time_in_frames: timed.time_in_frames,
+        event: match timed.event.parse() {
+            Some(MidiMessage::NoteOn { note, .. }) => NoteEvent::NoteOn(note),
+            _ => NoteEvent::Other,
+        },
+    });
+    let mut filtered = mapped.filter(|timed: &Timed<RawMidiEvent>| timed.time_in_frames > 0);
+    filtered.handle_event(
+        Timed::new(0, RawMidiEvent::new(&[0x90, 60, 90])),
+        &mut context,
+    );
+    filtered.handle_event(
+        Timed::new(3, RawMidiEvent::new(&[0x90, 64, 90])),
+        &mut context,
+    );
+    filtered.handle_event(Timed::new(5, RawMidiEvent::new(&[0xC0, 1])), &mut context);
+    assert_eq!(
+        context,
+        vec![
+            Timed::new(3, NoteEvent::NoteOn(64)),
+            Timed::new(5, NoteEvent::Other)
+        ]
+    );
+}
+
+/// A System Exclusive ("SysEx") event.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SysExEvent<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Debug for SysExEvent<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "SysExEvent{{data (length: {:?}): &[", self.data.len())?;
+        for byte in self.data {
+            write!(f, "{:X} ", byte)?;
+        }
+        write!(f, "]}}")
+    }
+}
+
+impl<'a> SysExEvent<'a> {
+    /// Create a new `SysExEvent` with the given `data`.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+    /// Get the data from the `SysExEvent`
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+const STATUS_BIT: u8 = 0x80;
+const FIRST_SYSTEM_STATUS: u8 = 0xF0;
+const SYSEX_START: u8 = 0xF0;
+const SYSEX_END: u8 = 0xF7;
+const FIRST_SYSTEM_REAL_TIME_STATUS: u8 = 0xF8;
+
+/// Reassembles System Exclusive messages that are delivered in several fragments.
+///
+/// Some hosts split a long SysEx message over several midi packets: the first packet
+/// starts with `F0` and the last packet ends with `F7`.
+/// Feed the fragments (in order) to the [`feed`] method;
+/// once the closing `F7` is seen, the complete message (including the opening `F0`
+/// and the closing `F7`) is passed as a [`SysExEvent`] to the callback.
+///
+/// Malformed streams
+/// =================
+/// * Bytes that are received outside of a SysEx message (before the first `F0`,
+///   or after an `F7`) are ignored.
+/// * When a new `F0` is received before the `F7` of the previous message,
+///   the incomplete previous message is discarded and a new message is started.
+/// * System real-time messages (`F8` up to and including `FF`) may be interleaved
+///   with the SysEx data; they are ignored.
+/// * Any other status byte (e.g. a note-on) before the `F7` aborts the message;
+///   the incomplete message is discarded.
+///
+/// # Example
+/// ```
+/// use rsynth::event::SysExReassembler;
+///
+/// let mut reassembler = SysExReassembler::new();
+/// let mut messages = Vec::new();
+/// reassembler.feed(&[0xF0, 0x7E, 0x7F], |event| messages.push(event.data().to_vec()));
+/// assert!(messages.is_empty());
+/// reassembler.feed(&[0x09, 0x01, 0xF7], |event| messages.push(event.data().to_vec()));
+/// assert_eq!(messages, vec![vec![0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7]]);
+/// ```
+///
+/// Note about using in a real-time context
+/// =======================================
+/// The internal buffer grows when a message does not fit, so this may allocate memory.
+/// Use [`with_capacity`] to allocate enough memory up front.
+///
+/// [`feed`]: #method.feed
+/// [`with_capacity`]: #method.with_capacity
+/// [`SysExEvent`]: ./struct.SysExEvent.html
+#[derive(Debug, Default)]
+pub struct SysExReassembler {
+    buffer: Vec<u8>,
+    in_progress: bool,
+}
+
+impl SysExReassembler {
+    /// Create a new `SysExReassembler`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new `SysExReassembler` that can store a message of `capacity` bytes
+    /// without allocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(capacity),
+            in_progress: false,
+        }
+    }
+
+    /// Return `true` if an `F0` has been received, but the corresponding `F7` not yet.
+    pub fn is_in_progress(&self) -> bool {
+        self.in_progress
+    }
+
+    /// Discard the message that is currently being reassembled, if any.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.in_progress = false;
+    }
+
+    /// Feed the next fragment of the byte stream.
+    ///
+    /// `on_complete` is called for every SysEx message that is completed by this fragment.
+    /// One fragment can complete more than one message.
+    pub fn feed<F>(&mut self, fragment: &[u8], mut on_complete: F)
+    where
+        F: FnMut(SysExEvent),
+    {
+        for &byte in fragment {
+            match byte {
+                SYSEX_START => {
+                    self.buffer.clear();
+                    self.buffer.push(byte);
+                    self.in_progress = true;
+                }
+                SYSEX_END if self.in_progress => {
+                    self.buffer.push(byte);
+                    on_complete(SysExEvent::new(&self.buffer));
+                    self.reset();
+                }
+                FIRST_SYSTEM_REAL_TIME_STATUS..=0xFF => {}
+                0x80..=0xFF => self.reset(),
+                _ => {
+                    if self.in_progress {
+                        self.buffer.push(byte);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn sys_ex_reassembler_reassembles_fragmented_messages() {
+    let mut reassembler = SysExReassembler::new();
+    let mut messages = Vec::new();
+    for fragment in &[
+        &[0xF0, 0x43][..],
+        &[0x12, 0x00],
+        &[0x7F, 0xF7, 0xF0, 0x01, 0xF7],
+    ] {
+        reassembler.feed(fragment, |event| messages.push(event.data().to_vec()));
+    }
+    assert_eq!(
+        messages,
+        vec![
+            vec![0xF0, 0x43, 0x12, 0x00, 0x7F, 0xF7],
+            vec![0xF0, 0x01, 0xF7]
+        ]
+    );
+    assert!(!reassembler.is_in_progress());
+}
+
+#[test]
+fn sys_ex_reassembler_handles_malformed_streams() {
+    let mut reassembler = SysExReassembler::new();
+    let mut messages = Vec::new();
+    // Data outside of a message is ignored.
+    reassembler.feed(&[0x12, 0xF7], |event| messages.push(event.data().to_vec()));
+    assert!(messages.is_empty());
+    // A new `F0` before the `F7` discards the incomplete message.
+    reassembler.feed(&[0xF0, 0x01, 0xF0, 0x02], |event| {
+        messages.push(event.data().to_vec())
+    });
+    // System real-time messages are ignored.
+    reassembler.feed(&[0xF8, 0x03, 0xF7], |event| {
+        messages.push(event.data().to_vec())
+    });
+    assert_eq!(messages, vec![vec![0xF0, 0x02, 0x03, 0xF7]]);
+    // Other status bytes abort the message.
+    reassembler.feed(&[0xF0, 0x04, 0x90, 0x05, 0xF7], |event| {
+        messages.push(event.data().to_vec())
+    });
+    assert_eq!(messages.len(), 1);
+    assert!(!reassembler.is_in_progress());
+}
+
+/// A raw midi event.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_1::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_1"))]
+pub struct RawMidiEvent {
+    data: [u8; 3],
+    length: usize,
+}
+
+impl Debug for RawMidiEvent {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
+        match self.length {
+            1 => write!(f, "RawMidiEvent({:X})", self.data[0]),
+            2 => write!(f, "RawMidiEvent({:X} {:X})", self.data[0], self.data[1]),
+            3 => write!(
+                f,
+                "RawMidiEvent({:X} {:X} {:X})",
+                self.data[0], self.data[1], self.data[2]
+            ),
+            _ => unreachable!("Raw midi event is expected to have length 1, 2 or 3."),
+        }
+    }
+}
+
+impl RawMidiEvent {
+    /// Create a new `RawMidiEvent` with the given raw data.
+    ///
+    /// Panics
+    /// ------
+    /// Panics when `data` does not have length 1, 2 or 3.
+    #[inline]
+    pub fn new(bytes: &[u8]) -> Self {
+        match Self::try_new(bytes) {
+            Some(s) => s,
+            None => {
+                Self::panic_data_too_long(bytes);
+            }
+        }
+    }
+
+    fn panic_data_too_long(bytes: &[u8]) -> ! {
+        let mut event_as_string = String::new();
+        write!(event_as_string, "data : &[");
+        for (index, byte) in bytes.iter().enumerate() {
+            write!(event_as_string, "{:X} ", byte);
+            if index > 64 {
+                write!(event_as_string, "... ");
+                break;
+            }
+        }
+        write!(event_as_string, "]");
+        panic!(
+            "Raw midi event is expected to have length 1, 2 or 3. Actual length: {}, data: {}",
+            bytes.len(),
+            event_as_string
+        );
+    }
+
+    /// Try to create a new `RawMidiEvent` with the given raw data.
+    /// Return None when `data` does not have length 1, 2 or 3.
+    pub fn try_new(data: &[u8]) -> Option<Self> {
+        match data.len() {
+            1 => Some(Self {
+                data: [data[0], 0, 0],
+                length: data.len(),
+            }),
+            2 => Some(Self {
+                data: [data[0], data[1], 0],
+                length: data.len(),
+            }),
+            3 => Some(Self {
+                data: [data[0], data[1], data[2]],
+                length: data.len(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Get the raw data from a `RawMidiEvent`, including "padding".
+    pub fn data(&self) -> &[u8; 3] {
+        &self.data
+    }
+
+    /// Get the raw data from a `RawMidiEvent`.
+    pub fn bytes(&self) -> &[u8] {
+        &self.data[0..self.length]
+    }
+
+    /// Return `true` if this is a system message (status byte `F0` up to and including `FF`),
+    /// including system real-time messages.
+    pub fn is_system_message(&self) -> bool {
+        self.data[0] >= FIRST_SYSTEM_STATUS
+    }
+
+    /// Return `true` if this is a system real-time message (status byte `F8` up to and
+    /// including `FF`), e.g. a timing clock message.
+    pub fn is_realtime(&self) -> bool {
+        self.data[0] >= FIRST_SYSTEM_REAL_TIME_STATUS
+    }
+
+    /// Return `true` if this is a channel voice message (status byte `80` up to and
+    /// including `EF`), e.g. a note-on message.
+    ///
+    /// _Note_: channel mode messages (e.g. "all notes off") are control change messages,
+    /// so they are also considered channel voice messages.
+    pub fn is_channel_voice(&self) -> bool {
+        self.data[0] & STATUS_BIT != 0 && !self.is_system_message()
+    }
+
+    /// The channel (in the range 0-15) of a channel voice message.
+    /// Return `None` if this is not a channel voice message.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::RawMidiEvent;
+    ///
+    /// assert_eq!(RawMidiEvent::new(&[0x93, 69, 100]).channel(), Some(3));
+    /// assert_eq!(RawMidiEvent::new(&[0xF8]).channel(), None);
+    /// ```
+    pub fn channel(&self) -> Option<u8> {
+        if self.is_channel_voice() {
+            Some(self.data[0] & !midi_consts::channel_event::EVENT_TYPE_MASK)
+        } else {
+            None
+        }
+    }
+
+    /// Create a note on event.
+    /// Return `None` when `channel` is not in the range 0-15 or when `note` or `velocity`
+    /// is not in the range 0-127.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::RawMidiEvent;
+    ///
+    /// let event = RawMidiEvent::note_on(1, 69, 100).unwrap();
+    /// assert_eq!(event.bytes(), &[0x91, 69, 100]);
+    /// assert_eq!(RawMidiEvent::note_on(16, 69, 100), None);
+    /// ```
+    pub fn note_on(channel: u8, note: u8, velocity: u8) -> Option<Self> {
+        if channel > MAX_CHANNEL || note > MAX_DATA_BYTE || velocity > MAX_DATA_BYTE {
+            return None;
+        }
+        Some(
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            }
+            .into(),
+        )
+    }
+
+    /// Create a note off event.
+    /// Return `None` when `channel` is not in the range 0-15 or when `note` or `velocity`
+    /// is not in the range 0-127.
+    pub fn note_off(channel: u8, note: u8, velocity: u8) -> Option<Self> {
+        if channel > MAX_CHANNEL || note > MAX_DATA_BYTE || velocity > MAX_DATA_BYTE {
+            return None;
+        }
+        Some(
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            }
+            .into(),
+        )
+    }
+
+    /// Create a control change event.
+    /// Return `None` when `channel` is not in the range 0-15 or when `controller` or `value`
+    /// is not in the range 0-127.
+    pub fn control_change(channel: u8, controller: u8, value: u8) -> Option<Self> {
+        if channel > MAX_CHANNEL || controller > MAX_DATA_BYTE || value > MAX_DATA_BYTE {
+            return None;
+        }
+        Some(
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            }
+            .into(),
+        )
+    }
+
+    /// Create a pitch bend event.
+    /// The `value` is centered around `0`, see [`MidiMessage::PitchBend`].
+    /// Return `None` when `channel` is not in the range 0-15 or when `value` is not
+    /// in the range -8192 up to and including 8191.
+    ///
+    /// [`MidiMessage::PitchBend`]: ./enum.MidiMessage.html#variant.PitchBend
+    pub fn pitch_bend(channel: u8, value: i16) -> Option<Self> {
+        if channel > MAX_CHANNEL || value < -PITCH_BEND_CENTER || value >= PITCH_BEND_CENTER {
+            return None;
+        }
+        Some(MidiMessage::PitchBend { channel, value }.into())
+    }
+
+    /// Parse the `RawMidiEvent` into a [`MidiMessage`].
+    /// Return `None` when the event is not a channel message or when it is malformed
+    /// (e.g. too short or with a data byte that has its most significant bit set).
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::{MidiMessage, RawMidiEvent};
+    ///
+    /// let event = RawMidiEvent::new(&[0x91, 69, 100]);
+    /// assert_eq!(
+    ///     event.parse(),
+    ///     Some(MidiMessage::NoteOn { channel: 1, note: 69, velocity: 100 })
+    /// );
+    /// ```
+    ///
+    /// [`MidiMessage`]: ./enum.MidiMessage.html
+    pub fn parse(&self) -> Option<MidiMessage> {
+        use midi_consts::channel_event::*;
+        let channel = self.channel()?;
+        let status = self.data[0];
+        let data_length = match status & EVENT_TYPE_MASK {
+            PROGRAM_CHANGE | CHANNEL_KEY_PRESSURE => 1,
+            NOTE_OFF | NOTE_ON | POLYPHONIC_KEY_PRESSURE | CONTROL_CHANGE | PITCH_BEND_CHANGE => 2,
+            _ => return None,
+        };
+        if self.length < 1 + data_length || self.data[1..=data_length].iter().any(|b| *b > 0x7F) {
+            return None;
+        }
+        let (a, b) = (self.data[1], self.data[2]);
+        Some(match status & EVENT_TYPE_MASK {
+            NOTE_OFF => MidiMessage::NoteOff {
+                channel,
+                note: a,
+                velocity: b,
+            },
+            NOTE_ON => MidiMessage::NoteOn {
+                channel,
+                note: a,
+                velocity: b,
+            },
+            POLYPHONIC_KEY_PRESSURE => MidiMessage::PolyPressure {
+                channel,
+                note: a,
+                pressure: b,
+            },
+            CONTROL_CHANGE => MidiMessage::ControlChange {
+                channel,
+                controller: a,
+                value: b,
+            },
+            PROGRAM_CHANGE => MidiMessage::ProgramChange {
+                channel,
+                program: a,
+            },
+            CHANNEL_KEY_PRESSURE => MidiMessage::ChannelPressure {
+                channel,
+                pressure: a,
+            },
+            PITCH_BEND_CHANGE => MidiMessage::PitchBend {
+                channel,
+                value: (((b as i16) << 7) | a as i16) - PITCH_BEND_CENTER,
+            },
+            _ => unreachable!(),
+        })
+    }
+}
+
+const PITCH_BEND_CENTER: i16 = 0x2000;
+const MAX_CHANNEL: u8 = 0x0F;
+const MAX_DATA_BYTE: u8 = 0x7F;
+
+/// A structured midi channel message, as returned by [`RawMidiEvent::parse`].
+///
+/// All fields are in the range that midi allows: `channel` ranges from 0 to 15
+/// and the other fields (except for the value of a pitch bend) range from 0 to 127.
+///
+/// [`RawMidiEvent::parse`]: ./struct.RawMidiEvent.html#method.parse
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MidiMessage {
+    /// A note off event.
+    NoteOff { channel: u8, note: u8, velocity: u8 },
+    /// A note on event.
+    ///
+    /// _Note_: by convention, a note on event with `velocity` `0` is to be treated as a note off event.
+    NoteOn { channel: u8, note: u8, velocity: u8 },
+    /// Polyphonic key pressure ("aftertouch") for a single note.
+    PolyPressure { channel: u8, note: u8, pressure: u8 },
+    /// A controller has changed its value.
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    /// A program change.
+    ProgramChange { channel: u8, program: u8 },
+    /// Channel pressure ("aftertouch") for the whole channel.
+    ChannelPressure { channel: u8, pressure: u8 },
+    /// A pitch bend. The 14-bit value is centered around `0`,
+    /// so it ranges from `-8192` up to and including `8191`.
+    PitchBend { channel: u8, value: i16 },
+}
+
+impl From<MidiMessage> for RawMidiEvent {
+    /// Encode the `MidiMessage` as a `RawMidiEvent`.
+    ///
+    /// Fields that are out of range are truncated to the number of bits that midi allows.
+    fn from(message: MidiMessage) -> Self {
+        use midi_consts::channel_event::*;
+        let status = |event_type: u8, channel: u8| event_type | (channel & !EVENT_TYPE_MASK);
+        match message {
+            MidiMessage::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => RawMidiEvent::new(&[status(NOTE_OFF, channel), note & 0x7F, velocity & 0x7F]),
+            MidiMessage::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => RawMidiEvent::new(&[status(NOTE_ON, channel), note & 0x7F, velocity & 0x7F]),
+            MidiMessage::PolyPressure {
+                channel,
+                note,
+                pressure,
+            } => RawMidiEvent::new(&[
+                status(POLYPHONIC_KEY_PRESSURE, channel),
+                note & 0x7F,
+                pressure & 0x7F,
+            ]),
+            MidiMessage::ControlChange {
+                channel,
+                controller,
+                value,
+            } => RawMidiEvent::new(&[
+                status(CONTROL_CHANGE, channel),
+                controller & 0x7F,
+                value & 0x7F,
+            ]),
+            MidiMessage::ProgramChange { channel, program } => {
+                RawMidiEvent::new(&[status(PROGRAM_CHANGE, channel), program & 0x7F])
+            }
+            MidiMessage::ChannelPressure { channel, pressure } => {
+                RawMidiEvent::new(&[status(CHANNEL_KEY_PRESSURE, channel), pressure & 0x7F])
+            }
+            MidiMessage::PitchBend { channel, value } => {
+                let value = (value.wrapping_add(PITCH_BEND_CENTER) as u16) & 0x3FFF;
+                RawMidiEvent::new(&[
+                    status(PITCH_BEND_CHANGE, channel),
+                    (value & 0x7F) as u8,
+                    (value >> 7) as u8,
+                ])
+            }
+        }
+    }
+}
+
+#[test]
+fn midi_message_round_trips_through_raw_midi_event() {
+    let messages = [
+        MidiMessage::NoteOff {
+            channel: 0,
+            note: 60,
+            velocity: 64,
+        },
+        MidiMessage::NoteOn {
+            channel: 15,
+            note: 69,
+            velocity: 127,
+        },
+        MidiMessage::PolyPressure {
+            channel: 3,
+            note: 12,
+            pressure: 34,
+        },
+        MidiMessage::ControlChange {
+            channel: 4,
+            controller: 7,
+            value: 100,
+        },
+        MidiMessage::ProgramChange {
+            channel: 5,
+            program: 42,
+        },
+        MidiMessage::ChannelPressure {
+            channel: 6,
+            pressure: 99,
+        },
+        MidiMessage::PitchBend {
+            channel: 7,
+            value: -8192,
+        },
+        MidiMessage::PitchBend {
+            channel: 8,
+            value: 0,
+        },
+        MidiMessage::PitchBend {
+            channel: 9,
+            value: 8191,
+        },
+    ];
+    for message in messages.iter() {
+        let event = RawMidiEvent::from(*message);
+        assert_eq!(event.parse(), Some(*message));
+        assert_eq!(RawMidiEvent::new(event.bytes()), event);
+    }
 }
 
-impl<T, E> EventHandlerExt<E> for T where T: EventHandler<E> + ?Sized {}
+#[test]
+fn midi_message_is_encoded_as_expected() {
+    assert_eq!(
+        RawMidiEvent::from(MidiMessage::NoteOn {
+            channel: 1,
+            note: 69,
+            velocity: 100
+        })
+        .bytes(),
+        &[0x91, 69, 100]
+    );
+    assert_eq!(
+        RawMidiEvent::from(MidiMessage::ProgramChange {
+            channel: 2,
+            program: 5
+        })
+        .bytes(),
+        &[0xC2, 5]
+    );
+    assert_eq!(
+        RawMidiEvent::from(MidiMessage::PitchBend {
+            channel: 0,
+            value: 0
+        })
+        .bytes(),
+        &[0xE0, 0x00, 0x40]
+    );
+}
 
-/// An [`EventHandler`] from the [`EventHandlerExt::map`] method.
-pub struct Map<'a, H, F>
-where
-    H: ?Sized,
-{
-    inner: &'a mut H,
-    function: F,
+#[test]
+fn constructors_create_the_expected_events() {
+    assert_eq!(
+        RawMidiEvent::note_on(2, 60, 127).map(|e| e.parse()),
+        Some(Some(MidiMessage::NoteOn {
+            channel: 2,
+            note: 60,
+            velocity: 127
+        }))
+    );
+    assert_eq!(
+        RawMidiEvent::note_off(15, 0, 0).unwrap().bytes(),
+        &[0x8F, 0, 0]
+    );
+    assert_eq!(
+        RawMidiEvent::control_change(0, 7, 100).unwrap().bytes(),
+        &[0xB0, 7, 100]
+    );
+    assert_eq!(
+        RawMidiEvent::pitch_bend(3, 8191).unwrap().bytes(),
+        &[0xE3, 0x7F, 0x7F]
+    );
+    assert_eq!(
+        RawMidiEvent::pitch_bend(3, -8192).unwrap().bytes(),
+        &[0xE3, 0x00, 0x00]
+    );
 }
 
-impl<'a, E, EE, F, H> EventHandler<EE> for Map<'a, H, F>
-where
-    H: EventHandler<E>,
-    F: FnMut(EE) -> E,
-{
-    fn handle_event(&mut self, event: EE) {
-        self.inner.handle_event((self.function)(event))
-    }
+#[test]
+fn constructors_reject_out_of_range_values() {
+    assert_eq!(RawMidiEvent::note_on(16, 60, 100), None);
+    assert_eq!(RawMidiEvent::note_on(0, 128, 100), None);
+    assert_eq!(RawMidiEvent::note_off(0, 60, 128), None);
+    assert_eq!(RawMidiEvent::control_change(0, 128, 0), None);
+    assert_eq!(RawMidiEvent::control_change(0, 0, 128), None);
+    assert_eq!(RawMidiEvent::pitch_bend(16, 0), None);
+    assert_eq!(RawMidiEvent::pitch_bend(0, 8192), None);
+    assert_eq!(RawMidiEvent::pitch_bend(0, -8193), None);
 }
 
-/// The trait that plugins should implement in order to handle the given type of events.
-///
-/// The type parameter `E` corresponds to the type of the event.
-/// The type parameter `Context` refers to the context that is passed to the event handler.
-pub trait ContextualEventHandler<E, Context> {
-    fn handle_event(&mut self, event: E, context: &mut Context);
+#[test]
+fn parse_rejects_non_channel_and_malformed_messages() {
+    // System messages
+    assert_eq!(RawMidiEvent::new(&[0xF8]).parse(), None);
+    // Running status (no status byte)
+    assert_eq!(RawMidiEvent::new(&[0x45, 0x12]).parse(), None);
+    // Too short
+    assert_eq!(RawMidiEvent::new(&[0x90, 69]).parse(), None);
+    // Data byte with the most significant bit set
+    assert_eq!(RawMidiEvent::new(&[0x90, 0x80, 100]).parse(), None);
 }
 
-/// A System Exclusive ("SysEx") event.
-#[derive(Clone, Copy, PartialEq, Eq)]
-pub struct SysExEvent<'a> {
-    data: &'a [u8],
+#[test]
+fn clock_is_a_realtime_system_message() {
+    let clock = RawMidiEvent::new(&[0xF8]);
+    assert!(clock.is_system_message());
+    assert!(clock.is_realtime());
+    assert!(!clock.is_channel_voice());
+    assert_eq!(clock.channel(), None);
 }
 
-impl<'a> Debug for SysExEvent<'a> {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
-        write!(f, "SysExEvent{{data (length: {:?}): &[", self.data.len())?;
-        for byte in self.data {
-            write!(f, "{:X} ", byte)?;
-        }
-        write!(f, "]}}")
-    }
+#[test]
+fn sysex_start_is_a_system_message_but_not_realtime() {
+    let sysex_start = RawMidiEvent::new(&[0xF0, 0x7E]);
+    assert!(sysex_start.is_system_message());
+    assert!(!sysex_start.is_realtime());
+    assert!(!sysex_start.is_channel_voice());
+    assert_eq!(sysex_start.channel(), None);
 }
 
-impl<'a> SysExEvent<'a> {
-    /// Create a new `SysExEvent` with the given `data`.
-    pub fn new(data: &'a [u8]) -> Self {
-        Self { data }
-    }
-    /// Get the data from the `SysExEvent`
-    pub fn data(&self) -> &'a [u8] {
-        self.data
-    }
+#[test]
+fn note_on_is_a_channel_voice_message() {
+    let note_on = RawMidiEvent::new(&[0x9A, 60, 100]);
+    assert!(!note_on.is_system_message());
+    assert!(!note_on.is_realtime());
+    assert!(note_on.is_channel_voice());
+    assert_eq!(note_on.channel(), Some(10));
 }
 
-/// A raw midi event.
+/// The maximum number of bytes that can be stored in a [`RawMidiEventBuf`].
+///
+/// [`RawMidiEventBuf`]: ./struct.RawMidiEventBuf.html
+pub const RAW_MIDI_EVENT_BUF_CAPACITY: usize = 8;
+
+/// A raw midi event that can hold up to [`RAW_MIDI_EVENT_BUF_CAPACITY`] bytes.
+///
+/// This can be used for messages that do not fit in a [`RawMidiEvent`], e.g. the
+/// two control change messages that together form a 14-bit controller value.
+/// The data is stored inline, so `RawMidiEventBuf` implements `Copy` and does not allocate.
+///
+/// [`RAW_MIDI_EVENT_BUF_CAPACITY`]: ./constant.RAW_MIDI_EVENT_BUF_CAPACITY.html
+/// [`RawMidiEvent`]: ./struct.RawMidiEvent.html
 #[derive(Clone, Copy, PartialEq, Eq)]
-pub struct RawMidiEvent {
-    data: [u8; 3],
+pub struct RawMidiEventBuf {
+    data: [u8; RAW_MIDI_EVENT_BUF_CAPACITY],
     length: usize,
 }
 
-impl Debug for RawMidiEvent {
+impl Debug for RawMidiEventBuf {
     fn fmt(&self, f: &mut Formatter) -> Result<(), std::fmt::Error> {
-        match self.length {
-            1 => write!(f, "RawMidiEvent({:X})", self.data[0]),
-            2 => write!(f, "RawMidiEvent({:X} {:X})", self.data[0], self.data[1]),
-            3 => write!(
-                f,
-                "RawMidiEvent({:X} {:X} {:X})",
-                self.data[0], self.data[1], self.data[2]
-            ),
-            _ => unreachable!("Raw midi event is expected to have length 1, 2 or 3."),
+        write!(f, "RawMidiEventBuf(")?;
+        for (index, byte) in self.bytes().iter().enumerate() {
+            if index > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:X}", byte)?;
         }
+        write!(f, ")")
     }
 }
 
-impl RawMidiEvent {
-    /// Create a new `RawMidiEvent` with the given raw data.
+impl RawMidiEventBuf {
+    /// Create a new `RawMidiEventBuf` with the given raw data.
     ///
     /// Panics
     /// ------
-    /// Panics when `data` does not have length 1, 2 or 3.
+    /// Panics when `data` is empty or has more than [`RAW_MIDI_EVENT_BUF_CAPACITY`] bytes.
+    ///
+    /// [`RAW_MIDI_EVENT_BUF_CAPACITY`]: ./constant.RAW_MIDI_EVENT_BUF_CAPACITY.html
     #[inline]
     pub fn new(bytes: &[u8]) -> Self {
         match Self::try_new(bytes) {
             Some(s) => s,
-            None => {
-                Self::panic_data_too_long(bytes);
-            }
-        }
-    }
-
-    fn panic_data_too_long(bytes: &[u8]) -> ! {
-        let mut event_as_string = String::new();
-        write!(event_as_string, "data : &[");
-        for (index, byte) in bytes.iter().enumerate() {
-            write!(event_as_string, "{:X} ", byte);
-            if index > 64 {
-                write!(event_as_string, "... ");
-                break;
-            }
+            None => panic!(
+                "Raw midi event buffer is expected to have length 1 up to {}. Actual length: {}",
+                RAW_MIDI_EVENT_BUF_CAPACITY,
+                bytes.len()
+            ),
         }
-        write!(event_as_string, "]");
-        panic!(
-            "Raw midi event is expected to have length 1, 2 or 3. Actual length: {}, data: {}",
-            bytes.len(),
-            event_as_string
-        );
     }
 
-    /// Try to create a new `RawMidiEvent` with the given raw data.
-    /// Return None when `data` does not have length 1, 2 or 3.
+    /// Try to create a new `RawMidiEventBuf` with the given raw data.
+    /// Return None when `data` is empty or has more than [`RAW_MIDI_EVENT_BUF_CAPACITY`] bytes.
+    ///
+    /// [`RAW_MIDI_EVENT_BUF_CAPACITY`]: ./constant.RAW_MIDI_EVENT_BUF_CAPACITY.html
     pub fn try_new(data: &[u8]) -> Option<Self> {
-        match data.len() {
-            1 => Some(Self {
-                data: [data[0], 0, 0],
-                length: data.len(),
-            }),
-            2 => Some(Self {
-                data: [data[0], data[1], 0],
-                length: data.len(),
-            }),
-            3 => Some(Self {
-                data: [data[0], data[1], data[2]],
-                length: data.len(),
-            }),
-            _ => None,
+        if data.is_empty() || data.len() > RAW_MIDI_EVENT_BUF_CAPACITY {
+            return None;
         }
+        let mut buffer = [0; RAW_MIDI_EVENT_BUF_CAPACITY];
+        buffer[0..data.len()].copy_from_slice(data);
+        Some(Self {
+            data: buffer,
+            length: data.len(),
+        })
     }
 
-    /// Get the raw data from a `RawMidiEvent`, including "padding".
-    pub fn data(&self) -> &[u8; 3] {
+    /// Get the raw data from a `RawMidiEventBuf`, including "padding".
+    pub fn data(&self) -> &[u8; RAW_MIDI_EVENT_BUF_CAPACITY] {
         &self.data
     }
 
-    /// Get the raw data from a `RawMidiEvent`.
+    /// Get the raw data from a `RawMidiEventBuf`.
     pub fn bytes(&self) -> &[u8] {
         &self.data[0..self.length]
     }
+
+    /// Convert to a `RawMidiEvent`.
+    /// Return None when the event has more than 3 bytes.
+    pub fn to_raw_midi_event(&self) -> Option<RawMidiEvent> {
+        RawMidiEvent::try_new(self.bytes())
+    }
+}
+
+impl AsRef<Self> for RawMidiEventBuf {
+    fn as_ref(&self) -> &RawMidiEventBuf {
+        self
+    }
+}
+
+impl AsMut<Self> for RawMidiEventBuf {
+    fn as_mut(&mut self) -> &mut RawMidiEventBuf {
+        self
+    }
+}
+
+impl From<RawMidiEvent> for RawMidiEventBuf {
+    fn from(event: RawMidiEvent) -> Self {
+        // A `RawMidiEvent` always fits in a `RawMidiEventBuf`.
+        RawMidiEventBuf::new(event.bytes())
+    }
+}
+
+#[test]
+fn raw_midi_event_buf_stores_more_than_three_bytes() {
+    let data = [0xB0, 0x07, 0x10, 0xB0, 0x27, 0x20];
+    let event = RawMidiEventBuf::new(&data);
+    assert_eq!(event.bytes(), &data);
+    assert_eq!(event.data(), &[0xB0, 0x07, 0x10, 0xB0, 0x27, 0x20, 0, 0]);
+    assert_eq!(event.to_raw_midi_event(), None);
+}
+
+#[test]
+fn raw_midi_event_buf_rejects_empty_and_too_long_data() {
+    assert_eq!(RawMidiEventBuf::try_new(&[]), None);
+    assert_eq!(
+        RawMidiEventBuf::try_new(&[0; RAW_MIDI_EVENT_BUF_CAPACITY + 1]),
+        None
+    );
+}
+
+#[test]
+fn raw_midi_event_buf_round_trips_raw_midi_event() {
+    let event = RawMidiEvent::new(&[0x90, 69, 100]);
+    let buf = RawMidiEventBuf::from(event);
+    assert_eq!(buf.bytes(), event.bytes());
+    assert_eq!(buf.to_raw_midi_event(), Some(event));
 }
 
 #[cfg(feature = "backend-combined-midly-0-5")]
@@ -279,7 +1427,7 @@ fn conversion_from_midly_to_raw_midi_event_works() {
     let program = 2;
     let event_kind = TrackEventKind::Midi {
         channel: u4::from(channel),
-        message: MidiMessage::ProgramChange {
+        message: MidlyMidiMessage::ProgramChange {
             program: u7::from(program),
         },
     };
@@ -309,12 +1457,23 @@ impl AsMut<Self> for RawMidiEvent {
 
 /// `Timed<E>` adds timing to an event.
 ///
+/// The type parameter `T` is the type that is used for the timing.
+/// It defaults to `u32`, which is what the backends use.
+/// For offline rendering of very long audio files, you can use `u64` instead,
+/// or a floating point type for sub-sample accurate timing.
+/// Use [`map_time`] to convert to the type that is expected by a backend.
+///
 /// # Suggestion
 /// If you want to handle events in a sample-accurate way, you can use an
-/// `EventQueue` to queue them when you receive them, and later use the
-/// `split` method on the queue to render the audio.
+/// `EventQueue` to queue them when you receive them (e.g. with a [`QueueingHandler`]),
+/// and later use the `split` method on the queue to render the audio.
+///
+/// [`QueueingHandler`]: ./struct.QueueingHandler.html
+/// [`map_time`]: #method.map_time
 #[derive(PartialEq, Eq, Debug)]
-pub struct Timed<E> {
+#[cfg_attr(feature = "serde", derive(serde_1::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_1"))]
+pub struct Timed<E, T = u32> {
     /// The offset (in frames) of the event relative to the start of
     /// the audio buffer.
     ///
@@ -323,7 +1482,7 @@ pub struct Timed<E> {
     /// the [`render_buffer`] method of the `Plugin` trait.
     ///
     /// [`render_buffer`]: ../trait.Plugin.html#tymethod.render_buffer
-    pub time_in_frames: u32,
+    pub time_in_frames: T,
     /// The underlying event.
     pub event: E,
 }
@@ -337,27 +1496,145 @@ impl<E> Timed<E> {
     }
 }
 
-impl<E> Clone for Timed<E>
+impl<E, T> Timed<E, T> {
+    /// Convert the timing of the event, keeping the underlying event.
+    ///
+    /// # Example
+    /// ```
+    /// use rsynth::event::Timed;
+    /// use std::convert::TryFrom;
+    ///
+    /// let long_timed = Timed { time_in_frames: 300_u64, event: 'a' };
+    /// let timed: Timed<char> = long_timed.map_time(|t| u32::try_from(t).unwrap());
+    /// assert_eq!(timed, Timed::new(300, 'a'));
+    /// ```
+    pub fn map_time<U, F>(self, f: F) -> Timed<E, U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        Timed {
+            time_in_frames: f(self.time_in_frames),
+            event: self.event,
+        }
+    }
+}
+
+impl<E, T> Clone for Timed<E, T>
 where
     E: Clone,
+    T: Clone,
 {
     fn clone(&self) -> Self {
         Timed {
-            time_in_frames: self.time_in_frames,
+            time_in_frames: self.time_in_frames.clone(),
             event: self.event.clone(),
         }
     }
 }
 
-impl<E> Copy for Timed<E> where E: Copy {}
+impl<E, T> Copy for Timed<E, T>
+where
+    E: Copy,
+    T: Copy,
+{
+}
+
+/// Timed events are ordered by `time_in_frames` only, the underlying event is ignored.
+///
+/// _Note_: this means that two `Timed` events with the same timing but a different
+/// underlying event are not equal, but compare as `Ordering::Equal`.
+/// Sorting a `Vec<Timed<E>>` with the (stable) `sort` method keeps the order of events
+/// with the same timing.
+impl<E, T> PartialOrd for Timed<E, T>
+where
+    E: PartialEq,
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.time_in_frames.partial_cmp(&other.time_in_frames)
+    }
+}
+
+/// Timed events are ordered by `time_in_frames` only, the underlying event is ignored.
+/// See the documentation of the `PartialOrd` implementation for more information.
+impl<E, T> Ord for Timed<E, T>
+where
+    E: Eq,
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.time_in_frames.cmp(&other.time_in_frames)
+    }
+}
+
+#[test]
+fn timed_events_are_sorted_by_time_only() {
+    let mut events = vec![
+        Timed::new(3, 'a'),
+        Timed::new(1, 'z'),
+        Timed::new(2, 'b'),
+        Timed::new(1, 'c'),
+    ];
+    events.sort();
+    assert_eq!(
+        events,
+        vec![
+            Timed::new(1, 'z'),
+            Timed::new(1, 'c'),
+            Timed::new(2, 'b'),
+            Timed::new(3, 'a'),
+        ]
+    );
+}
+
+#[test]
+fn timed_events_can_be_used_in_a_binary_heap() {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse(Timed::new(5, 1)));
+    heap.push(Reverse(Timed::new(2, 2)));
+    heap.push(Reverse(Timed::new(8, 3)));
+    let times: Vec<u32> = std::iter::from_fn(|| heap.pop())
+        .map(|Reverse(timed)| timed.time_in_frames)
+        .collect();
+    assert_eq!(times, vec![2, 5, 8]);
+}
+
+#[test]
+fn timed_events_can_use_a_different_time_type() {
+    let mut events = vec![
+        Timed {
+            time_in_frames: 5_000_000_000_u64,
+            event: 'b',
+        },
+        Timed {
+            time_in_frames: 1,
+            event: 'a',
+        },
+    ];
+    events.sort();
+    assert_eq!(events[0].event, 'a');
+    assert_eq!(events[1].time_in_frames, 5_000_000_000);
+
+    let early = Timed {
+        time_in_frames: 0.25,
+        event: 'x',
+    };
+    let late = Timed {
+        time_in_frames: 0.75,
+        event: 'x',
+    };
+    assert!(early < late);
+}
 
-impl<E> AsRef<E> for Timed<E> {
+impl<E, T> AsRef<E> for Timed<E, T> {
     fn as_ref(&self) -> &E {
         &self.event
     }
 }
 
-impl<E> AsMut<E> for Timed<E> {
+impl<E, T> AsMut<E> for Timed<E, T> {
     fn as_mut(&mut self) -> &mut E {
         &mut self.event
     }
@@ -405,12 +1682,174 @@ impl<E> AsMut<E> for Indexed<E> {
     }
 }
 
+/// The number of microseconds in one second.
+pub const MICROSECONDS_PER_SECOND: u64 = 1_000_000;
+
+/// An event with a timing that is relative to the previous event, in microseconds.
+///
+/// See the [`to_timed`] and [`from_timed`] methods for converting from and to
+/// a `Timed` event, whose timing is in frames, relative to the start of a buffer.
+///
+/// [`to_timed`]: #method.to_timed
+/// [`from_timed`]: #method.from_timed
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct DeltaEvent<E> {
     pub microseconds_since_previous_event: u64,
     pub event: E,
 }
 
+impl<E> DeltaEvent<E> {
+    /// Return the time of the event in frames, counted from the start of the event stream
+    /// (rounded down).
+    ///
+    /// `running_time_in_microseconds` is the time of the previous event in microseconds,
+    /// counted from the start of the event stream (`0` for the first event).
+    /// It is updated to the time of this event, so that it can be passed for the next event.
+    pub fn absolute_time_in_frames(
+        &self,
+        frames_per_second: u64,
+        running_time_in_microseconds: &mut u64,
+    ) -> u64 {
+        *running_time_in_microseconds += self.microseconds_since_previous_event;
+        *running_time_in_microseconds * frames_per_second / MICROSECONDS_PER_SECOND
+    }
+
+    /// Convert to a `Timed` event whose timing is relative to the start of the buffer that
+    /// starts at frame `buffer_start_in_frames` (counted from the start of the event stream).
+    ///
+    /// `running_time_in_microseconds` is used as in [`absolute_time_in_frames`].
+    ///
+    /// Panics
+    /// ======
+    /// Panics if the event happens before `buffer_start_in_frames` or more than
+    /// `u32::MAX` frames after it.
+    ///
+    /// [`absolute_time_in_frames`]: #method.absolute_time_in_frames
+    pub fn to_timed(
+        self,
+        frames_per_second: u64,
+        running_time_in_microseconds: &mut u64,
+        buffer_start_in_frames: u64,
+    ) -> Timed<E> {
+        let time_in_frames =
+            self.absolute_time_in_frames(frames_per_second, running_time_in_microseconds);
+        assert!(
+            time_in_frames >= buffer_start_in_frames,
+            "The event happens before the start of the buffer."
+        );
+        let time_in_frames = time_in_frames - buffer_start_in_frames;
+        assert!(time_in_frames <= u32::MAX as u64);
+        Timed {
+            time_in_frames: time_in_frames as u32,
+            event: self.event,
+        }
+    }
+
+    /// Convert a `Timed` event, whose timing is relative to the start of the buffer that
+    /// starts at frame `buffer_start_in_frames`, to a `DeltaEvent`.
+    /// This is the inverse of [`to_timed`], up to rounding.
+    ///
+    /// `running_time_in_microseconds` is the time of the previous event in microseconds,
+    /// counted from the start of the event stream (`0` for the first event).
+    /// It is updated to the time of this event, so that it can be passed for the next event.
+    ///
+    /// Panics
+    /// ======
+    /// Panics if the event happens before the previous event.
+    ///
+    /// [`to_timed`]: #method.to_timed
+    pub fn from_timed(
+        timed: Timed<E>,
+        frames_per_second: u64,
+        running_time_in_microseconds: &mut u64,
+        buffer_start_in_frames: u64,
+    ) -> Self {
+        let time_in_frames = buffer_start_in_frames + timed.time_in_frames as u64;
+        let time_in_microseconds = time_in_frames * MICROSECONDS_PER_SECOND / frames_per_second;
+        assert!(
+            time_in_microseconds >= *running_time_in_microseconds,
+            "The event happens before the previous event."
+        );
+        let microseconds_since_previous_event =
+            time_in_microseconds - *running_time_in_microseconds;
+        *running_time_in_microseconds = time_in_microseconds;
+        DeltaEvent {
+            microseconds_since_previous_event,
+            event: timed.event,
+        }
+    }
+}
+
+#[test]
+fn delta_events_are_converted_to_timed_events_relative_to_the_buffer() {
+    const FRAMES_PER_SECOND: u64 = 8000; // 1 frame is 125 microseconds.
+    let mut running_time_in_microseconds = 0;
+    let first = DeltaEvent {
+        microseconds_since_previous_event: 1000,
+        event: 'a',
+    };
+    assert_eq!(
+        first.to_timed(FRAMES_PER_SECOND, &mut running_time_in_microseconds, 6),
+        Timed::new(2, 'a')
+    );
+    assert_eq!(running_time_in_microseconds, 1000);
+    let second = DeltaEvent {
+        microseconds_since_previous_event: 1200,
+        event: 'b',
+    };
+    // 2200 microseconds is 17.6 frames, which is rounded down.
+    assert_eq!(
+        second.to_timed(FRAMES_PER_SECOND, &mut running_time_in_microseconds, 15),
+        Timed::new(2, 'b')
+    );
+}
+
+#[test]
+fn timed_events_are_converted_to_delta_events() {
+    const FRAMES_PER_SECOND: u64 = 8000; // 1 frame is 125 microseconds.
+    let mut running_time_in_microseconds = 0;
+    assert_eq!(
+        DeltaEvent::from_timed(
+            Timed::new(2, 'a'),
+            FRAMES_PER_SECOND,
+            &mut running_time_in_microseconds,
+            6
+        ),
+        DeltaEvent {
+            microseconds_since_previous_event: 1000,
+            event: 'a'
+        }
+    );
+    assert_eq!(
+        DeltaEvent::from_timed(
+            Timed::new(0, 'b'),
+            FRAMES_PER_SECOND,
+            &mut running_time_in_microseconds,
+            9
+        ),
+        DeltaEvent {
+            microseconds_since_previous_event: 125,
+            event: 'b'
+        }
+    );
+}
+
+#[test]
+fn delta_event_round_trips_through_timed_event() {
+    const FRAMES_PER_SECOND: u64 = 44100;
+    const BUFFER_START: u64 = 44032;
+    let delta_event = DeltaEvent {
+        microseconds_since_previous_event: 1_000_000,
+        event: (),
+    };
+    let timed = delta_event.to_timed(FRAMES_PER_SECOND, &mut 0, BUFFER_START);
+    assert_eq!(timed, Timed::new(68, ()));
+    assert_eq!(
+        DeltaEvent::from_timed(timed, FRAMES_PER_SECOND, &mut 0, BUFFER_START),
+        delta_event
+    );
+}
+
 /// Stretch integer (`u64`) time stamps by a fractional factor that may change over time.
 pub struct TimeStretcher {
     nominator: u64,
@@ -0,0 +1,183 @@
+//! Record the events that an event handler receives, e.g. for regression tests.
+use super::{ContextualEventHandler, EventHandler};
+use crate::buffer::AudioBufferInOut;
+use crate::{AudioHandler, AudioHandlerMeta, AudioRenderer, ContextualAudioRenderer};
+
+/// An event handler that records a copy of every event it receives before letting the inner
+/// event handler handle it.
+///
+/// This allows to check the exact sequence of events (including their timing, for [`Timed`]
+/// events) that a plugin receives.
+/// When the `serde` feature is enabled, `Timed` and [`RawMidiEvent`] can be serialized,
+/// so that the recorded events can be compared with a "golden file".
+///
+/// `RecordingEventHandler` implements both [`EventHandler`] and [`ContextualEventHandler`],
+/// depending on what the inner event handler implements.
+/// The audio related traits are forwarded to the inner event handler, so that
+/// `RecordingEventHandler` can be used to wrap a complete plugin.
+///
+/// # Example
+/// ```
+/// use rsynth::event::recording::RecordingEventHandler;
+/// use rsynth::event::{EventHandler, Timed};
+///
+/// struct Plugin;
+/// impl EventHandler<Timed<char>> for Plugin {
+///     fn handle_event(&mut self, _event: Timed<char>) {}
+/// }
+///
+/// let mut recorder = RecordingEventHandler::new(Plugin);
+/// recorder.handle_event(Timed::new(2, 'a'));
+/// recorder.handle_event(Timed::new(5, 'b'));
+/// assert_eq!(recorder.recorded(), &[Timed::new(2, 'a'), Timed::new(5, 'b')]);
+/// ```
+///
+/// # Note about using in a real-time context
+/// Recording an event may allocate memory.
+///
+/// [`Timed`]: ../struct.Timed.html
+/// [`RawMidiEvent`]: ../struct.RawMidiEvent.html
+/// [`EventHandler`]: ../trait.EventHandler.html
+/// [`ContextualEventHandler`]: ../trait.ContextualEventHandler.html
+pub struct RecordingEventHandler<H, E> {
+    inner: H,
+    recorded: Vec<E>,
+}
+
+impl<H, E> RecordingEventHandler<H, E> {
+    /// Create a new `RecordingEventHandler` that has not recorded any events yet.
+    pub fn new(inner: H) -> Self {
+        Self {
+            inner,
+            recorded: Vec::new(),
+        }
+    }
+
+    /// The events that have been recorded so far, in the order in which they were received.
+    pub fn recorded(&self) -> &[E] {
+        &self.recorded
+    }
+
+    /// Forget the events that have been recorded so far.
+    pub fn clear(&mut self) {
+        self.recorded.clear();
+    }
+
+    /// Get a reference to the inner event handler.
+    pub fn inner(&self) -> &H {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner event handler.
+    pub fn inner_mut(&mut self) -> &mut H {
+        &mut self.inner
+    }
+
+    /// Return the inner event handler and the events that have been recorded.
+    pub fn into_parts(self) -> (H, Vec<E>) {
+        (self.inner, self.recorded)
+    }
+}
+
+impl<H, E> EventHandler<E> for RecordingEventHandler<H, E>
+where
+    H: EventHandler<E>,
+    E: Clone,
+{
+    fn handle_event(&mut self, event: E) {
+        self.recorded.push(event.clone());
+        self.inner.handle_event(event);
+    }
+}
+
+impl<H, E, C> ContextualEventHandler<E, C> for RecordingEventHandler<H, E>
+where
+    H: ContextualEventHandler<E, C>,
+    E: Clone,
+{
+    fn handle_event(&mut self, event: E, context: &mut C) {
+        self.recorded.push(event.clone());
+        self.inner.handle_event(event, context);
+    }
+}
+
+impl<H, E> AudioHandlerMeta for RecordingEventHandler<H, E>
+where
+    H: AudioHandlerMeta,
+{
+    fn max_number_of_audio_inputs(&self) -> usize {
+        self.inner.max_number_of_audio_inputs()
+    }
+    fn max_number_of_audio_outputs(&self) -> usize {
+        self.inner.max_number_of_audio_outputs()
+    }
+}
+
+impl<H, E> AudioHandler for RecordingEventHandler<H, E>
+where
+    H: AudioHandler,
+{
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.inner.set_sample_rate(sample_rate);
+    }
+}
+
+impl<H, E, S> AudioRenderer<S> for RecordingEventHandler<H, E>
+where
+    H: AudioRenderer<S>,
+    S: Copy,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>) {
+        self.inner.render_buffer(buffer);
+    }
+}
+
+impl<H, E, S, C> ContextualAudioRenderer<S, C> for RecordingEventHandler<H, E>
+where
+    H: ContextualAudioRenderer<S, C>,
+    S: Copy,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>, context: &mut C) {
+        self.inner.render_buffer(buffer, context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordingEventHandler;
+    use crate::event::{ContextualEventHandler, RawMidiEvent, Timed};
+
+    // Counts the note on events and sends every event to the context.
+    struct NoteCounter {
+        number_of_notes: usize,
+    }
+
+    impl ContextualEventHandler<Timed<RawMidiEvent>, Vec<RawMidiEvent>> for NoteCounter {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>, context: &mut Vec<RawMidiEvent>) {
+            if event.event.data()[0] & 0xF0 == 0x90 {
+                self.number_of_notes += 1;
+            }
+            context.push(event.event);
+        }
+    }
+
+    #[test]
+    fn recorded_events_match_the_events_that_were_fed_and_are_handled_by_the_inner_handler() {
+        let events = vec![
+            Timed::new(0, RawMidiEvent::new(&[0x90, 60, 100])),
+            Timed::new(0, RawMidiEvent::new(&[0x90, 64, 100])),
+            Timed::new(7, RawMidiEvent::new(&[0xB0, 7, 90])),
+            Timed::new(12, RawMidiEvent::new(&[0x80, 60, 0])),
+        ];
+        let mut recorder = RecordingEventHandler::new(NoteCounter { number_of_notes: 0 });
+        let mut context = Vec::new();
+        for event in events.iter() {
+            recorder.handle_event(*event, &mut context);
+        }
+        assert_eq!(recorder.recorded(), &events[..]);
+        assert_eq!(recorder.inner().number_of_notes, 2);
+        assert_eq!(context, events.iter().map(|e| e.event).collect::<Vec<_>>());
+        recorder.clear();
+        assert!(recorder.recorded().is_empty());
+    }
+}
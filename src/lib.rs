@@ -12,6 +12,9 @@
 //! * [`vst`] (behind the `backend-vst` feature)
 //! * [`combined`] combine different back-ends for audio input, audio output, midi input and
 //!     midi output, mostly for offline rendering and testing (behind various features)
+//! * [`cpal`] real-time audio output without a JACK server (behind the `backend-cpal` feature)
+//! * [`alsa`] real-time audio output and midi input on Linux without a JACK server
+//!     (behind the `backend-alsa` feature)
 //!
 //! See the documentation of each back-end for more information.
 //!
@@ -27,6 +30,8 @@
 //! * Jack: [`run()`](./backend/jack_backend/fn.run.html)
 //! * Offline : [`run()`](backend/combined/fn.run.html)
 //! * VST 2.4:  [`vst_init!`]
+//! * Cpal: [`run()`](./backend/cpal_backend/fn.run.html)
+//! * Alsa: [`run()`](./backend/alsa_backend/fn.run.html)
 //!
 //! ### Meta-data
 //! There are a number of traits that an application or plugin needs to implement in order to define
@@ -75,7 +80,7 @@
 //! **Rendering audio offline**
 //!
 //! Applications need to implement
-//! * [`ContextualAudioRenderer`]`<S, `[`MidiWriterWrapper`]`<`[`Timed`]`<`[`RawMidiEvent`]`>>>` Note: the type parameter `S`, which represents the sample data type, is free.
+//! * [`ContextualAudioRenderer`]`<S, `[`OfflineHost`]`<W>>` (where `W` is the midi writer). Note: the type parameter `S`, which represents the sample data type, is free.
 //!
 //! **Rendering audio with VST 2.4**
 //!
@@ -133,7 +138,7 @@
 //!
 //! **Generating midi events with offline rendering**
 //!
-//! [`MidiWriterWrapper`] implements
+//! [`OfflineHost`] implements
 //! * [`EventHandler`]`<`[`Timed`]`<`[`RawMidiEvent`]`>>`
 //!
 //! **Generating midi events with VST 2.4 is not possible**
@@ -152,7 +157,7 @@
 //!
 //! **Stopping offline rendering**
 //!
-//! Stopping offline rendering is possible: [`MidiWriterWrapper`] implements the [`Stop`] trait.
+//! Stopping offline rendering is possible: [`OfflineHost`] implements the [`Stop`] trait.
 //! Additionally, offline rendering automatically stops when the [`fill_buffer`] method of the
 //! [`AudioReader`] indicates that no frames are to be expected anymore.
 //!
@@ -161,6 +166,8 @@
 //! [`jack`]: ./backend/jack_backend/index.html
 //! [`vst`]: ./backend/vst_backend/index.html
 //! [`combined`]: ./backend/combined/index.html
+//! [`cpal`]: ./backend/cpal_backend/index.html
+//! [`alsa`]: ./backend/alsa_backend/index.html
 //! [`EventHandler`]: ./event/trait.EventHandler.html
 //! [`RawMidiEvent`]: ./event/struct.RawMidiEvent.html
 //! [`SysExEvent`]: ./event/struct.SysExEvent.html
@@ -184,10 +191,9 @@
 //! [`HostInterface`]: ./backend/trait.HostInterface.html
 //! [`JackHost`]: ./backend/jack_backend/struct.JackHost.html
 //! [`AudioHandler`]: ./trait.AudioHandler.html
-//! [`MidiWriterWrapper`]: ./backend/combined/struct.MidiWriterWrapper.html
 //! [`CommonMidiPortMeta`]: ./trait.CommonMidiPortMeta.html
 //! [`VstPluginMeta`]: ./backend/vst_backend/trait.VstPluginMeta.html
-//! [`MidiWriterWrapper`]: ./backend/combined/struct.MidiWriterWrapper.html
+//! [`OfflineHost`]: ./backend/combined/struct.OfflineHost.html
 //! [`stop`]: ./backend/trait.HostInterface.html#method.stop
 //! [`Stop`]: ./backend/trait.Stop.html
 //! [`fill_buffer`]: ./backend/combined/trait.AudioReader.html#tymethod.fill_buffer
@@ -203,13 +209,27 @@ use std::fmt::{Error, Write};
 #[macro_use]
 pub mod buffer;
 pub mod backend;
+pub mod boxed;
+pub mod dsp;
 pub mod envelope;
 pub mod event;
 pub mod meta;
+pub mod middleware;
 pub mod test_utilities;
 pub mod utilities;
 
 /// Re-exports from the [`vecstorage`](https://crates.io/crates/vecstorage) crate.
+///
+/// _Note_: `VecStorage::vec_guard` panics if a previous guard has not been dropped
+/// (e.g. because it was passed to `std::mem::forget`).
+/// The backends in `rsynth` always drop the guard before the end of the audio callback,
+/// so this cannot happen there. A non-panicking `try_vec_guard` would need to be added to
+/// the `vecstorage` crate itself; `VecStorage` is no longer defined in `rsynth`.
+///
+/// Pushing more items than the capacity of the `VecStorage` allocates memory.
+/// To keep the audio callback free of allocations, create the `VecStorage` with
+/// `VecStorage::with_capacity` and a capacity for the maximum number of channels,
+/// as the backends do, and do not push more items than that.
 pub mod vecstorage {
     pub use vecstorage::VecStorage;
 }
@@ -327,6 +347,15 @@ pub trait CommonPluginMeta {
     fn plugin_name<W: std::fmt::Write>(&self, buffer: &mut W) -> Result<(), std::fmt::Error> {
         buffer.write_str(self.name())
     }
+
+    /// The latency of the plugin, in frames: the number of frames by which the output
+    /// of the plugin lags behind its input.
+    /// Backends report this to the host, so that it can compensate for it.
+    ///
+    /// The default implementation returns `0`.
+    fn latency_in_frames(&self) -> u32 {
+        0
+    }
 }
 
 /// Provides some meta-data of the audio-ports used by the plugin or application to the host.
@@ -481,6 +510,10 @@ where
     fn plugin_name<W: std::fmt::Write>(&self, buffer: &mut W) -> Result<(), Error> {
         self.meta().general().write_name(buffer)
     }
+
+    fn latency_in_frames(&self) -> u32 {
+        self.meta().general().latency_in_frames()
+    }
 }
 
 impl<T> AudioHandlerMeta for T
@@ -55,9 +55,18 @@
 //! Now the `MetaData<G, _, _>` struct implements `General` with associated type
 //! `General::GeneralData` equal to `G`.
 //! Also, `Name` is implemented for `String` and for `&'static str`.
+//! Use [`DynamicName`] for a name that is computed at run-time (e.g. "Synth #3").
 //! So if a plugin implements `Meta` with the associated type `Meta::MetaData` equal to the struct
 //! `MetaData<&'static str, _, _>`, then it automatically implements `CommonPluginMeta`.
+//!
+//! The latency of the plugin is taken from the `latency_in_frames` method of the `Name` trait,
+//! which returns 0 frames by default. Use the [`NameAndLatency`] struct as general meta-data
+//! for a plugin that has latency.
+//!
+//! [`NameAndLatency`]: ./struct.NameAndLatency.html
+//! [`DynamicName`]: ./struct.DynamicName.html
 
+use crate::{CommonAudioPortMeta, CommonMidiPortMeta, CommonPluginMeta};
 use std::fmt::Error;
 
 /// Define the meta-data for an application or plug-in.
@@ -103,6 +112,17 @@ pub trait Name {
     fn write_name<W: std::fmt::Write>(&self, buffer: &mut W) -> Result<(), std::fmt::Error> {
         buffer.write_str(self.name())
     }
+
+    /// The latency of the plugin, in frames, for meta-data that also contains the latency
+    /// (e.g. [`NameAndLatency`]).
+    /// This allows backends to get the latency from any meta-data that contains a name.
+    ///
+    /// The default implementation returns `0`.
+    ///
+    /// [`NameAndLatency`]: ./struct.NameAndLatency.html
+    fn latency_in_frames(&self) -> u32 {
+        0
+    }
 }
 
 impl Name for String {
@@ -125,6 +145,98 @@ impl Name for &'static str {
     }
 }
 
+/// A name that is computed when it is needed, e.g. from the state of the plugin instance.
+///
+/// The closure writes the name to the given buffer, so that no `String` needs to be stored.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::meta::{DynamicName, InOut, Meta, MetaData};
+/// use rsynth::CommonPluginMeta;
+/// use std::fmt::{Error, Write};
+///
+/// struct Synth<F> {
+///     meta: MetaData<DynamicName<F>, &'static str, &'static str>,
+/// }
+///
+/// impl<F> Meta for Synth<F> {
+///     type MetaData = MetaData<DynamicName<F>, &'static str, &'static str>;
+///     fn meta(&self) -> &Self::MetaData {
+///         &self.meta
+///     }
+/// }
+///
+/// let instance_number = 3;
+/// let synth = Synth {
+///     meta: MetaData {
+///         general_meta: DynamicName(move |buffer: &mut dyn Write| {
+///             write!(buffer, "Synth #{}", instance_number)
+///         }),
+///         audio_port_meta: InOut { inputs: vec![], outputs: vec!["out"] },
+///         midi_port_meta: InOut { inputs: vec!["in"], outputs: vec![] },
+///     },
+/// };
+/// let mut name = String::new();
+/// synth.plugin_name(&mut name).unwrap();
+/// assert_eq!(name, "Synth #3");
+/// ```
+pub struct DynamicName<F>(pub F);
+
+impl<F> Name for DynamicName<F>
+where
+    F: Fn(&mut dyn std::fmt::Write) -> Result<(), Error>,
+{
+    fn write_name<W: std::fmt::Write>(&self, buffer: &mut W) -> Result<(), Error> {
+        (self.0)(buffer)
+    }
+}
+
+/// General meta-data that consists of a name and a latency.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::meta::{InOut, MetaData, NameAndLatency};
+/// let meta: MetaData<NameAndLatency<&'static str>, &'static str, &'static str> = MetaData {
+///     general_meta: NameAndLatency {
+///         name: "Look-ahead limiter",
+///         latency_in_frames: 128,
+///     },
+///     audio_port_meta: InOut {
+///         inputs: vec!["in"],
+///         outputs: vec!["out"],
+///     },
+///     midi_port_meta: InOut {
+///         inputs: vec![],
+///         outputs: vec![],
+///     },
+/// };
+/// ```
+pub struct NameAndLatency<N> {
+    /// The name.
+    pub name: N,
+    /// The latency, in frames.
+    pub latency_in_frames: u32,
+}
+
+impl<N> Name for NameAndLatency<N>
+where
+    N: Name,
+{
+    fn name(&self) -> &str {
+        self.name.name()
+    }
+
+    fn write_name<W: std::fmt::Write>(&self, buffer: &mut W) -> Result<(), Error> {
+        self.name.write_name(buffer)
+    }
+
+    fn latency_in_frames(&self) -> u32 {
+        self.latency_in_frames
+    }
+}
+
 /// Define meta-data for input ports and output ports.
 ///
 /// The type parameter `T` is a dummy type parameter so that meta-data for different types of
@@ -248,3 +360,267 @@ impl<G, AP, MP> Port<MidiPort> for MetaData<G, AP, MP> {
         self.midi_port_meta.outputs.as_ref()
     }
 }
+
+/// A summary of the meta-data of a plugin: its name, its latency and the names of its ports.
+///
+/// The number of ports can be obtained from the lengths of the vectors with the port names.
+/// When the `serde` feature is enabled, this struct can be serialized, e.g. to JSON with the
+/// [`to_json`] method, so that tools can inspect the plugin without having to load it in a host.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::meta::{Meta, MetaData, InOut, PluginInfo};
+/// struct MyPlugin {
+///     meta: MetaData<&'static str, &'static str, &'static str>
+/// }
+///
+/// impl Meta for MyPlugin {
+///     type MetaData = MetaData<&'static str, &'static str, &'static str>;
+///     fn meta(&self) -> &Self::MetaData {
+///         &self.meta
+///     }
+/// }
+///
+/// let plugin = MyPlugin {
+///     meta: MetaData {
+///         general_meta: "my plugin",
+///         audio_port_meta: InOut {
+///             inputs: vec![],
+///             outputs: vec!["left", "right"],
+///         },
+///         midi_port_meta: InOut {
+///             inputs: vec!["midi in"],
+///             outputs: vec![],
+///         },
+///     }
+/// };
+/// let info = PluginInfo::from_plugin(&plugin).expect("Unexpected error");
+/// assert_eq!(info.name, "my plugin");
+/// assert_eq!(info.audio_outputs, vec!["left".to_string(), "right".to_string()]);
+/// ```
+///
+/// Note about using in a real-time context
+/// =======================================
+/// Creating a `PluginInfo` allocates memory.
+///
+/// [`to_json`]: #method.to_json
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde_1::Serialize))]
+#[cfg_attr(feature = "serde", serde(crate = "serde_1"))]
+pub struct PluginInfo {
+    /// The name of the plugin.
+    pub name: String,
+    /// The latency of the plugin, in frames.
+    pub latency_in_frames: u32,
+    /// The names of the audio inputs.
+    pub audio_inputs: Vec<String>,
+    /// The names of the audio outputs.
+    pub audio_outputs: Vec<String>,
+    /// The names of the midi inputs.
+    pub midi_inputs: Vec<String>,
+    /// The names of the midi outputs.
+    pub midi_outputs: Vec<String>,
+}
+
+impl PluginInfo {
+    /// Collect the meta-data of the given plugin.
+    pub fn from_plugin<P>(plugin: &P) -> Result<Self, Error>
+    where
+        P: CommonPluginMeta + CommonAudioPortMeta + CommonMidiPortMeta,
+    {
+        let mut audio_inputs = Vec::with_capacity(plugin.max_number_of_audio_inputs());
+        for index in 0..plugin.max_number_of_audio_inputs() {
+            let mut name = String::new();
+            CommonAudioPortMeta::input_name(plugin, &mut name, index)?;
+            audio_inputs.push(name);
+        }
+        let mut audio_outputs = Vec::with_capacity(plugin.max_number_of_audio_outputs());
+        for index in 0..plugin.max_number_of_audio_outputs() {
+            let mut name = String::new();
+            CommonAudioPortMeta::output_name(plugin, &mut name, index)?;
+            audio_outputs.push(name);
+        }
+        let mut midi_inputs = Vec::with_capacity(plugin.max_number_of_midi_inputs());
+        for index in 0..plugin.max_number_of_midi_inputs() {
+            let mut name = String::new();
+            CommonMidiPortMeta::input_name(plugin, &mut name, index)?;
+            midi_inputs.push(name);
+        }
+        let mut midi_outputs = Vec::with_capacity(plugin.max_number_of_midi_outputs());
+        for index in 0..plugin.max_number_of_midi_outputs() {
+            let mut name = String::new();
+            CommonMidiPortMeta::output_name(plugin, &mut name, index)?;
+            midi_outputs.push(name);
+        }
+        let mut name = String::new();
+        plugin.plugin_name(&mut name)?;
+        Ok(Self {
+            name,
+            latency_in_frames: plugin.latency_in_frames(),
+            audio_inputs,
+            audio_outputs,
+            midi_inputs,
+            midi_outputs,
+        })
+    }
+
+    /// Serialize the meta-data to JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, serde_json_1::Error> {
+        serde_json_1::to_string(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynamicName, InOut, Meta, MetaData, Name, NameAndLatency, PluginInfo};
+    use crate::CommonPluginMeta;
+    use std::fmt::Write;
+
+    struct SinePlugin {
+        meta: MetaData<&'static str, &'static str, &'static str>,
+    }
+
+    impl Meta for SinePlugin {
+        type MetaData = MetaData<&'static str, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    fn sine_plugin() -> SinePlugin {
+        SinePlugin {
+            meta: MetaData {
+                general_meta: "sine",
+                audio_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec!["left", "right"],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec!["midi in"],
+                    outputs: vec![],
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn plugin_info_contains_the_port_names() {
+        let info = PluginInfo::from_plugin(&sine_plugin()).expect("Unexpected error");
+        assert_eq!(
+            info,
+            PluginInfo {
+                name: "sine".to_string(),
+                latency_in_frames: 0,
+                audio_inputs: vec![],
+                audio_outputs: vec!["left".to_string(), "right".to_string()],
+                midi_inputs: vec!["midi in".to_string()],
+                midi_outputs: vec![],
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn plugin_info_can_be_serialized_to_json() {
+        let json = PluginInfo::from_plugin(&sine_plugin())
+            .expect("Unexpected error")
+            .to_json()
+            .expect("Unexpected error");
+        assert!(json.contains(r#""name":"sine""#));
+        assert!(json.contains(r#""audio_outputs":["left","right"]"#));
+        assert!(json.contains(r#""midi_inputs":["midi in"]"#));
+    }
+
+    type NumberedName =
+        NameAndLatency<DynamicName<Box<dyn Fn(&mut dyn Write) -> std::fmt::Result>>>;
+
+    struct NumberedPlugin {
+        meta: MetaData<NumberedName, &'static str, &'static str>,
+    }
+
+    impl Meta for NumberedPlugin {
+        type MetaData = MetaData<NumberedName, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    fn numbered_plugin(instance_number: usize) -> NumberedPlugin {
+        NumberedPlugin {
+            meta: MetaData {
+                general_meta: NameAndLatency {
+                    name: DynamicName(Box::new(move |buffer: &mut dyn Write| {
+                        write!(buffer, "Synth #{}", instance_number)
+                    })),
+                    latency_in_frames: 64,
+                },
+                audio_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec!["out"],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec![],
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn latency_of_the_plugin_is_the_latency_of_the_general_meta_data() {
+        assert_eq!(sine_plugin().latency_in_frames(), 0);
+        assert_eq!(numbered_plugin(1).latency_in_frames(), 64);
+    }
+
+    #[test]
+    fn dynamic_name_is_computed_per_instance() {
+        let first = PluginInfo::from_plugin(&numbered_plugin(1)).expect("Unexpected error");
+        let third = PluginInfo::from_plugin(&numbered_plugin(3)).expect("Unexpected error");
+        assert_eq!(first.name, "Synth #1");
+        assert_eq!(third.name, "Synth #3");
+        assert_eq!(third.latency_in_frames, 64);
+    }
+
+    // A name type that does not override `latency_in_frames`.
+    struct CustomName;
+
+    impl Name for CustomName {
+        fn write_name<W: Write>(&self, buffer: &mut W) -> std::fmt::Result {
+            buffer.write_str("custom")
+        }
+    }
+
+    struct CustomNamePlugin {
+        meta: MetaData<CustomName, &'static str, &'static str>,
+    }
+
+    impl Meta for CustomNamePlugin {
+        type MetaData = MetaData<CustomName, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    #[test]
+    fn name_without_latency_has_no_latency() {
+        let plugin = CustomNamePlugin {
+            meta: MetaData {
+                general_meta: CustomName,
+                audio_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec![],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec![],
+                    outputs: vec![],
+                },
+            },
+        };
+        let mut name = String::new();
+        plugin.plugin_name(&mut name).expect("Unexpected error");
+        assert_eq!(name, "custom");
+        assert_eq!(plugin.latency_in_frames(), 0);
+    }
+}
@@ -0,0 +1,188 @@
+//! Route midi events by channel, e.g. in a multi-timbral setup.
+//!
+//! [`ChannelFilter`] wraps a plugin and only forwards the channel voice messages on the
+//! configured channels, optionally moving them to another channel.
+//!
+//! [`ChannelFilter`]: ./struct.ChannelFilter.html
+use crate::event::{ContextualEventHandler, EventHandler, RawMidiEvent, Timed};
+use midi_consts::channel_event::EVENT_TYPE_MASK;
+
+/// A channel mask with all 16 midi channels.
+pub const ALL_CHANNELS: u16 = 0xFFFF;
+const MAX_CHANNEL: u8 = 0x0F;
+
+/// Middleware that only forwards the channel voice messages (e.g. note on, control change)
+/// on the configured channels and optionally changes their channel.
+///
+/// Other messages (e.g. system exclusive and timing clock messages) are always forwarded
+/// unchanged.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::middleware::channel_filter::ChannelFilter;
+/// # struct MyPlugin;
+/// # let my_plugin = MyPlugin;
+///
+/// // Only forward the events on channel 2 and 3 and move them to channel 0.
+/// let plugin = ChannelFilter::new(my_plugin)
+///     .with_channel_mask(0b1100)
+///     .with_remap_to(0);
+/// ```
+pub struct ChannelFilter<P> {
+    plugin: P,
+    channel_mask: u16,
+    target_channel: Option<u8>,
+}
+
+impl<P> ChannelFilter<P> {
+    /// Create a new `ChannelFilter` that forwards the events on all channels, unchanged.
+    pub fn new(plugin: P) -> Self {
+        Self {
+            plugin,
+            channel_mask: ALL_CHANNELS,
+            target_channel: None,
+        }
+    }
+
+    /// Only forward the events on the channels in the mask: the events on channel `c`
+    /// (in the range 0-15) are forwarded if bit `c` of `channel_mask` is set.
+    pub fn with_channel_mask(self, channel_mask: u16) -> Self {
+        Self {
+            channel_mask,
+            ..self
+        }
+    }
+
+    /// Only forward the events on the given channel (in the range 0-15).
+    ///
+    /// # Panics
+    /// Panics if `channel` is not in the range 0-15.
+    pub fn with_only_channel(self, channel: u8) -> Self {
+        assert!(channel <= MAX_CHANNEL);
+        self.with_channel_mask(1 << channel)
+    }
+
+    /// Move the forwarded events to the given channel (in the range 0-15).
+    ///
+    /// # Panics
+    /// Panics if `channel` is not in the range 0-15.
+    pub fn with_remap_to(self, channel: u8) -> Self {
+        assert!(channel <= MAX_CHANNEL);
+        Self {
+            target_channel: Some(channel),
+            ..self
+        }
+    }
+
+    // Return the event that should be forwarded, if any.
+    fn filter(&self, event: RawMidiEvent) -> Option<RawMidiEvent> {
+        let channel = match event.channel() {
+            Some(channel) => channel,
+            None => return Some(event),
+        };
+        if self.channel_mask & (1 << channel) == 0 {
+            return None;
+        }
+        match self.target_channel {
+            Some(target_channel) => {
+                let mut data = *event.data();
+                data[0] = (data[0] & EVENT_TYPE_MASK) | target_channel;
+                Some(RawMidiEvent::new(&data[..event.bytes().len()]))
+            }
+            None => Some(event),
+        }
+    }
+}
+
+impl<P> EventHandler<Timed<RawMidiEvent>> for ChannelFilter<P>
+where
+    P: EventHandler<Timed<RawMidiEvent>>,
+{
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+        if let Some(filtered) = self.filter(event.event) {
+            self.plugin
+                .handle_event(Timed::new(event.time_in_frames, filtered));
+        }
+    }
+}
+
+impl<P, C> ContextualEventHandler<Timed<RawMidiEvent>, C> for ChannelFilter<P>
+where
+    P: ContextualEventHandler<Timed<RawMidiEvent>, C>,
+{
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>, context: &mut C) {
+        if let Some(filtered) = self.filter(event.event) {
+            self.plugin
+                .handle_event(Timed::new(event.time_in_frames, filtered), context);
+        }
+    }
+}
+
+middleware_impl!(ChannelFilter<P>);
+forward_audio_renderer!(ChannelFilter<P>);
+
+#[cfg(test)]
+mod tests {
+    use super::ChannelFilter;
+    use crate::event::recording::RecordingEventHandler;
+    use crate::event::{EventHandler, RawMidiEvent, Timed};
+    use crate::test_utilities::DummyEventHandler;
+
+    type Recorder = RecordingEventHandler<DummyEventHandler, Timed<RawMidiEvent>>;
+
+    fn forwarded(mut filter: ChannelFilter<Recorder>, events: &[&[u8]]) -> Vec<Vec<u8>> {
+        for (time, event) in events.iter().enumerate() {
+            filter.handle_event(Timed::new(time as u32, RawMidiEvent::new(event)));
+        }
+        filter
+            .inner()
+            .recorded()
+            .iter()
+            .map(|event| event.event.bytes().to_vec())
+            .collect()
+    }
+
+    fn recorder() -> Recorder {
+        RecordingEventHandler::new(DummyEventHandler)
+    }
+
+    #[test]
+    fn only_events_on_the_channels_in_the_mask_are_forwarded() {
+        let filter = ChannelFilter::new(recorder()).with_channel_mask(0b101);
+        assert_eq!(
+            forwarded(
+                filter,
+                &[
+                    &[0x90, 60, 100],
+                    &[0x91, 61, 100],
+                    &[0xB2, 7, 100],
+                    &[0x83, 60, 0]
+                ]
+            ),
+            vec![vec![0x90, 60, 100], vec![0xB2, 7, 100]]
+        );
+    }
+
+    #[test]
+    fn forwarded_events_are_moved_to_the_target_channel() {
+        let filter = ChannelFilter::new(recorder())
+            .with_only_channel(9)
+            .with_remap_to(1);
+        assert_eq!(
+            forwarded(filter, &[&[0x99, 36, 100], &[0xC9, 5], &[0x90, 60, 100]]),
+            vec![vec![0x91, 36, 100], vec![0xC1, 5]]
+        );
+    }
+
+    #[test]
+    fn messages_without_channel_are_forwarded_unchanged() {
+        let filter = ChannelFilter::new(recorder())
+            .with_channel_mask(0)
+            .with_remap_to(1);
+        assert_eq!(
+            forwarded(filter, &[&[0xF8], &[0xF2, 1, 2], &[0x90, 60, 100]]),
+            vec![vec![0xF8], vec![0xF2, 1, 2]]
+        );
+    }
+}
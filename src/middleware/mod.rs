@@ -0,0 +1,211 @@
+//! Middleware: wrappers around a plugin that change the events that the plugin receives
+//! or check the audio that it renders.
+//!
+//! Middleware forwards the meta-data of the wrapped plugin (the name, the latency, the ports
+//! and the VST-specific meta-data) and implements the event handling and audio rendering
+//! traits of the wrapped plugin, so that different middleware can be combined.
+//! Middleware can be passed to a backend instead of the plugin itself when it handles all
+//! the event types that the backend sends to the plugin:
+//! [`SanityCheck`] forwards all events, so it can be used with every backend.
+//! The other middleware only handle `Timed<RawMidiEvent>`, so they can be used with
+//! e.g. the [`run`] function of the combined backend, but not with the jack or the vst backend,
+//! which also send system exclusive events.
+//!
+//! * [`SanityCheck`]: check the output of the plugin for NaN, infinite or out-of-range samples
+//! * [`Quantize`]: snap the timing of the midi events to a rhythmic grid
+//!   (behind the "event-queue" feature)
+//! * [`ChannelFilter`]: only forward the midi events on some channels, optionally moving them
+//!   to another channel
+//! * [`VelocityCurve`]: change the velocity of the note on events
+//!
+//! [`SanityCheck`]: ./sanity_check/struct.SanityCheck.html
+//! [`Quantize`]: ./quantize/struct.Quantize.html
+//! [`ChannelFilter`]: ./channel_filter/struct.ChannelFilter.html
+//! [`VelocityCurve`]: ./velocity_curve/struct.VelocityCurve.html
+//! [`run`]: ../backend/combined/fn.run.html
+
+// Implement `inner`, `inner_mut` and `into_inner` for middleware that stores the wrapped plugin
+// in the field `plugin`, and forward `AudioHandler` and the meta-data traits to the wrapped plugin.
+macro_rules! middleware_impl {
+    ($middleware:ident<P $(, $param:ident)*>) => {
+        impl<P $(, $param)*> $middleware<P $(, $param)*> {
+            /// Get a reference to the wrapped plugin.
+            pub fn inner(&self) -> &P {
+                &self.plugin
+            }
+
+            /// Get a mutable reference to the wrapped plugin.
+            pub fn inner_mut(&mut self) -> &mut P {
+                &mut self.plugin
+            }
+
+            /// Get the wrapped plugin back.
+            pub fn into_inner(self) -> P {
+                self.plugin
+            }
+        }
+
+        impl<P $(, $param)*> $crate::AudioHandler for $middleware<P $(, $param)*>
+        where
+            P: $crate::AudioHandler,
+        {
+            fn set_sample_rate(&mut self, sample_rate: f64) {
+                self.plugin.set_sample_rate(sample_rate)
+            }
+        }
+
+        impl<P $(, $param)*> $crate::AudioHandlerMeta for $middleware<P $(, $param)*>
+        where
+            P: $crate::AudioHandlerMeta,
+        {
+            fn max_number_of_audio_inputs(&self) -> usize {
+                self.plugin.max_number_of_audio_inputs()
+            }
+
+            fn max_number_of_audio_outputs(&self) -> usize {
+                self.plugin.max_number_of_audio_outputs()
+            }
+        }
+
+        impl<P $(, $param)*> $crate::MidiHandlerMeta for $middleware<P $(, $param)*>
+        where
+            P: $crate::MidiHandlerMeta,
+        {
+            fn max_number_of_midi_inputs(&self) -> usize {
+                self.plugin.max_number_of_midi_inputs()
+            }
+
+            fn max_number_of_midi_outputs(&self) -> usize {
+                self.plugin.max_number_of_midi_outputs()
+            }
+        }
+
+        impl<P $(, $param)*> $crate::CommonPluginMeta for $middleware<P $(, $param)*>
+        where
+            P: $crate::CommonPluginMeta,
+        {
+            fn plugin_name<W: std::fmt::Write>(
+                &self,
+                buffer: &mut W,
+            ) -> Result<(), std::fmt::Error> {
+                self.plugin.plugin_name(buffer)
+            }
+
+            fn latency_in_frames(&self) -> u32 {
+                self.plugin.latency_in_frames()
+            }
+        }
+
+        impl<P $(, $param)*> $crate::CommonAudioPortMeta for $middleware<P $(, $param)*>
+        where
+            P: $crate::CommonAudioPortMeta,
+        {
+            fn input_name<W: std::fmt::Write>(
+                &self,
+                buffer: &mut W,
+                index: usize,
+            ) -> Result<(), std::fmt::Error> {
+                $crate::CommonAudioPortMeta::input_name(&self.plugin, buffer, index)
+            }
+
+            fn output_name<W: std::fmt::Write>(
+                &self,
+                buffer: &mut W,
+                index: usize,
+            ) -> Result<(), std::fmt::Error> {
+                $crate::CommonAudioPortMeta::output_name(&self.plugin, buffer, index)
+            }
+        }
+
+        impl<P $(, $param)*> $crate::CommonMidiPortMeta for $middleware<P $(, $param)*>
+        where
+            P: $crate::CommonMidiPortMeta,
+        {
+            fn input_name<W: std::fmt::Write>(
+                &self,
+                buffer: &mut W,
+                index: usize,
+            ) -> Result<(), std::fmt::Error> {
+                $crate::CommonMidiPortMeta::input_name(&self.plugin, buffer, index)
+            }
+
+            fn output_name<W: std::fmt::Write>(
+                &self,
+                buffer: &mut W,
+                index: usize,
+            ) -> Result<(), std::fmt::Error> {
+                $crate::CommonMidiPortMeta::output_name(&self.plugin, buffer, index)
+            }
+        }
+
+        #[cfg(feature = "backend-vst")]
+        impl<P $(, $param)*> $crate::backend::vst_backend::VstPluginMeta
+            for $middleware<P $(, $param)*>
+        where
+            P: $crate::backend::vst_backend::VstPluginMeta,
+        {
+            fn plugin_id(&self) -> i32 {
+                self.plugin.plugin_id()
+            }
+
+            fn category(&self) -> $crate::backend::vst_backend::vst::plugin::Category {
+                self.plugin.category()
+            }
+
+            fn parameters(
+                &self,
+            ) -> Option<std::sync::Arc<dyn $crate::backend::vst_backend::VstParameters>> {
+                self.plugin.parameters()
+            }
+
+            fn suspend(&mut self) {
+                self.plugin.suspend()
+            }
+
+            fn resume(&mut self) {
+                self.plugin.resume()
+            }
+
+            fn tail_size_in_frames(&self) -> Option<usize> {
+                self.plugin.tail_size_in_frames()
+            }
+        }
+    };
+}
+
+// Forward `AudioRenderer` and `ContextualAudioRenderer` to the wrapped plugin,
+// for middleware that does not change the audio.
+macro_rules! forward_audio_renderer {
+    ($middleware:ident<P $(, $param:ident)*>) => {
+        impl<P, S $(, $param)*> $crate::AudioRenderer<S> for $middleware<P $(, $param)*>
+        where
+            P: $crate::AudioRenderer<S>,
+            S: Copy,
+        {
+            fn render_buffer(&mut self, buffer: &mut $crate::buffer::AudioBufferInOut<S>) {
+                self.plugin.render_buffer(buffer)
+            }
+        }
+
+        impl<P, S, C $(, $param)*> $crate::ContextualAudioRenderer<S, C>
+            for $middleware<P $(, $param)*>
+        where
+            P: $crate::ContextualAudioRenderer<S, C>,
+            S: Copy,
+        {
+            fn render_buffer(
+                &mut self,
+                buffer: &mut $crate::buffer::AudioBufferInOut<S>,
+                context: &mut C,
+            ) {
+                self.plugin.render_buffer(buffer, context)
+            }
+        }
+    };
+}
+
+pub mod channel_filter;
+#[cfg(feature = "event-queue")]
+pub mod quantize;
+pub mod sanity_check;
+pub mod velocity_curve;
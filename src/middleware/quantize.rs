@@ -0,0 +1,240 @@
+//! Snap the timing of incoming midi events to a rhythmic grid.
+//!
+//! [`Quantize`] wraps a plugin and rounds the time of every incoming midi event to the nearest
+//! point on the grid before forwarding it to the wrapped plugin.
+//!
+//! [`Quantize`]: ./struct.Quantize.html
+use crate::buffer::AudioBufferInOut;
+use crate::event::{ContextualEventHandler, EventHandler, RawMidiEvent, Timed};
+use crate::{AudioRenderer, ContextualAudioRenderer};
+use event_queue::{AlwaysInsertNewAfterOld, EventQueue};
+
+const SECONDS_PER_MINUTE: f64 = 60.0;
+
+// The maximum number of midi events that can wait to be forwarded.
+const EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// Compute the grid size in frames for the given tempo (in beats per minute) and number of grid
+/// points per beat (e.g. `4` for sixteenth notes in a 4/4 time signature), rounded to the
+/// nearest frame.
+pub fn grid_size_in_frames(
+    beats_per_minute: f64,
+    grid_points_per_beat: u32,
+    sample_rate: f64,
+) -> u32 {
+    let seconds_per_grid_point =
+        SECONDS_PER_MINUTE / (beats_per_minute * grid_points_per_beat as f64);
+    (seconds_per_grid_point * sample_rate).round() as u32
+}
+
+/// Middleware that rounds the time of every incoming midi event to the nearest point on a grid.
+///
+/// The grid starts at the first frame that is rendered.
+/// Events are forwarded to the wrapped plugin in the call to `render_buffer`.
+/// Events that are rounded to a time after the end of the current buffer are forwarded
+/// with the buffer that contains their new time.
+/// Since rounding never changes the order of events, events are forwarded in the order in
+/// which they were received.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::middleware::quantize::{grid_size_in_frames, Quantize};
+/// # struct MyPlugin;
+/// # let my_plugin = MyPlugin;
+///
+/// // Snap to sixteenth notes at 120 beats per minute and 44100 frames per second.
+/// let plugin = Quantize::new(my_plugin, grid_size_in_frames(120.0, 4, 44100.0));
+/// ```
+///
+/// _Note_: the events that have not yet been forwarded are lost when calling [`into_inner`].
+///
+/// # Note: cannot be used in a real-time context
+/// [`new`] allocates memory; the other methods do not.
+///
+/// [`into_inner`]: #method.into_inner
+/// [`new`]: #method.new
+pub struct Quantize<P> {
+    plugin: P,
+    grid_size_in_frames: u64,
+    // The number of frames that have been rendered so far.
+    position_in_frames: u64,
+    // Events with their (rounded) time, relative to the start of the current buffer.
+    queue: EventQueue<u32, RawMidiEvent>,
+}
+
+impl<P> Quantize<P> {
+    /// Create a new `Quantize` that rounds the time of the events to a multiple of
+    /// `grid_size_in_frames`.
+    ///
+    /// # Panics
+    /// Panics if `grid_size_in_frames` is `0`.
+    pub fn new(plugin: P, grid_size_in_frames: u32) -> Self {
+        assert!(grid_size_in_frames > 0);
+        Self {
+            plugin,
+            grid_size_in_frames: grid_size_in_frames as u64,
+            position_in_frames: 0,
+            queue: EventQueue::new(EVENT_QUEUE_CAPACITY),
+        }
+    }
+
+    /// Change the grid size, e.g. when the tempo changes.
+    ///
+    /// # Panics
+    /// Panics if `grid_size_in_frames` is `0`.
+    pub fn set_grid_size_in_frames(&mut self, grid_size_in_frames: u32) {
+        assert!(grid_size_in_frames > 0);
+        self.grid_size_in_frames = grid_size_in_frames as u64;
+    }
+
+    // The rounded time, relative to the start of the current buffer.
+    // Events that are rounded to a time before the current buffer get time `0`.
+    fn quantize(&self, time_in_frames: u32) -> u32 {
+        let grid = self.grid_size_in_frames;
+        let absolute_time = self.position_in_frames + time_in_frames as u64;
+        let rounded = (absolute_time + grid / 2) / grid * grid;
+        rounded.saturating_sub(self.position_in_frames) as u32
+    }
+
+    fn queue_event(&mut self, event: Timed<RawMidiEvent>) {
+        let time = self.quantize(event.time_in_frames);
+        if let Some((time, dropped)) = self
+            .queue
+            .queue_event((time, event.event), AlwaysInsertNewAfterOld)
+        {
+            warn!(
+                "Too many midi events to quantize; dropping event {:?} at time {}.",
+                dropped, time
+            );
+        }
+    }
+
+    // Must be called after the buffer of `number_of_frames` frames has been rendered.
+    fn advance(&mut self, number_of_frames: u32) {
+        self.queue.shift_time(number_of_frames);
+        self.position_in_frames += number_of_frames as u64;
+    }
+}
+
+impl<P> EventHandler<Timed<RawMidiEvent>> for Quantize<P> {
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+        self.queue_event(event);
+    }
+}
+
+impl<P, C> ContextualEventHandler<Timed<RawMidiEvent>, C> for Quantize<P> {
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>, _context: &mut C) {
+        self.queue_event(event);
+    }
+}
+
+impl<P, S> AudioRenderer<S> for Quantize<P>
+where
+    P: AudioRenderer<S> + EventHandler<Timed<RawMidiEvent>>,
+    S: 'static + Copy,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>) {
+        let number_of_frames = buffer.number_of_frames() as u32;
+        for (time, event) in self.queue.drain(number_of_frames) {
+            self.plugin.handle_event(Timed::new(time, event));
+        }
+        self.plugin.render_buffer(buffer);
+        self.advance(number_of_frames);
+    }
+}
+
+impl<P, S, C> ContextualAudioRenderer<S, C> for Quantize<P>
+where
+    P: ContextualAudioRenderer<S, C> + ContextualEventHandler<Timed<RawMidiEvent>, C>,
+    S: 'static + Copy,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>, context: &mut C) {
+        let number_of_frames = buffer.number_of_frames() as u32;
+        for (time, event) in self.queue.drain(number_of_frames) {
+            self.plugin.handle_event(Timed::new(time, event), context);
+        }
+        self.plugin.render_buffer(buffer, context);
+        self.advance(number_of_frames);
+    }
+}
+
+middleware_impl!(Quantize<P>);
+
+#[cfg(all(test, feature = "backend-combined"))]
+mod tests {
+    use super::{grid_size_in_frames, Quantize};
+    use crate::backend::combined::dummy::AudioDummy;
+    use crate::backend::combined::{run, RunConfig, TestMidiReader};
+    use crate::buffer::AudioBufferInOut;
+    use crate::event::recording::RecordingEventHandler;
+    use crate::event::{ContextualEventHandler, DeltaEvent, RawMidiEvent, Timed};
+    use crate::ContextualAudioRenderer;
+
+    // With this sample rate, one frame lasts one microsecond.
+    const SAMPLE_RATE: u32 = 1_000_000;
+
+    struct Silence;
+
+    impl<C> ContextualEventHandler<Timed<RawMidiEvent>, C> for Silence {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>, _context: &mut C) {}
+    }
+
+    impl<C> ContextualAudioRenderer<f32, C> for Silence {
+        fn render_buffer(&mut self, _buffer: &mut AudioBufferInOut<f32>, _context: &mut C) {}
+    }
+
+    fn delta_event(
+        microseconds_since_previous_event: u64,
+        data: &[u8],
+    ) -> DeltaEvent<RawMidiEvent> {
+        DeltaEvent {
+            microseconds_since_previous_event,
+            event: RawMidiEvent::new(data),
+        }
+    }
+
+    #[test]
+    fn grid_size_is_computed_from_the_tempo() {
+        assert_eq!(grid_size_in_frames(120.0, 4, 44100.0), 5513);
+        assert_eq!(grid_size_in_frames(60.0, 1, 48000.0), 48000);
+    }
+
+    #[test]
+    fn off_grid_notes_are_snapped_to_the_nearest_grid_point() {
+        let note_on = [0x90, 60, 100];
+        let note_off = [0x80, 60, 0];
+        let mut plugin = Quantize::new(RecordingEventHandler::new(Silence), 8);
+
+        // Two buffers of 14 frames: frames 0 to 13 and frames 14 to 27.
+        run(
+            &mut plugin,
+            RunConfig::new(
+                AudioDummy::<f32>::with_sample_rate_and_length(SAMPLE_RATE, 28),
+                AudioDummy::<f32>::with_sample_rate_and_length(SAMPLE_RATE, 0),
+            )
+            .with_buffer_size(14)
+            .with_midi_in(TestMidiReader::new(vec![
+                delta_event(3, &note_on),
+                delta_event(2, &note_off),
+                delta_event(8, &note_on),
+                delta_event(2, &note_off),
+            ])),
+        )
+        .expect("Unexpected error.");
+
+        assert_eq!(
+            plugin.inner().recorded(),
+            &[
+                // 3 is rounded down to 0.
+                Timed::new(0, RawMidiEvent::new(&note_on)),
+                // 5 is rounded up to 8.
+                Timed::new(8, RawMidiEvent::new(&note_off)),
+                // 13 is rounded up to 16, which is frame 2 of the second buffer.
+                Timed::new(2, RawMidiEvent::new(&note_on)),
+                // 15 is rounded up to 16 as well.
+                Timed::new(2, RawMidiEvent::new(&note_off)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! Catch bugs in the signal processing by checking the output of a plugin.
+//!
+//! [`SanityCheck`] wraps a plugin and checks the output after every call to `render_buffer`.
+//! The checks are only done in debug builds; in release builds, `SanityCheck` only forwards
+//! to the wrapped plugin.
+//!
+//! [`SanityCheck`]: ./struct.SanityCheck.html
+use crate::buffer::AudioBufferInOut;
+use crate::event::{ContextualEventHandler, EventHandler};
+use crate::{AudioRenderer, ContextualAudioRenderer};
+use num_traits::Float;
+use std::fmt::Debug;
+
+/// Middleware that checks the output of the wrapped plugin after every call to `render_buffer`.
+///
+/// In debug builds, `SanityCheck` panics when an output sample is NaN or infinite and,
+/// if the range check is enabled (see [`with_range_check`]), when an output sample is
+/// outside the range `[-1, 1]`.
+/// The panic message contains the channel and the frame of the offending sample.
+/// In release builds, no checks are done.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::middleware::sanity_check::SanityCheck;
+/// # struct MyPlugin;
+/// # let my_plugin = MyPlugin;
+///
+/// let plugin = SanityCheck::new(my_plugin).with_range_check();
+/// ```
+///
+/// [`with_range_check`]: #method.with_range_check
+pub struct SanityCheck<P> {
+    plugin: P,
+    check_range: bool,
+}
+
+impl<P> SanityCheck<P> {
+    /// Create a new `SanityCheck` that checks that every output sample of the given plugin
+    /// is finite (not NaN and not infinite).
+    pub fn new(plugin: P) -> Self {
+        Self {
+            plugin,
+            check_range: false,
+        }
+    }
+
+    /// Also check that every output sample is in the range `[-1, 1]`.
+    pub fn with_range_check(self) -> Self {
+        Self {
+            check_range: true,
+            ..self
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn check<S>(&self, buffer: &mut AudioBufferInOut<S>)
+    where
+        S: Float + Debug,
+    {
+        let check_range = self.check_range;
+        for (channel_index, channel) in buffer.outputs().channel_iter_mut().enumerate() {
+            for (frame_index, sample) in channel.iter().enumerate() {
+                assert!(
+                    sample.is_finite(),
+                    "Output sample {:?} at channel {}, frame {} is not finite.",
+                    sample,
+                    channel_index,
+                    frame_index
+                );
+                assert!(
+                    !check_range || (*sample >= -S::one() && *sample <= S::one()),
+                    "Output sample {:?} at channel {}, frame {} is outside the range [-1, 1].",
+                    sample,
+                    channel_index,
+                    frame_index
+                );
+            }
+        }
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[inline(always)]
+    fn check<S>(&self, _buffer: &mut AudioBufferInOut<S>)
+    where
+        S: Float + Debug,
+    {
+    }
+}
+
+middleware_impl!(SanityCheck<P>);
+
+impl<P, S> AudioRenderer<S> for SanityCheck<P>
+where
+    P: AudioRenderer<S>,
+    S: Float + Debug,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>) {
+        self.plugin.render_buffer(buffer);
+        self.check(buffer);
+    }
+}
+
+impl<P, S, C> ContextualAudioRenderer<S, C> for SanityCheck<P>
+where
+    P: ContextualAudioRenderer<S, C>,
+    S: Float + Debug,
+{
+    fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<S>, context: &mut C) {
+        self.plugin.render_buffer(buffer, context);
+        self.check(buffer);
+    }
+}
+
+impl<P, E> EventHandler<E> for SanityCheck<P>
+where
+    P: EventHandler<E>,
+{
+    fn handle_event(&mut self, event: E) {
+        self.plugin.handle_event(event)
+    }
+}
+
+impl<P, E, C> ContextualEventHandler<E, C> for SanityCheck<P>
+where
+    P: ContextualEventHandler<E, C>,
+{
+    fn handle_event(&mut self, event: E, context: &mut C) {
+        self.plugin.handle_event(event, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SanityCheck;
+    use crate::buffer::{AudioBufferInOut, AudioChunk};
+    use crate::meta::{InOut, Meta, MetaData, NameAndLatency};
+    use crate::{
+        AudioHandlerMeta, CommonAudioPortMeta, CommonMidiPortMeta, CommonPluginMeta,
+        ContextualAudioRenderer, MidiHandlerMeta,
+    };
+
+    // Outputs the given samples on the first channel.
+    struct Samples(Vec<f32>);
+
+    impl ContextualAudioRenderer<f32, ()> for Samples {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut ()) {
+            buffer.index_output_channel(0).copy_from_slice(&self.0[..]);
+        }
+    }
+
+    fn render(plugin: &mut SanityCheck<Samples>) {
+        let number_of_frames = plugin.inner().0.len();
+        let mut output = AudioChunk::zero(1, number_of_frames);
+        let mut output_slices = output.as_mut_slices();
+        let mut buffer = AudioBufferInOut::new(&[], &mut output_slices, number_of_frames);
+        plugin.render_buffer(&mut buffer, &mut ());
+    }
+
+    #[test]
+    fn finite_output_is_accepted() {
+        render(&mut SanityCheck::new(Samples(vec![0.0, 1.5, -2.0])));
+        render(&mut SanityCheck::new(Samples(vec![0.0, 1.0, -1.0])).with_range_check());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "at channel 0, frame 1 is not finite")]
+    fn nan_is_caught_in_debug_builds() {
+        render(&mut SanityCheck::new(Samples(vec![0.0, f32::NAN, 0.0])));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "at channel 0, frame 2 is outside the range [-1, 1]")]
+    fn out_of_range_is_caught_in_debug_builds_when_checking_range() {
+        render(&mut SanityCheck::new(Samples(vec![0.0, 1.0, 1.5])).with_range_check());
+    }
+
+    struct MetaPlugin {
+        meta: MetaData<NameAndLatency<&'static str>, &'static str, &'static str>,
+    }
+
+    impl Meta for MetaPlugin {
+        type MetaData = MetaData<NameAndLatency<&'static str>, &'static str, &'static str>;
+        fn meta(&self) -> &Self::MetaData {
+            &self.meta
+        }
+    }
+
+    #[test]
+    fn meta_data_of_the_wrapped_plugin_is_forwarded() {
+        let plugin = SanityCheck::new(MetaPlugin {
+            meta: MetaData {
+                general_meta: NameAndLatency {
+                    name: "checked",
+                    latency_in_frames: 32,
+                },
+                audio_port_meta: InOut {
+                    inputs: vec!["in"],
+                    outputs: vec!["left", "right"],
+                },
+                midi_port_meta: InOut {
+                    inputs: vec!["midi in"],
+                    outputs: vec![],
+                },
+            },
+        });
+        let mut name = String::new();
+        plugin.plugin_name(&mut name).expect("Unexpected error");
+        assert_eq!(name, "checked");
+        assert_eq!(plugin.latency_in_frames(), 32);
+        assert_eq!(plugin.max_number_of_audio_inputs(), 1);
+        assert_eq!(plugin.max_number_of_audio_outputs(), 2);
+        assert_eq!(plugin.max_number_of_midi_inputs(), 1);
+        assert_eq!(plugin.max_number_of_midi_outputs(), 0);
+        let mut output_name = String::new();
+        CommonAudioPortMeta::output_name(&plugin, &mut output_name, 1).expect("Unexpected error");
+        assert_eq!(output_name, "right");
+        let mut input_name = String::new();
+        CommonMidiPortMeta::input_name(&plugin, &mut input_name, 0).expect("Unexpected error");
+        assert_eq!(input_name, "midi in");
+    }
+}
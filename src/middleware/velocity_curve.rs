@@ -0,0 +1,228 @@
+//! Reshape the velocity of incoming note on events, e.g. to adapt to a keyboard controller.
+//!
+//! [`VelocityCurve`] wraps a plugin and applies a [`VelocityTransferFunction`] to the velocity
+//! of every note on event before forwarding it to the wrapped plugin.
+//!
+//! [`VelocityCurve`]: ./struct.VelocityCurve.html
+//! [`VelocityTransferFunction`]: ./enum.VelocityTransferFunction.html
+use crate::event::{ContextualEventHandler, EventHandler, RawMidiEvent, Timed};
+use midi_consts::channel_event::{EVENT_TYPE_MASK, NOTE_ON};
+
+const MAX_VELOCITY: u8 = 0x7F;
+
+/// Defines how the velocity of a note on event is changed.
+///
+/// The resulting velocity is always in the range 1-127, so that a note on event is never
+/// turned into a note off event (a note on event with velocity 0).
+///
+/// _Note_: use [`linear`] and [`exponential`] to create a linear or an exponential transfer
+/// function, so that the type parameter `F` does not need to be specified.
+///
+/// [`linear`]: #method.linear
+/// [`exponential`]: #method.exponential
+pub enum VelocityTransferFunction<F = fn(u8) -> u8> {
+    /// Multiply the velocity with the given factor.
+    Linear(f32),
+    /// Raise the velocity (scaled to the range 0-1) to the given power:
+    /// an exponent above `1` makes soft notes softer, an exponent below `1` makes them louder.
+    Exponential(f32),
+    /// Apply the given function to the velocity.
+    Custom(F),
+}
+
+impl VelocityTransferFunction {
+    /// Create a transfer function that multiplies the velocity with the given factor.
+    pub fn linear(factor: f32) -> Self {
+        VelocityTransferFunction::Linear(factor)
+    }
+
+    /// Create a transfer function that raises the velocity (scaled to the range 0-1)
+    /// to the given power.
+    pub fn exponential(exponent: f32) -> Self {
+        VelocityTransferFunction::Exponential(exponent)
+    }
+}
+
+impl<F> VelocityTransferFunction<F>
+where
+    F: Fn(u8) -> u8,
+{
+    /// Apply the transfer function to a velocity in the range 1-127.
+    pub fn apply(&self, velocity: u8) -> u8 {
+        let new_velocity = match self {
+            VelocityTransferFunction::Linear(factor) => {
+                (velocity as f32 * factor).round().min(MAX_VELOCITY as f32) as u8
+            }
+            VelocityTransferFunction::Exponential(exponent) => {
+                let max = MAX_VELOCITY as f32;
+                ((velocity as f32 / max).powf(*exponent) * max).round() as u8
+            }
+            VelocityTransferFunction::Custom(function) => function(velocity),
+        };
+        new_velocity.clamp(1, MAX_VELOCITY)
+    }
+}
+
+/// Middleware that changes the velocity of note on events with a [`VelocityTransferFunction`].
+///
+/// Note on events with velocity 0 (which are note off events) and all other events are
+/// forwarded unchanged.
+/// `VelocityCurve` can be combined with other middleware, e.g. with a [`ChannelFilter`].
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::middleware::channel_filter::ChannelFilter;
+/// use rsynth::middleware::velocity_curve::{VelocityCurve, VelocityTransferFunction};
+/// # struct MyPlugin;
+/// # let my_plugin = MyPlugin;
+///
+/// // Make soft notes softer, only for the events on channel 0.
+/// let plugin = ChannelFilter::new(VelocityCurve::new(
+///     my_plugin,
+///     VelocityTransferFunction::exponential(2.0),
+/// ))
+/// .with_only_channel(0);
+/// ```
+///
+/// [`VelocityTransferFunction`]: ./enum.VelocityTransferFunction.html
+/// [`ChannelFilter`]: ../channel_filter/struct.ChannelFilter.html
+pub struct VelocityCurve<P, F = fn(u8) -> u8> {
+    plugin: P,
+    transfer_function: VelocityTransferFunction<F>,
+}
+
+impl<P, F> VelocityCurve<P, F>
+where
+    F: Fn(u8) -> u8,
+{
+    /// Create a new `VelocityCurve` with the given transfer function.
+    pub fn new(plugin: P, transfer_function: VelocityTransferFunction<F>) -> Self {
+        Self {
+            plugin,
+            transfer_function,
+        }
+    }
+
+    fn transform(&self, event: Timed<RawMidiEvent>) -> Timed<RawMidiEvent> {
+        let data = event.event.data();
+        let is_note_on = event.event.bytes().len() == 3 && data[0] & EVENT_TYPE_MASK == NOTE_ON;
+        if !is_note_on || data[2] == 0 {
+            return event;
+        }
+        let velocity = self.transfer_function.apply(data[2]);
+        Timed::new(
+            event.time_in_frames,
+            RawMidiEvent::new(&[data[0], data[1], velocity]),
+        )
+    }
+}
+
+impl<P, F> EventHandler<Timed<RawMidiEvent>> for VelocityCurve<P, F>
+where
+    P: EventHandler<Timed<RawMidiEvent>>,
+    F: Fn(u8) -> u8,
+{
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+        let event = self.transform(event);
+        self.plugin.handle_event(event);
+    }
+}
+
+impl<P, F, C> ContextualEventHandler<Timed<RawMidiEvent>, C> for VelocityCurve<P, F>
+where
+    P: ContextualEventHandler<Timed<RawMidiEvent>, C>,
+    F: Fn(u8) -> u8,
+{
+    fn handle_event(&mut self, event: Timed<RawMidiEvent>, context: &mut C) {
+        let event = self.transform(event);
+        self.plugin.handle_event(event, context);
+    }
+}
+
+middleware_impl!(VelocityCurve<P, F>);
+forward_audio_renderer!(VelocityCurve<P, F>);
+
+#[cfg(test)]
+mod tests {
+    use super::{VelocityCurve, VelocityTransferFunction};
+    use crate::event::recording::RecordingEventHandler;
+    use crate::event::{EventHandler, RawMidiEvent, Timed};
+    use crate::middleware::channel_filter::ChannelFilter;
+    use crate::test_utilities::DummyEventHandler;
+
+    type Recorder = RecordingEventHandler<DummyEventHandler, Timed<RawMidiEvent>>;
+
+    fn recorder() -> Recorder {
+        RecordingEventHandler::new(DummyEventHandler)
+    }
+
+    fn recorded_bytes(recorder: &Recorder) -> Vec<Vec<u8>> {
+        recorder
+            .recorded()
+            .iter()
+            .map(|event| event.event.bytes().to_vec())
+            .collect()
+    }
+
+    fn send<H: EventHandler<Timed<RawMidiEvent>>>(handler: &mut H, events: &[&[u8]]) {
+        for event in events {
+            handler.handle_event(Timed::new(0, RawMidiEvent::new(event)));
+        }
+    }
+
+    #[test]
+    fn transfer_functions_keep_the_velocity_in_range() {
+        let linear = VelocityTransferFunction::linear(2.0);
+        assert_eq!(linear.apply(10), 20);
+        assert_eq!(linear.apply(100), 127);
+        let exponential = VelocityTransferFunction::exponential(2.0);
+        assert_eq!(exponential.apply(127), 127);
+        assert_eq!(exponential.apply(64), 32);
+        assert_eq!(exponential.apply(1), 1);
+        let custom = VelocityTransferFunction::Custom(|_| 0);
+        assert_eq!(custom.apply(100), 1);
+    }
+
+    #[test]
+    fn only_the_velocity_of_note_on_events_is_changed() {
+        let mut curve = VelocityCurve::new(recorder(), VelocityTransferFunction::linear(0.5));
+        send(
+            &mut curve,
+            &[&[0x91, 60, 100], &[0x81, 60, 100], &[0xB1, 7, 100], &[0xF8]],
+        );
+        assert_eq!(
+            recorded_bytes(curve.inner()),
+            vec![
+                vec![0x91, 60, 50],
+                vec![0x81, 60, 100],
+                vec![0xB1, 7, 100],
+                vec![0xF8]
+            ]
+        );
+    }
+
+    #[test]
+    fn note_on_with_velocity_zero_stays_a_note_off() {
+        let mut curve = VelocityCurve::new(recorder(), VelocityTransferFunction::Custom(|_| 127));
+        send(&mut curve, &[&[0x90, 60, 0], &[0x90, 60, 1]]);
+        assert_eq!(
+            recorded_bytes(curve.inner()),
+            vec![vec![0x90, 60, 0], vec![0x90, 60, 127]]
+        );
+    }
+
+    #[test]
+    fn can_be_combined_with_a_channel_filter() {
+        let mut plugin = ChannelFilter::new(VelocityCurve::new(
+            recorder(),
+            VelocityTransferFunction::linear(0.5),
+        ))
+        .with_only_channel(1);
+        send(&mut plugin, &[&[0x90, 60, 100], &[0x91, 60, 100]]);
+        assert_eq!(
+            recorded_bytes(plugin.inner().inner()),
+            vec![vec![0x91, 60, 50]]
+        );
+    }
+}
@@ -1,9 +1,12 @@
 //! Utilities for testing.
 
-use crate::buffer::{AudioBufferInOut, AudioChunk};
+use crate::backend::HostInterface;
+use crate::buffer::{buffers_as_mut_slice, buffers_as_slice, AudioBufferInOut, AudioChunk};
 use crate::event::{ContextualEventHandler, EventHandler};
 use crate::{AudioHandler, AudioHandlerMeta, ContextualAudioRenderer};
+use num_traits::Zero;
 use std::fmt::Debug;
+use std::time::{Duration, Instant};
 
 pub struct DummyEventHandler;
 
@@ -199,3 +202,482 @@ where
         self.event_index += 1;
     }
 }
+
+/// A host useful for testing plugins that send events to the host, e.g. midi output events
+/// (of type `Indexed<Timed<RawMidiEvent>>`) of an arpeggiator.
+///
+/// Pass the `TestHost` as the context to `render_buffer` and call [`check_buffer`] after every
+/// call to `render_buffer` to compare the events that were sent during this call with the
+/// expected output events for this buffer.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::buffer::AudioBufferInOut;
+/// use rsynth::event::{EventHandler, Indexed, RawMidiEvent, Timed};
+/// use rsynth::test_utilities::TestHost;
+/// use rsynth::ContextualAudioRenderer;
+///
+/// // A plugin that sends a note on event at the start of every buffer.
+/// struct Metronome;
+/// impl<H> ContextualAudioRenderer<f32, H> for Metronome
+/// where
+///     H: EventHandler<Indexed<Timed<RawMidiEvent>>>,
+/// {
+///     fn render_buffer(&mut self, _buffer: &mut AudioBufferInOut<f32>, context: &mut H) {
+///         let note_on = RawMidiEvent::new(&[0x90, 60, 100]);
+///         context.handle_event(Indexed::new(0, Timed::new(0, note_on)));
+///     }
+/// }
+///
+/// let note_on = RawMidiEvent::new(&[0x90, 60, 100]);
+/// let mut host = TestHost::new(vec![vec![Indexed::new(0, Timed::new(0, note_on))]]);
+/// let mut output = [0.0];
+/// let mut outputs = [&mut output[..]];
+/// let mut buffer = AudioBufferInOut::new(&[], &mut outputs, 1);
+/// Metronome.render_buffer(&mut buffer, &mut host);
+/// host.check_buffer();
+/// host.check_last();
+/// ```
+///
+/// [`check_buffer`]: #method.check_buffer
+pub struct TestHost<E> {
+    expected_output_events: Vec<Vec<E>>,
+    output_events: Vec<E>,
+    buffer_index: usize,
+}
+
+impl<E> TestHost<E> {
+    /// Create a new `TestHost` with the events that are expected to be sent to the host,
+    /// for each buffer.
+    pub fn new(expected_output_events: Vec<Vec<E>>) -> Self {
+        TestHost {
+            expected_output_events,
+            output_events: Vec::new(),
+            buffer_index: 0,
+        }
+    }
+
+    /// Compare the events that have been sent to the host since the previous call to
+    /// `check_buffer` with the expected output events for the current buffer and move on
+    /// to the next buffer.
+    ///
+    /// # Panics
+    /// Panics if the events differ from the expected output events or if `check_buffer` is
+    /// called more often than there are buffers with expected output events.
+    pub fn check_buffer(&mut self)
+    where
+        E: PartialEq + Debug,
+    {
+        assert!(
+            self.buffer_index < self.expected_output_events.len(),
+            "`check_buffer` called more often than expected: expected only {} times",
+            self.expected_output_events.len()
+        );
+        assert_eq!(
+            self.output_events, self.expected_output_events[self.buffer_index],
+            "mismatch in the output events for buffer #{}",
+            self.buffer_index
+        );
+        self.output_events.clear();
+        self.buffer_index += 1;
+    }
+
+    /// Check that `check_buffer` has been called for every buffer.
+    pub fn check_last(&self) {
+        assert_eq!(self.buffer_index, self.expected_output_events.len());
+    }
+}
+
+impl<E> HostInterface for TestHost<E> {}
+
+impl<E> EventHandler<E> for TestHost<E> {
+    fn handle_event(&mut self, event: E) {
+        self.output_events.push(event);
+    }
+}
+
+// The number of frames before and after the first difference that `assert_chunks_eq`
+// shows as context.
+const CONTEXT_IN_FRAMES: usize = 3;
+
+/// Assert that two audio chunks are equal.
+///
+/// In contrast to `assert_eq!`, this does not print the complete audio chunks when they differ,
+/// but only the first sample that differs (with its channel and frame index) and some frames
+/// around it as context.
+///
+/// # Panics
+/// Panics if the audio chunks have a different number of channels, if a channel has a different
+/// number of frames or if a sample differs.
+///
+/// # Example
+/// ```
+/// #[macro_use]
+/// extern crate rsynth;
+/// use rsynth::test_utilities::assert_chunks_eq;
+/// # fn main() {
+/// let rendered = audio_chunk![[0.0, 0.5, 1.0], [0.0, -0.5, -1.0]];
+/// assert_chunks_eq(&rendered, &audio_chunk![[0.0, 0.5, 1.0], [0.0, -0.5, -1.0]]);
+/// # }
+/// ```
+pub fn assert_chunks_eq<S>(left: &AudioChunk<S>, right: &AudioChunk<S>)
+where
+    S: PartialEq + Debug,
+{
+    assert_eq!(
+        left.number_of_channels(),
+        right.number_of_channels(),
+        "audio chunks have a different number of channels: left has {} channels, right has {}",
+        left.number_of_channels(),
+        right.number_of_channels()
+    );
+    for (channel_index, (left_channel, right_channel)) in left
+        .channels()
+        .iter()
+        .zip(right.channels().iter())
+        .enumerate()
+    {
+        if let Some(frame_index) = left_channel
+            .iter()
+            .zip(right_channel.iter())
+            .position(|(l, r)| l != r)
+        {
+            let start = frame_index.saturating_sub(CONTEXT_IN_FRAMES);
+            let end = frame_index + CONTEXT_IN_FRAMES + 1;
+            panic!(
+                "audio chunks differ at channel #{}, frame #{}: left is {:?}, right is {:?}\n\
+                 left, frames {}..: {:?}\n\
+                 right, frames {}..: {:?}",
+                channel_index,
+                frame_index,
+                left_channel[frame_index],
+                right_channel[frame_index],
+                start,
+                &left_channel[start..end.min(left_channel.len())],
+                start,
+                &right_channel[start..end.min(right_channel.len())]
+            );
+        }
+        assert_eq!(
+            left_channel.len(),
+            right_channel.len(),
+            "channel #{} of the audio chunks has a different number of frames: \
+             left has {} frames, right has {}",
+            channel_index,
+            left_channel.len(),
+            right_channel.len()
+        );
+    }
+}
+
+/// A host that does nothing, to be used as the context when benchmarking a plugin.
+///
+/// Events sent to the `BenchHost` (e.g. midi output) are ignored.
+/// See also [`run_bench`].
+///
+/// [`run_bench`]: ./fn.run_bench.html
+pub struct BenchHost;
+
+impl HostInterface for BenchHost {}
+
+impl<E> EventHandler<E> for BenchHost {
+    fn handle_event(&mut self, _event: E) {}
+}
+
+impl<E, C> ContextualEventHandler<E, C> for BenchHost {
+    fn handle_event(&mut self, _event: E, _context: &mut C) {}
+}
+
+/// Call `render_buffer` on the plugin `iterations` times and return the total time spent
+/// in `render_buffer`, e.g. to profile the signal processing of a plugin.
+///
+/// The buffers are allocated before the timing starts; the inputs are silent.
+///
+/// # Example
+/// ```
+/// use rsynth::buffer::AudioBufferInOut;
+/// use rsynth::test_utilities::{run_bench, BenchHost};
+/// use rsynth::ContextualAudioRenderer;
+///
+/// struct Silence;
+/// impl ContextualAudioRenderer<f32, BenchHost> for Silence {
+///     fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut BenchHost) {
+///         buffer.outputs().set(0.0);
+///     }
+/// }
+///
+/// let duration = run_bench::<f32, _>(&mut Silence, 2, 2, 512, 1000);
+/// println!("Rendering one buffer takes {:?} on average.", duration / 1000);
+/// ```
+pub fn run_bench<S, P>(
+    plugin: &mut P,
+    number_of_input_channels: usize,
+    number_of_output_channels: usize,
+    buffer_size_in_frames: usize,
+    iterations: u32,
+) -> Duration
+where
+    S: Copy + Zero + 'static,
+    P: ContextualAudioRenderer<S, BenchHost>,
+{
+    let input_buffers =
+        AudioChunk::<S>::zero(number_of_input_channels, buffer_size_in_frames).inner();
+    let mut output_buffers =
+        AudioChunk::<S>::zero(number_of_output_channels, buffer_size_in_frames).inner();
+    let inputs = buffers_as_slice(&input_buffers, buffer_size_in_frames);
+    let mut outputs = buffers_as_mut_slice(&mut output_buffers, buffer_size_in_frames);
+    let mut buffer = AudioBufferInOut::new(&inputs, &mut outputs, buffer_size_in_frames);
+    let mut host = BenchHost;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        plugin.render_buffer(&mut buffer, &mut host);
+    }
+    start.elapsed()
+}
+
+// The autocorrelation of a periodic signal must be at least this fraction of the
+// autocorrelation at lag `0` in order to be considered a pitch.
+const MINIMUM_PERIODICITY: f32 = 0.5;
+
+/// Estimate the fundamental frequency (in Hz) of the first channel of the audio chunk,
+/// e.g. to verify the tuning of a synthesizer.
+///
+/// The pitch is estimated with the autocorrelation of the signal: the first peak of the
+/// autocorrelation (after the autocorrelation has become negative) is taken as the period.
+/// Only periods that fit at least twice in the audio chunk can be detected.
+///
+/// Returns `None` if the audio chunk has no channels, is silent or is not periodic enough.
+///
+/// # Example
+/// ```
+/// use rsynth::buffer::AudioChunk;
+/// use rsynth::test_utilities::estimate_pitch;
+///
+/// let sample_rate = 44100.0;
+/// let sine: Vec<f32> = (0..4096)
+///     .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / sample_rate).sin() as f32)
+///     .collect();
+/// let mut chunk = AudioChunk::new(1);
+/// chunk.append_sliced_chunk(&[&sine]);
+/// let pitch = estimate_pitch(&chunk, sample_rate).unwrap();
+/// assert!((pitch - 440.0).abs() < 2.0);
+/// ```
+pub fn estimate_pitch(chunk: &AudioChunk<f32>, sample_rate: f64) -> Option<f32> {
+    let signal = chunk.channels().first()?;
+    let max_lag = signal.len() / 2;
+    // Unbiased autocorrelation, normalized such that the autocorrelation at lag `0` is `1`.
+    let energy: f32 = signal.iter().map(|x| x * x).sum::<f32>() / signal.len() as f32;
+    if energy <= 0.0 || max_lag < 2 {
+        return None;
+    }
+    let autocorrelation: Vec<f32> = (0..=max_lag)
+        .map(|lag| {
+            let sum: f32 = signal
+                .iter()
+                .zip(signal[lag..].iter())
+                .map(|(x, y)| x * y)
+                .sum();
+            sum / (signal.len() - lag) as f32 / energy
+        })
+        .collect();
+    let first_negative_lag = autocorrelation.iter().position(|r| *r < 0.0)?;
+    let highest = autocorrelation[first_negative_lag..max_lag]
+        .iter()
+        .cloned()
+        .fold(std::f32::MIN, f32::max);
+    if highest < MINIMUM_PERIODICITY {
+        return None;
+    }
+    // Take the first peak that is almost as high as the highest peak, so that a multiple
+    // of the period is not mistaken for the period.
+    let mut lag = first_negative_lag
+        + autocorrelation[first_negative_lag..max_lag]
+            .iter()
+            .position(|r| *r >= 0.9 * highest)?;
+    while lag + 1 < max_lag && autocorrelation[lag + 1] > autocorrelation[lag] {
+        lag += 1;
+    }
+    // Parabolic interpolation around the peak for sub-sample accuracy.
+    let (before, peak, after) = (
+        autocorrelation[lag - 1],
+        autocorrelation[lag],
+        autocorrelation[lag + 1],
+    );
+    let denominator = before - 2.0 * peak + after;
+    let offset = if denominator == 0.0 {
+        0.0
+    } else {
+        0.5 * (before - after) / denominator
+    };
+    Some((sample_rate / (lag as f64 + offset as f64)) as f32)
+}
+
+#[cfg(test)]
+fn sine(frequency: f64, sample_rate: f64, number_of_frames: usize) -> AudioChunk<f32> {
+    let samples: Vec<f32> = (0..number_of_frames)
+        .map(|i| (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin() as f32)
+        .collect();
+    let mut chunk = AudioChunk::new(1);
+    chunk.append_sliced_chunk(&[&samples[..]]);
+    chunk
+}
+
+#[test]
+fn estimate_pitch_of_a_sine() {
+    let pitch = estimate_pitch(&sine(440.0, 44100.0, 4096), 44100.0).expect("No pitch found.");
+    assert!(
+        (pitch - 440.0).abs() < 2.0,
+        "Expected 440 Hz, but got {} Hz.",
+        pitch
+    );
+    let pitch = estimate_pitch(&sine(110.0, 48000.0, 4096), 48000.0).expect("No pitch found.");
+    assert!(
+        (pitch - 110.0).abs() < 1.0,
+        "Expected 110 Hz, but got {} Hz.",
+        pitch
+    );
+}
+
+#[test]
+fn estimate_pitch_of_silence_is_none() {
+    assert_eq!(estimate_pitch(&AudioChunk::zero(1, 1024), 44100.0), None);
+}
+
+#[test]
+fn run_bench_renders_the_given_number_of_buffers() {
+    struct CountingPlugin {
+        number_of_buffers: u32,
+    }
+    impl ContextualAudioRenderer<f32, BenchHost> for CountingPlugin {
+        fn render_buffer(&mut self, buffer: &mut AudioBufferInOut<f32>, _context: &mut BenchHost) {
+            assert_eq!(buffer.number_of_input_channels(), 1);
+            assert_eq!(buffer.number_of_output_channels(), 2);
+            assert_eq!(buffer.number_of_frames(), 64);
+            self.number_of_buffers += 1;
+        }
+    }
+    let mut plugin = CountingPlugin {
+        number_of_buffers: 0,
+    };
+    run_bench(&mut plugin, 1, 2, 64, 10);
+    assert_eq!(plugin.number_of_buffers, 10);
+}
+
+#[test]
+fn assert_chunks_eq_accepts_equal_chunks() {
+    assert_chunks_eq(
+        &audio_chunk![[1, 2, 3], [4, 5, 6]],
+        &audio_chunk![[1, 2, 3], [4, 5, 6]],
+    );
+}
+
+#[test]
+#[should_panic(expected = "audio chunks differ at channel #1, frame #5: left is 15, right is 99")]
+fn assert_chunks_eq_reports_the_first_differing_sample() {
+    let left = audio_chunk![
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [10, 11, 12, 13, 14, 15, 16, 17, 18, 19]
+    ];
+    let right = audio_chunk![
+        [0, 1, 2, 3, 4, 5, 6, 7, 8, 9],
+        [10, 11, 12, 13, 14, 99, 16, 17, 18, 19]
+    ];
+    assert_chunks_eq(&left, &right);
+}
+
+#[test]
+#[should_panic(expected = "channel #0 of the audio chunks has a different number of frames")]
+fn assert_chunks_eq_reports_a_different_length() {
+    assert_chunks_eq(&audio_chunk![[1, 2, 3]], &audio_chunk![[1, 2]]);
+}
+
+#[test]
+fn recording_event_handler_records_any_type_of_event() {
+    use crate::event::recording::RecordingEventHandler;
+    let plugin: TestPlugin<f32, u8, ()> =
+        TestPlugin::new(vec![], vec![], vec![vec![1, 2]], vec![], ());
+    let mut handler = RecordingEventHandler::new(plugin);
+    handler.handle_event(1);
+    handler.handle_event(2);
+    assert_eq!(handler.recorded(), &[1, 2]);
+    handler.clear();
+    assert!(handler.recorded().is_empty());
+}
+
+#[test]
+fn test_plugin_writes_the_outputs_and_sends_the_events() {
+    use crate::event::recording::RecordingEventHandler;
+    let mut plugin = TestPlugin::new(
+        vec![audio_chunk![[1, 2]], audio_chunk![[3, 4]]],
+        vec![audio_chunk![[10, 20]], audio_chunk![[30, 40]]],
+        vec![vec![1], vec![2, 3], vec![]],
+        vec![vec![4, 5], vec![6]],
+        (),
+    );
+    let mut host = RecordingEventHandler::new(DummyEventHandler);
+    let mut rendered = AudioChunk::new(1);
+    let events_per_buffer: [&[i32]; 2] = [&[1], &[2, 3]];
+    for (input, events) in [[1, 2], [3, 4]].iter().zip(events_per_buffer.iter()) {
+        for event in events.iter() {
+            plugin.handle_event(*event);
+        }
+        let mut output = [0, 0];
+        {
+            let inputs = [&input[..]];
+            let mut outputs = [&mut output[..]];
+            let mut buffer = AudioBufferInOut::new(&inputs, &mut outputs, 2);
+            plugin.render_buffer(&mut buffer, &mut host);
+        }
+        rendered.append_sliced_chunk(&[&output[..]]);
+    }
+    plugin.check_last();
+    assert_eq!(rendered, audio_chunk![[10, 20, 30, 40]]);
+    assert_eq!(host.recorded(), &[4, 5, 6]);
+}
+
+#[test]
+#[should_panic(expected = "mismatch for event #1 after 1 calls to `render_buffer`")]
+fn test_plugin_compares_events_with_the_expected_events_of_the_current_buffer() {
+    let mut plugin = TestPlugin::new(
+        vec![audio_chunk![[1]]],
+        vec![audio_chunk![[1]]],
+        vec![vec![1], vec![2, 3]],
+        vec![vec![]],
+        (),
+    );
+    plugin.handle_event(1);
+    let input = [1];
+    let mut output = [0];
+    {
+        let inputs = [&input[..]];
+        let mut outputs = [&mut output[..]];
+        let mut buffer = AudioBufferInOut::new(&inputs, &mut outputs, 1);
+        plugin.render_buffer(&mut buffer, &mut DummyEventHandler);
+    }
+    plugin.handle_event(2);
+    // The second event after the first buffer is expected to be `3`.
+    plugin.handle_event(1);
+}
+
+#[test]
+fn test_host_compares_the_output_events_per_buffer() {
+    let mut host = TestHost::new(vec![vec![1, 2], vec![], vec![3]]);
+    host.handle_event(1);
+    host.handle_event(2);
+    host.check_buffer();
+    host.check_buffer();
+    host.handle_event(3);
+    host.check_buffer();
+    host.check_last();
+}
+
+#[test]
+#[should_panic(expected = "mismatch in the output events for buffer #1")]
+fn test_host_reports_unexpected_output_events() {
+    let mut host = TestHost::new(vec![vec![1], vec![2]]);
+    host.handle_event(1);
+    host.check_buffer();
+    host.handle_event(3);
+    host.check_buffer();
+}
@@ -1,4 +1,6 @@
-#![deprecated(
+//! Utilities.
+pub mod overlap_add;
+#[deprecated(
     since = "0.1.1",
     note = "Deprecated in favour of the dedicated `polyphony` crate."
 )]
@@ -0,0 +1,217 @@
+//! Windowed overlap-add, e.g. for effects that work in the frequency domain
+//! (pitch shifting, spectral gating, ...).
+//!
+//! The input is cut into overlapping frames.
+//! Each frame is multiplied with a window and passed to a callback that can change the frame
+//! in place, e.g. by computing its FFT, changing the spectrum and computing the inverse FFT.
+//! The changed frame is multiplied with the window again and added to the output.
+//! The output is normalized, so that an identity callback reconstructs the input,
+//! delayed by [`latency_in_frames`].
+//!
+//! [`latency_in_frames`]: ./struct.OverlapAdd.html#method.latency_in_frames
+use std::f32::consts::PI;
+
+/// Create a (periodic) Hann window with the given size.
+///
+/// # Note: cannot be used in a real-time context
+/// This method allocates memory and cannot be used in a real-time context.
+pub fn hann_window(size: usize) -> Vec<f32> {
+    (0..size)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / size as f32).cos())
+        .collect()
+}
+
+/// Windowed overlap-add for one channel.
+///
+/// The frame size is the length of the window; a new frame is processed every `hop_size` samples.
+///
+/// # Example
+/// ```
+/// use rsynth::utilities::overlap_add::OverlapAdd;
+///
+/// // Hann window of 8 samples with 50% overlap.
+/// let mut overlap_add = OverlapAdd::hann(8, 4);
+/// let input = vec![1.0; 32];
+/// let mut output = vec![0.0; 32];
+/// overlap_add.process(&input, &mut output, |_frame: &mut [f32]| {
+///     // Compute the FFT of the frame, change the spectrum, compute the inverse FFT.
+/// });
+/// let latency = overlap_add.latency_in_frames();
+/// assert!(output[latency..].iter().all(|sample| (sample - 1.0).abs() < 1e-5));
+/// ```
+pub struct OverlapAdd {
+    window: Vec<f32>,
+    // `normalization[i]` is the inverse of the sum of the squared window values
+    // that overlap at position `i` (modulo `hop_size`).
+    normalization: Vec<f32>,
+    hop_size: usize,
+    input: Vec<f32>,
+    accumulator: Vec<f32>,
+    ready: Vec<f32>,
+    frame: Vec<f32>,
+    position: usize,
+}
+
+impl OverlapAdd {
+    /// Create a new `OverlapAdd` with the given window and hop size.
+    ///
+    /// # Panics
+    /// Panics if one of the following happens:
+    /// * `window` is empty,
+    /// * `hop_size` is `0` or the length of the window is not a multiple of `hop_size`,
+    /// * the overlapping windows do not cover every sample (e.g. because the window is zero
+    ///   on the same position in every overlapping frame), so the input cannot be reconstructed.
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    pub fn new(window: Vec<f32>, hop_size: usize) -> Self {
+        let frame_size = window.len();
+        assert!(frame_size > 0);
+        assert!(hop_size > 0);
+        assert_eq!(
+            frame_size % hop_size,
+            0,
+            "The frame size must be a multiple of the hop size."
+        );
+        let normalization: Vec<f32> = (0..hop_size)
+            .map(|i| {
+                let sum: f32 = window.iter().skip(i).step_by(hop_size).map(|w| w * w).sum();
+                assert!(
+                    sum > 0.0,
+                    "The overlapping windows must cover every sample."
+                );
+                1.0 / sum
+            })
+            .collect();
+        Self {
+            window,
+            normalization,
+            hop_size,
+            input: vec![0.0; frame_size],
+            accumulator: vec![0.0; frame_size],
+            ready: vec![0.0; hop_size],
+            frame: vec![0.0; frame_size],
+            position: 0,
+        }
+    }
+
+    /// Create a new `OverlapAdd` with a Hann window of the given frame size.
+    ///
+    /// # Panics
+    /// See [`new`].
+    ///
+    /// # Note: cannot be used in a real-time context
+    /// This method allocates memory and cannot be used in a real-time context.
+    ///
+    /// [`new`]: #method.new
+    pub fn hann(frame_size: usize, hop_size: usize) -> Self {
+        Self::new(hann_window(frame_size), hop_size)
+    }
+
+    /// The number of samples in one frame.
+    pub fn frame_size(&self) -> usize {
+        self.window.len()
+    }
+
+    /// The number of samples between the start of two subsequent frames.
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
+    }
+
+    /// The number of samples by which the output lags behind the input.
+    pub fn latency_in_frames(&self) -> usize {
+        self.frame_size()
+    }
+
+    /// Process the `input` and write the result to `output`.
+    ///
+    /// Every time `hop_size` new samples have been read, `callback` is called with the
+    /// windowed frame, which it can change in place.
+    /// The input and the output can have any length, as long as they have the same length.
+    ///
+    /// # Panics
+    /// Panics if `input` and `output` do not have the same length.
+    pub fn process<F>(&mut self, input: &[f32], output: &mut [f32], mut callback: F)
+    where
+        F: FnMut(&mut [f32]),
+    {
+        assert_eq!(input.len(), output.len());
+        let frame_size = self.frame_size();
+        for (input_sample, output_sample) in input.iter().zip(output.iter_mut()) {
+            self.input[frame_size - self.hop_size + self.position] = *input_sample;
+            *output_sample = self.ready[self.position];
+            self.position += 1;
+            if self.position == self.hop_size {
+                self.position = 0;
+                self.process_frame(&mut callback);
+            }
+        }
+    }
+
+    fn process_frame<F>(&mut self, callback: &mut F)
+    where
+        F: FnMut(&mut [f32]),
+    {
+        for ((frame_sample, input_sample), window) in self
+            .frame
+            .iter_mut()
+            .zip(self.input.iter())
+            .zip(self.window.iter())
+        {
+            *frame_sample = input_sample * window;
+        }
+        callback(&mut self.frame);
+        for (index, ((accumulated, frame_sample), window)) in self
+            .accumulator
+            .iter_mut()
+            .zip(self.frame.iter())
+            .zip(self.window.iter())
+            .enumerate()
+        {
+            *accumulated += frame_sample * window * self.normalization[index % self.hop_size];
+        }
+        // The first `hop_size` samples of the accumulator will not change anymore.
+        self.ready
+            .copy_from_slice(&self.accumulator[..self.hop_size]);
+        self.accumulator.rotate_left(self.hop_size);
+        let frame_size = self.frame_size();
+        for sample in self.accumulator[frame_size - self.hop_size..].iter_mut() {
+            *sample = 0.0;
+        }
+        self.input.rotate_left(self.hop_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OverlapAdd;
+
+    #[test]
+    fn identity_callback_reconstructs_the_input_with_hann_window_at_50_percent_overlap() {
+        let mut overlap_add = OverlapAdd::hann(16, 8);
+        let input: Vec<f32> = (0..100).map(|i| (i as f32 * 0.3).sin()).collect();
+        let mut output = vec![0.0; input.len()];
+        let mut number_of_frames = 0;
+        // Use a chunk size that is not related to the hop size.
+        for (input_chunk, output_chunk) in input.chunks(7).zip(output.chunks_mut(7)) {
+            overlap_add.process(input_chunk, output_chunk, |_| number_of_frames += 1);
+        }
+        assert_eq!(number_of_frames, 100 / 8);
+        let latency = overlap_add.latency_in_frames();
+        assert_eq!(latency, 16);
+        for (expected, observed) in input.iter().zip(output[latency..].iter()) {
+            assert!(
+                (expected - observed).abs() < 1e-5,
+                "Expected {}, but got {}.",
+                expected,
+                observed
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn frame_size_must_be_a_multiple_of_the_hop_size() {
+        OverlapAdd::hann(16, 6);
+    }
+}
@@ -78,8 +78,11 @@
 //!
 //! ```
 
-use crate::event::{ContextualEventHandler, EventHandler, RawMidiEvent};
+use crate::event::{
+    ContextualEventHandler, EventHandler, MidiMessage, RawMidiEvent, SysExEvent, Timed,
+};
 use midi_consts::channel_event::*;
+use num_traits::AsPrimitive;
 
 pub enum EventDispatchClass<Identifier> {
     Broadcast,
@@ -128,6 +131,29 @@ where
     }
 }
 
+/// System exclusive events are not specific to a tone, so they are broadcast to all voices.
+impl<'a> EventDispatchClassifier<SysExEvent<'a>> for RawMidiEventToneIdentifierDispatchClassifier {
+    type VoiceIdentifier = ToneIdentifier;
+
+    fn classify(&self, _event: &SysExEvent<'a>) -> EventDispatchClass<Self::VoiceIdentifier> {
+        EventDispatchClass::Broadcast
+    }
+}
+
+/// System exclusive events are not specific to a tone, so they are broadcast to all voices.
+impl<'a> EventDispatchClassifier<Timed<SysExEvent<'a>>>
+    for RawMidiEventToneIdentifierDispatchClassifier
+{
+    type VoiceIdentifier = ToneIdentifier;
+
+    fn classify(
+        &self,
+        _event: &Timed<SysExEvent<'a>>,
+    ) -> EventDispatchClass<Self::VoiceIdentifier> {
+        EventDispatchClass::Broadcast
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum VoiceAssignment {
     None,
@@ -140,6 +166,166 @@ pub trait Voice<State> {
     fn state(&self) -> State;
 }
 
+/// Implement this trait to inform a voice stealing policy how loud this voice currently is,
+/// e.g. for the [`StealQuietest`] policy.
+///
+/// [`StealQuietest`]: ./simple_event_dispatching/struct.StealQuietest.html
+pub trait VoiceLoudness {
+    /// The current loudness of the voice, e.g. the amplitude of its envelope.
+    fn loudness(&self) -> f32;
+}
+
+/// Keeps track of the amplitude of the audio that a voice renders, so that the voice can
+/// report that it is idle as soon as it has become inaudible (e.g. at the end of a long release),
+/// instead of only when it receives a note-off event.
+///
+/// The voice passes every sample that it renders to [`observe`].
+/// When the absolute value of the samples has stayed below the threshold for the given number
+/// of frames, [`is_silent`] returns `true`, so that the voice can report that it is idle and the
+/// event dispatcher can reuse it.
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::utilities::polyphony::{AmplitudeTracker, ToneIdentifier, Voice};
+/// use rsynth::utilities::polyphony::simple_event_dispatching::SimpleVoiceState;
+/// struct MyVoice {
+///     state: SimpleVoiceState<ToneIdentifier>,
+///     amplitude: AmplitudeTracker,
+///     // ...
+/// }
+///
+/// impl Voice<SimpleVoiceState<ToneIdentifier>> for MyVoice {
+///     fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+///         if self.amplitude.is_silent() {
+///             SimpleVoiceState::Idle
+///         } else {
+///             self.state
+///         }
+///     }
+/// }
+/// ```
+///
+/// _Note_: call [`reset`] when the voice starts a new note, so that the voice is not considered
+/// idle before it had the chance to render any audio.
+///
+/// [`observe`]: #method.observe
+/// [`is_silent`]: #method.is_silent
+/// [`reset`]: #method.reset
+#[derive(Clone, Debug)]
+pub struct AmplitudeTracker {
+    threshold: f32,
+    required_number_of_silent_frames: usize,
+    number_of_silent_frames: usize,
+}
+
+impl AmplitudeTracker {
+    /// Create a new `AmplitudeTracker` that considers the voice silent when the absolute value
+    /// of the samples has been below `threshold` for `required_number_of_silent_frames`
+    /// consecutive frames.
+    ///
+    /// A new `AmplitudeTracker` considers the voice to be silent.
+    pub fn new(threshold: f32, required_number_of_silent_frames: usize) -> Self {
+        Self {
+            threshold,
+            required_number_of_silent_frames,
+            number_of_silent_frames: required_number_of_silent_frames,
+        }
+    }
+
+    /// Observe one sample rendered by the voice.
+    ///
+    /// For a voice that renders more than one channel, call this method once per frame,
+    /// e.g. with the sample of the loudest channel.
+    pub fn observe<S>(&mut self, sample: S)
+    where
+        S: AsPrimitive<f32>,
+    {
+        if sample.as_().abs() < self.threshold {
+            if self.number_of_silent_frames < self.required_number_of_silent_frames {
+                self.number_of_silent_frames += 1;
+            }
+        } else {
+            self.number_of_silent_frames = 0;
+        }
+    }
+
+    /// Return `true` if the samples have been below the threshold long enough.
+    pub fn is_silent(&self) -> bool {
+        self.number_of_silent_frames >= self.required_number_of_silent_frames
+    }
+
+    /// Forget the samples that have been observed so far, so that the voice is not
+    /// considered silent until the samples have been below the threshold long enough.
+    pub fn reset(&mut self) {
+        self.number_of_silent_frames = 0;
+    }
+}
+
+/// Keeps track of the polyphonic key pressure ("aftertouch") of the note that a voice plays,
+/// e.g. to modulate the volume or the brightness of that voice only.
+///
+/// The [`RawMidiEventToneIdentifierDispatchClassifier`] classifies polyphonic key pressure
+/// events as voice-specific, so the event dispatcher sends them only to the voice that plays
+/// the corresponding note. The voice passes every event it receives to [`observe`].
+///
+/// Example
+/// -------
+/// ```
+/// use rsynth::event::{EventHandler, RawMidiEvent, Timed};
+/// use rsynth::utilities::polyphony::KeyPressure;
+///
+/// struct MyVoice {
+///     pressure: KeyPressure,
+///     // ...
+/// }
+///
+/// impl EventHandler<Timed<RawMidiEvent>> for MyVoice {
+///     fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+///         self.pressure.observe(&event.event);
+///         // ...
+///     }
+/// }
+///
+/// let mut voice = MyVoice { pressure: KeyPressure::new() };
+/// voice.handle_event(Timed::new(0, RawMidiEvent::new(&[0xA0, 60, 127])));
+/// assert_eq!(voice.pressure.pressure(), 1.0);
+/// ```
+///
+/// [`RawMidiEventToneIdentifierDispatchClassifier`]: ./struct.RawMidiEventToneIdentifierDispatchClassifier.html
+/// [`observe`]: #method.observe
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KeyPressure {
+    pressure: f32,
+}
+
+impl KeyPressure {
+    /// Create a new `KeyPressure` with pressure `0`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Observe an event that the voice receives.
+    ///
+    /// A polyphonic key pressure event sets the pressure; a note on event resets the pressure
+    /// to `0`, so that a new note does not start with the pressure of the previous note.
+    /// Other events are ignored.
+    pub fn observe(&mut self, event: &RawMidiEvent) {
+        match event.parse() {
+            Some(MidiMessage::PolyPressure { pressure, .. }) => {
+                self.pressure = pressure as f32 / 127.0;
+            }
+            Some(MidiMessage::NoteOn { .. }) => self.pressure = 0.0,
+            _ => {}
+        }
+    }
+
+    /// The current pressure, between `0.0` and `1.0` (inclusive).
+    pub fn pressure(&self) -> f32 {
+        self.pressure
+    }
+}
+
 pub trait VoiceAssigner<Event>: EventDispatchClassifier<Event>
 where
     Event: Copy,
@@ -173,6 +359,15 @@ where
         identifier: Self::VoiceIdentifier,
         voices: &mut [Self::Voice],
     ) -> usize;
+
+    /// A number that indicates when the voice with the given index has been assigned to a
+    /// new note: a voice that has been assigned more recently has a higher number.
+    /// Returns `None` if this is not known, e.g. because the voice has never been assigned.
+    ///
+    /// The default implementation always returns `None`.
+    fn assignment_order(&self, _voice_index: usize) -> Option<u64> {
+        None
+    }
 }
 
 pub trait EventDispatcher<Event>: VoiceAssigner<Event>
@@ -187,9 +382,7 @@ where
                 voices[index].handle_event(event);
             }
             VoiceAssignment::All => {
-                for voice in voices {
-                    voice.handle_event(event);
-                }
+                voices.handle_event(event);
             }
         }
     }
@@ -225,7 +418,7 @@ where
 pub mod simple_event_dispatching {
     use super::{
         ContextualEventDispatcher, EventDispatchClass, EventDispatchClassifier, EventDispatcher,
-        Voice, VoiceAssigner,
+        Voice, VoiceAssigner, VoiceAssignment, VoiceLoudness,
     };
     use crate::event::{ContextualEventHandler, EventHandler};
     use std::marker::PhantomData;
@@ -247,6 +440,160 @@ pub mod simple_event_dispatching {
         Active(VoiceIdentifier),
     }
 
+    /// Keeps track of the order in which voices have been assigned to new notes.
+    ///
+    /// Every time a voice is assigned, a counter is increased and stored for that voice.
+    #[derive(Clone, Debug, Default)]
+    pub struct VoiceAges {
+        // The value of `counter` at the time the voice with the corresponding index was assigned,
+        // `0` if it has never been assigned.
+        assigned_at: Vec<u64>,
+        counter: u64,
+    }
+
+    impl VoiceAges {
+        /// Create a new `VoiceAges` for which no voice has been assigned yet.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Create a new `VoiceAges` for which no voice has been assigned yet and that can keep
+        /// track of `number_of_voices` voices without allocating memory.
+        pub fn with_voices(number_of_voices: usize) -> Self {
+            let mut ages = Self::new();
+            ages.reserve(number_of_voices);
+            ages
+        }
+
+        /// Make sure that no memory needs to be allocated when one of the first
+        /// `number_of_voices` voices is assigned.
+        ///
+        /// # Note about using in a real-time context
+        /// This method may allocate memory.
+        pub fn reserve(&mut self, number_of_voices: usize) {
+            if self.assigned_at.len() < number_of_voices {
+                self.assigned_at.resize(number_of_voices, 0);
+            }
+        }
+
+        /// Register that the voice with the given index has been assigned to a new note.
+        ///
+        /// # Panics
+        /// Panics if `index` is not lower than the number of voices that have been reserved
+        /// with [`with_voices`] or with the [`reserve`] method.
+        ///
+        /// [`with_voices`]: #method.with_voices
+        /// [`reserve`]: #method.reserve
+        pub fn voice_assigned(&mut self, index: usize) {
+            self.counter += 1;
+            self.assigned_at[index] = self.counter;
+        }
+
+        /// The value of the counter when the voice with the given index was assigned most
+        /// recently, or `None` if the voice has never been assigned.
+        pub fn assignment_order(&self, index: usize) -> Option<u64> {
+            match self.assigned_at.get(index) {
+                Some(0) | None => None,
+                Some(order) => Some(*order),
+            }
+        }
+
+        /// The index of the voice that has been assigned the longest time ago,
+        /// among the first `number_of_voices` voices.
+        /// Voices that have never been assigned are considered older than the other voices.
+        ///
+        /// Returns `None` if `number_of_voices` is `0`.
+        pub fn oldest(&self, number_of_voices: usize) -> Option<usize> {
+            (0..number_of_voices).min_by_key(|index| self.assignment_order(*index).unwrap_or(0))
+        }
+    }
+
+    /// Decides which voice is "stolen" when a new voice is needed, but no voice is idle.
+    ///
+    /// The type parameter `V` refers to the voice and the type parameter `State` refers to
+    /// the state of the voice.
+    pub trait VoiceStealingPolicy<V, State> {
+        /// Return the index of the voice that should be stolen, or `None` if no voice should
+        /// be stolen; the event that needed a new voice is then ignored.
+        ///
+        /// `ages` indicates the order in which the voices have been assigned.
+        /// This method is only called when no voice is idle.
+        fn voice_to_steal(&mut self, voices: &[V], ages: &VoiceAges) -> Option<usize>;
+    }
+
+    /// Steal a voice that is releasing or, if no voice is releasing, the first voice.
+    ///
+    /// This is the default policy of the [`SimpleEventDispatcher`].
+    ///
+    /// [`SimpleEventDispatcher`]: ./struct.SimpleEventDispatcher.html
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct StealReleasing;
+
+    impl<V, VoiceIdentifier> VoiceStealingPolicy<V, SimpleVoiceState<VoiceIdentifier>>
+        for StealReleasing
+    where
+        V: Voice<SimpleVoiceState<VoiceIdentifier>>,
+        VoiceIdentifier: Copy + Eq,
+    {
+        fn voice_to_steal(&mut self, voices: &[V], _ages: &VoiceAges) -> Option<usize> {
+            let mut second_best = 0;
+            for (index, voice) in voices.iter().enumerate() {
+                if let SimpleVoiceState::Releasing(_) = voice.state() {
+                    second_best = index;
+                }
+            }
+            Some(second_best)
+        }
+    }
+
+    /// Steal the voice that has been assigned to a note the longest time ago.
+    ///
+    /// _Note_: the [`SimpleEventDispatcher`] keeps track of the age of the voices,
+    /// so it should be kept between events when using this policy.
+    ///
+    /// [`SimpleEventDispatcher`]: ./struct.SimpleEventDispatcher.html
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct StealOldest;
+
+    impl<V, State> VoiceStealingPolicy<V, State> for StealOldest {
+        fn voice_to_steal(&mut self, voices: &[V], ages: &VoiceAges) -> Option<usize> {
+            ages.oldest(voices.len())
+        }
+    }
+
+    /// Steal the voice with the lowest [`loudness`].
+    ///
+    /// [`loudness`]: ../trait.VoiceLoudness.html#tymethod.loudness
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct StealQuietest;
+
+    impl<V, State> VoiceStealingPolicy<V, State> for StealQuietest
+    where
+        V: VoiceLoudness,
+    {
+        fn voice_to_steal(&mut self, voices: &[V], _ages: &VoiceAges) -> Option<usize> {
+            let mut quietest: Option<(usize, f32)> = None;
+            for (index, voice) in voices.iter().enumerate() {
+                let loudness = voice.loudness();
+                match quietest {
+                    Some((_, quietest_loudness)) if quietest_loudness <= loudness => {}
+                    _ => quietest = Some((index, loudness)),
+                }
+            }
+            quietest.map(|(index, _)| index)
+        }
+    }
+
+    /// Never steal a voice: new notes are ignored when no voice is idle.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct NeverSteal;
+
+    impl<V, State> VoiceStealingPolicy<V, State> for NeverSteal {
+        fn voice_to_steal(&mut self, _voices: &[V], _ages: &VoiceAges) -> Option<usize> {
+            None
+        }
+    }
+
     /// A simple event dispatcher.
     ///
     /// The type parameter `Classifier` refers to the classifier that is used to classify events.
@@ -254,34 +601,102 @@ pub mod simple_event_dispatching {
     /// the concrete type used for `Classifier` should implement the `EventDispatchClassifier` trait.
     ///
     /// The type parameter `V` refers to the voice.
-    pub struct SimpleEventDispatcher<Classifier, V> {
+    ///
+    /// The type parameter `P` refers to the [`VoiceStealingPolicy`] that decides which voice
+    /// is used for a new note when no voice is idle.
+    ///
+    /// The `SimpleEventDispatcher` keeps track of the order in which the voices have been
+    /// assigned to new notes (see [`voice_ages`]).
+    /// It also remembers which voices have received a note on event, but not yet the
+    /// corresponding note off event, so that a note off event is sent to the voice that
+    /// received the note on event, even if more than one voice is playing the same note.
+    /// When no such voice is found, the note off event is sent to the first voice that is
+    /// active with the same voice identifier.
+    ///
+    /// _Note_: only the voices that have been reserved with [`with_voices`] or
+    /// [`reserve_voices`] are tracked, so that no memory is allocated when dispatching events.
+    /// Reserve the number of voices of the plugin when creating the `SimpleEventDispatcher`.
+    ///
+    /// [`VoiceStealingPolicy`]: ./trait.VoiceStealingPolicy.html
+    /// [`voice_ages`]: #method.voice_ages
+    /// [`with_voices`]: #method.with_voices
+    /// [`reserve_voices`]: #method.reserve_voices
+    pub struct SimpleEventDispatcher<Classifier, V, P = StealReleasing> {
         classifier: Classifier,
+        policy: P,
+        ages: VoiceAges,
+        // `held[i]` is `true` if voice `i` has been assigned to a note that has not been released.
+        held: Vec<bool>,
         _voice_phantom: PhantomData<V>,
     }
 
     impl<Classifier, V> SimpleEventDispatcher<Classifier, V> {
         pub fn new(classifier: Classifier) -> Self {
+            Self::with_policy(classifier, StealReleasing)
+        }
+    }
+
+    impl<Classifier, V, P> SimpleEventDispatcher<Classifier, V, P> {
+        /// Create a new `SimpleEventDispatcher` with the given voice stealing policy.
+        pub fn with_policy(classifier: Classifier, policy: P) -> Self {
             Self {
                 classifier,
+                policy,
+                ages: VoiceAges::new(),
+                held: Vec::new(),
                 _voice_phantom: PhantomData,
             }
         }
+
+        /// Keep track of the first `number_of_voices` voices.
+        ///
+        /// Example
+        /// -------
+        /// ```
+        /// use rsynth::utilities::polyphony::RawMidiEventToneIdentifierDispatchClassifier;
+        /// use rsynth::utilities::polyphony::simple_event_dispatching::SimpleEventDispatcher;
+        /// # struct MyVoice;
+        /// let dispatcher: SimpleEventDispatcher<_, MyVoice> =
+        ///     SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier)
+        ///         .with_voices(16);
+        /// ```
+        ///
+        /// # Note about using in a real-time context
+        /// This method allocates memory.
+        pub fn with_voices(mut self, number_of_voices: usize) -> Self {
+            self.reserve_voices(number_of_voices);
+            self
+        }
+
+        /// Keep track of the first `number_of_voices` voices.
+        ///
+        /// # Note about using in a real-time context
+        /// This method may allocate memory.
+        pub fn reserve_voices(&mut self, number_of_voices: usize) {
+            self.ages.reserve(number_of_voices);
+            if self.held.len() < number_of_voices {
+                self.held.resize(number_of_voices, false);
+            }
+        }
+
+        /// The order in which the voices have been assigned to new notes.
+        pub fn voice_ages(&self) -> &VoiceAges {
+            &self.ages
+        }
     }
 
-    impl<Classifier, V> Default for SimpleEventDispatcher<Classifier, V>
+    impl<Classifier, V, P> Default for SimpleEventDispatcher<Classifier, V, P>
     where
         Classifier: Default,
+        P: Default,
     {
         fn default() -> Self {
-            Self {
-                classifier: Classifier::default(),
-                _voice_phantom: PhantomData,
-            }
+            Self::with_policy(Classifier::default(), P::default())
         }
     }
 
-    impl<Event, Classifier, Voice> EventDispatchClassifier<Event>
-        for SimpleEventDispatcher<Classifier, Voice>
+    impl<Event, Classifier, Voice, P> EventDispatchClassifier<Event>
+        for SimpleEventDispatcher<Classifier, Voice, P>
     where
         Classifier: EventDispatchClassifier<Event>,
         Event: Copy,
@@ -293,14 +708,95 @@ pub mod simple_event_dispatching {
         }
     }
 
-    impl<Event, Classifier, V> VoiceAssigner<Event> for SimpleEventDispatcher<Classifier, V>
+    impl<Classifier, V, P> SimpleEventDispatcher<Classifier, V, P> {
+        fn find_voice_for_new_note<VoiceIdentifier>(&mut self, voices: &[V]) -> Option<usize>
+        where
+            V: Voice<SimpleVoiceState<VoiceIdentifier>>,
+            P: VoiceStealingPolicy<V, SimpleVoiceState<VoiceIdentifier>>,
+            VoiceIdentifier: Copy + Eq,
+        {
+            let index = match voices
+                .iter()
+                .position(|voice| voice.state() == SimpleVoiceState::Idle)
+            {
+                Some(index) => index,
+                None => self.policy.voice_to_steal(voices, &self.ages)?,
+            };
+            // Only the voices that have been reserved are tracked, to avoid allocating memory.
+            if index < self.held.len() {
+                self.ages.voice_assigned(index);
+                self.held[index] = true;
+            }
+            Some(index)
+        }
+
+        // Find the voice that has been assigned the longest time ago to a note with the given
+        // identifier that has not been released yet.
+        fn find_held_voice<VoiceIdentifier>(
+            &mut self,
+            identifier: VoiceIdentifier,
+            voices: &[V],
+        ) -> Option<usize>
+        where
+            V: Voice<SimpleVoiceState<VoiceIdentifier>>,
+            VoiceIdentifier: Copy + Eq,
+        {
+            let held = &self.held;
+            let ages = &self.ages;
+            voices
+                .iter()
+                .enumerate()
+                .filter(|(index, voice)| {
+                    held.get(*index).cloned().unwrap_or(false)
+                        && voice.state() == SimpleVoiceState::Active(identifier)
+                })
+                .min_by_key(|(index, _)| ages.assignment_order(*index))
+                .map(|(index, _)| index)
+        }
+    }
+
+    impl<Event, Classifier, V, P> VoiceAssigner<Event> for SimpleEventDispatcher<Classifier, V, P>
     where
         Classifier: EventDispatchClassifier<Event>,
         V: Voice<SimpleVoiceState<Classifier::VoiceIdentifier>>,
+        P: VoiceStealingPolicy<V, SimpleVoiceState<Classifier::VoiceIdentifier>>,
         Event: Copy,
     {
         type Voice = V;
 
+        fn assign_event(&mut self, event: Event, voices: &mut [Self::Voice]) -> VoiceAssignment {
+            match self.classify(&event) {
+                EventDispatchClass::AssignNewVoice(_) => {
+                    match self.find_voice_for_new_note::<Classifier::VoiceIdentifier>(voices) {
+                        Some(index) => VoiceAssignment::Some(index),
+                        None => VoiceAssignment::None,
+                    }
+                }
+                EventDispatchClass::Broadcast => VoiceAssignment::All,
+                EventDispatchClass::ReleaseVoice(identifier) => {
+                    if let Some(index) = self.find_held_voice(identifier, voices) {
+                        self.held[index] = false;
+                        return VoiceAssignment::Some(index);
+                    }
+                    match self.find_active_voice(identifier, voices) {
+                        Some(index) => VoiceAssignment::Some(index),
+                        None => VoiceAssignment::None,
+                    }
+                }
+                EventDispatchClass::VoiceSpecific(identifier) => {
+                    // Send e.g. polyphonic key pressure to the voice that will receive
+                    // the note off event.
+                    if let Some(index) = self.find_held_voice(identifier, voices) {
+                        return VoiceAssignment::Some(index);
+                    }
+                    match self.find_active_voice(identifier, voices) {
+                        Some(index) => VoiceAssignment::Some(index),
+                        None => VoiceAssignment::None,
+                    }
+                }
+            }
+        }
+
         fn find_active_voice(
             &mut self,
             identifier: Self::VoiceIdentifier,
@@ -313,42 +809,621 @@ pub mod simple_event_dispatching {
             // is already releasing?
         }
 
+        /// Find an idle voice or, if no voice is idle, the voice that is stolen according to
+        /// the voice stealing policy.
+        ///
+        /// _Note_: when the policy does not steal a voice, the first voice is returned.
         fn find_idle_voice(
             &mut self,
             _identifier: Self::VoiceIdentifier,
             voices: &mut [Self::Voice],
         ) -> usize {
-            let mut second_best = 0;
-            for (index, voice) in voices.iter().enumerate() {
-                match voice.state() {
-                    SimpleVoiceState::Idle => {
-                        return index;
+            self.find_voice_for_new_note::<Classifier::VoiceIdentifier>(voices)
+                .unwrap_or(0)
+        }
+
+        fn assignment_order(&self, voice_index: usize) -> Option<u64> {
+            self.ages.assignment_order(voice_index)
+        }
+    }
+
+    impl<Event, Classifier, V, P, Context> ContextualEventDispatcher<Event, Context>
+        for SimpleEventDispatcher<Classifier, V, P>
+    where
+        Classifier: EventDispatchClassifier<Event>,
+        V: Voice<SimpleVoiceState<Classifier::VoiceIdentifier>>
+            + ContextualEventHandler<Event, Context>,
+        P: VoiceStealingPolicy<V, SimpleVoiceState<Classifier::VoiceIdentifier>>,
+        Event: Copy,
+    {
+    }
+
+    impl<Event, Classifier, V, P> EventDispatcher<Event> for SimpleEventDispatcher<Classifier, V, P>
+    where
+        Classifier: EventDispatchClassifier<Event>,
+        V: Voice<SimpleVoiceState<Classifier::VoiceIdentifier>> + EventHandler<Event>,
+        P: VoiceStealingPolicy<V, SimpleVoiceState<Classifier::VoiceIdentifier>>,
+        Event: Copy,
+    {
+    }
+
+    /// The number of held notes that a `MonoLegatoDispatcher` created with
+    /// [`MonoLegatoDispatcher::new`] can remember.
+    ///
+    /// [`MonoLegatoDispatcher::new`]: ./struct.MonoLegatoDispatcher.html#method.new
+    pub const DEFAULT_NUMBER_OF_HELD_NOTES: usize = 16;
+
+    /// An event dispatcher for a monophonic synthesizer with legato: all events are sent to
+    /// one voice (the first voice in the slice of voices).
+    ///
+    /// The `MonoLegatoDispatcher` keeps a stack of the notes that are held:
+    /// * When a note on event is received, the note is pushed on the stack and the note on
+    ///   event is sent to the voice.
+    ///   When another note is already held, the voice is still active when it receives the
+    ///   note on event; the voice should then change its pitch without restarting its envelope
+    ///   (legato). When no note is held, the voice is retriggered.
+    /// * When a note off event is received for the note on top of the stack, the note is
+    ///   popped from the stack. If another note is still held, the (original) note on event
+    ///   of the note that is then on top of the stack is sent to the voice again, so that
+    ///   the voice goes back to the previous note. If no note is held anymore, the note off
+    ///   event is sent to the voice, so that the voice is released.
+    /// * When a note off event is received for a note that is held, but not on top of the stack,
+    ///   the note is removed from the stack and the note off event is ignored.
+    ///   Note off events for notes that are not held are ignored as well.
+    /// * Other events are sent to the voice.
+    ///
+    /// When the stack is full, the note that has been held the longest is forgotten,
+    /// so that no memory needs to be allocated.
+    ///
+    /// _Note_: the held notes are stored in the `MonoLegatoDispatcher`, so it should be kept
+    /// between events.
+    /// The held notes are only updated by the [`dispatch_event`] and
+    /// [`dispatch_contextual_event`] methods, not by the [`assign_event`] method.
+    ///
+    /// [`dispatch_event`]: ../trait.EventDispatcher.html#method.dispatch_event
+    /// [`dispatch_contextual_event`]: ../trait.ContextualEventDispatcher.html#method.dispatch_contextual_event
+    /// [`assign_event`]: ../trait.VoiceAssigner.html#method.assign_event
+    pub struct MonoLegatoDispatcher<Classifier, V, Event> {
+        classifier: Classifier,
+        // The note on events of the held notes, the most recent one last.
+        held_notes: Vec<Event>,
+        _voice_phantom: PhantomData<V>,
+    }
+
+    impl<Classifier, V, Event> MonoLegatoDispatcher<Classifier, V, Event> {
+        /// Create a new `MonoLegatoDispatcher` that can remember
+        /// [`DEFAULT_NUMBER_OF_HELD_NOTES`] held notes.
+        ///
+        /// # Note about using in a real-time context
+        /// This method allocates memory.
+        ///
+        /// [`DEFAULT_NUMBER_OF_HELD_NOTES`]: ./constant.DEFAULT_NUMBER_OF_HELD_NOTES.html
+        pub fn new(classifier: Classifier) -> Self {
+            Self::with_capacity(classifier, DEFAULT_NUMBER_OF_HELD_NOTES)
+        }
+
+        /// Create a new `MonoLegatoDispatcher` that can remember `capacity` held notes.
+        ///
+        /// # Panics
+        /// Panics if `capacity` is `0`.
+        ///
+        /// # Note about using in a real-time context
+        /// This method allocates memory.
+        pub fn with_capacity(classifier: Classifier, capacity: usize) -> Self {
+            assert!(capacity > 0);
+            Self {
+                classifier,
+                held_notes: Vec::with_capacity(capacity),
+                _voice_phantom: PhantomData,
+            }
+        }
+
+        /// The number of notes that are currently held.
+        pub fn number_of_held_notes(&self) -> usize {
+            self.held_notes.len()
+        }
+    }
+
+    impl<Classifier, V, Event> MonoLegatoDispatcher<Classifier, V, Event>
+    where
+        Classifier: EventDispatchClassifier<Event>,
+        Event: Copy,
+    {
+        fn identifier(&self, note_on: &Event) -> Option<Classifier::VoiceIdentifier> {
+            match self.classifier.classify(note_on) {
+                EventDispatchClass::AssignNewVoice(identifier) => Some(identifier),
+                _ => None,
+            }
+        }
+
+        fn position_of_held_note(&self, identifier: Classifier::VoiceIdentifier) -> Option<usize> {
+            self.held_notes
+                .iter()
+                .position(|note_on| self.identifier(note_on) == Some(identifier))
+        }
+
+        // Update the held notes and return the event that should be sent to the voice, if any.
+        fn update_held_notes(&mut self, event: Event) -> Option<Event> {
+            match self.classifier.classify(&event) {
+                EventDispatchClass::AssignNewVoice(identifier) => {
+                    if let Some(position) = self.position_of_held_note(identifier) {
+                        self.held_notes.remove(position);
+                    } else if self.held_notes.len() == self.held_notes.capacity() {
+                        self.held_notes.remove(0);
+                    }
+                    self.held_notes.push(event);
+                    Some(event)
+                }
+                EventDispatchClass::ReleaseVoice(identifier) => {
+                    let position = self.position_of_held_note(identifier)?;
+                    self.held_notes.remove(position);
+                    if position < self.held_notes.len() {
+                        // The note was not on top of the stack.
+                        return None;
                     }
-                    SimpleVoiceState::Releasing(_) => {
-                        second_best = index;
+                    match self.held_notes.last() {
+                        Some(previous_note_on) => Some(*previous_note_on),
+                        None => Some(event),
                     }
-                    SimpleVoiceState::Active(_) => {}
                 }
+                EventDispatchClass::Broadcast | EventDispatchClass::VoiceSpecific(_) => Some(event),
+            }
+        }
+    }
+
+    impl<Event, Classifier, V> EventDispatchClassifier<Event>
+        for MonoLegatoDispatcher<Classifier, V, Event>
+    where
+        Classifier: EventDispatchClassifier<Event>,
+        Event: Copy,
+    {
+        type VoiceIdentifier = Classifier::VoiceIdentifier;
+
+        fn classify(&self, event: &Event) -> EventDispatchClass<Self::VoiceIdentifier> {
+            self.classifier.classify(event)
+        }
+    }
+
+    impl<Event, Classifier, V> VoiceAssigner<Event> for MonoLegatoDispatcher<Classifier, V, Event>
+    where
+        Classifier: EventDispatchClassifier<Event>,
+        Event: Copy,
+    {
+        type Voice = V;
+
+        fn assign_event(&mut self, _event: Event, voices: &mut [Self::Voice]) -> VoiceAssignment {
+            if voices.is_empty() {
+                VoiceAssignment::None
+            } else {
+                VoiceAssignment::Some(0)
+            }
+        }
+
+        fn find_active_voice(
+            &mut self,
+            _identifier: Self::VoiceIdentifier,
+            voices: &mut [Self::Voice],
+        ) -> Option<usize> {
+            if voices.is_empty() {
+                None
+            } else {
+                Some(0)
             }
-            second_best
+        }
+
+        fn find_idle_voice(
+            &mut self,
+            _identifier: Self::VoiceIdentifier,
+            _voices: &mut [Self::Voice],
+        ) -> usize {
+            0
         }
     }
 
     impl<Event, Classifier, V, Context> ContextualEventDispatcher<Event, Context>
-        for SimpleEventDispatcher<Classifier, V>
+        for MonoLegatoDispatcher<Classifier, V, Event>
     where
         Classifier: EventDispatchClassifier<Event>,
-        V: Voice<SimpleVoiceState<Classifier::VoiceIdentifier>>
-            + ContextualEventHandler<Event, Context>,
+        V: ContextualEventHandler<Event, Context>,
         Event: Copy,
     {
+        fn dispatch_contextual_event(
+            &mut self,
+            event: Event,
+            voices: &mut [Self::Voice],
+            context: &mut Context,
+        ) {
+            if let Some(event) = self.update_held_notes(event) {
+                if let Some(voice) = voices.first_mut() {
+                    voice.handle_event(event, context);
+                }
+            }
+        }
     }
 
-    impl<Event, Classifier, V> EventDispatcher<Event> for SimpleEventDispatcher<Classifier, V>
+    impl<Event, Classifier, V> EventDispatcher<Event> for MonoLegatoDispatcher<Classifier, V, Event>
     where
         Classifier: EventDispatchClassifier<Event>,
-        V: Voice<SimpleVoiceState<Classifier::VoiceIdentifier>> + EventHandler<Event>,
+        V: EventHandler<Event>,
         Event: Copy,
     {
+        fn dispatch_event(&mut self, event: Event, voices: &mut [Self::Voice]) {
+            if let Some(event) = self.update_held_notes(event) {
+                if let Some(voice) = voices.first_mut() {
+                    voice.handle_event(event);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::simple_event_dispatching::{
+        MonoLegatoDispatcher, NeverSteal, SimpleEventDispatcher, SimpleVoiceState, StealOldest,
+        StealQuietest,
+    };
+    use super::{
+        AmplitudeTracker, EventDispatcher, KeyPressure,
+        RawMidiEventToneIdentifierDispatchClassifier, ToneIdentifier, Voice, VoiceLoudness,
+    };
+    use crate::event::{EventHandler, RawMidiEvent, SysExEvent, Timed};
+
+    struct SysExRecorder {
+        state: SimpleVoiceState<ToneIdentifier>,
+        received: Vec<Vec<u8>>,
+    }
+
+    impl Voice<SimpleVoiceState<ToneIdentifier>> for SysExRecorder {
+        fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+            self.state
+        }
+    }
+
+    impl<'a> EventHandler<Timed<SysExEvent<'a>>> for SysExRecorder {
+        fn handle_event(&mut self, event: Timed<SysExEvent<'a>>) {
+            self.received.push(event.event.data().to_vec());
+        }
+    }
+
+    #[test]
+    fn sys_ex_events_are_broadcast_to_all_voices() {
+        let mut voices = vec![
+            SysExRecorder {
+                state: SimpleVoiceState::Idle,
+                received: Vec::new(),
+            },
+            SysExRecorder {
+                state: SimpleVoiceState::Active(ToneIdentifier(60)),
+                received: Vec::new(),
+            },
+            SysExRecorder {
+                state: SimpleVoiceState::Releasing(ToneIdentifier(64)),
+                received: Vec::new(),
+            },
+        ];
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier);
+        let data = [0xF0, 0x7E, 0x7F, 0x09, 0x01, 0xF7];
+        dispatcher.dispatch_event(Timed::new(0, SysExEvent::new(&data)), &mut voices);
+        for voice in voices.iter() {
+            assert_eq!(voice.received, vec![data.to_vec()]);
+        }
+    }
+
+    #[test]
+    fn amplitude_tracker_is_silent_after_enough_quiet_frames() {
+        let mut tracker = AmplitudeTracker::new(0.01, 3);
+        tracker.reset();
+        tracker.observe(0.5f32);
+        assert!(!tracker.is_silent());
+        tracker.observe(0.001f32);
+        tracker.observe(-0.001f32);
+        assert!(!tracker.is_silent());
+        tracker.observe(0.0f32);
+        assert!(tracker.is_silent());
+        // A loud sample makes the voice audible again.
+        tracker.observe(-0.5f32);
+        assert!(!tracker.is_silent());
+    }
+
+    // A voice that is releasing and that reports to be idle once it has become silent.
+    struct FadingVoice {
+        state: SimpleVoiceState<ToneIdentifier>,
+        amplitude: AmplitudeTracker,
+        received_events: usize,
+    }
+
+    impl FadingVoice {
+        fn releasing(tone: u8) -> Self {
+            let mut amplitude = AmplitudeTracker::new(0.01, 4);
+            amplitude.reset();
+            Self {
+                state: SimpleVoiceState::Releasing(ToneIdentifier(tone)),
+                amplitude,
+                received_events: 0,
+            }
+        }
+
+        fn render(&mut self, samples: &[f64]) {
+            for sample in samples {
+                self.amplitude.observe(*sample);
+            }
+        }
+    }
+
+    impl Voice<SimpleVoiceState<ToneIdentifier>> for FadingVoice {
+        fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+            if self.amplitude.is_silent() {
+                SimpleVoiceState::Idle
+            } else {
+                self.state
+            }
+        }
+    }
+
+    impl EventHandler<Timed<RawMidiEvent>> for FadingVoice {
+        fn handle_event(&mut self, _event: Timed<RawMidiEvent>) {
+            self.received_events += 1;
+        }
+    }
+
+    #[test]
+    fn voice_that_is_silent_long_enough_is_reused() {
+        let mut voices = vec![FadingVoice::releasing(60), FadingVoice::releasing(62)];
+        voices[0].render(&[0.5, 0.1, 0.005, 0.002, 0.001, 0.0]);
+        voices[1].render(&[0.5, 0.4, 0.3, 0.2, 0.1, 0.05]);
+        assert!(voices[0].state() == SimpleVoiceState::Idle);
+        assert!(voices[1].state() == SimpleVoiceState::Releasing(ToneIdentifier(62)));
+
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier);
+        dispatcher.dispatch_event(
+            Timed::new(0, RawMidiEvent::new(&[0x90, 64, 100])),
+            &mut voices,
+        );
+        assert_eq!(voices[0].received_events, 1);
+        assert_eq!(voices[1].received_events, 0);
+    }
+
+    // A voice that plays the last note it received.
+    struct NoteVoice {
+        state: SimpleVoiceState<ToneIdentifier>,
+        loudness: f32,
+        notes: Vec<u8>,
+    }
+
+    impl NoteVoice {
+        fn idle() -> Self {
+            Self {
+                state: SimpleVoiceState::Idle,
+                loudness: 0.0,
+                notes: Vec::new(),
+            }
+        }
+    }
+
+    impl Voice<SimpleVoiceState<ToneIdentifier>> for NoteVoice {
+        fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+            self.state
+        }
+    }
+
+    impl VoiceLoudness for NoteVoice {
+        fn loudness(&self) -> f32 {
+            self.loudness
+        }
+    }
+
+    impl EventHandler<Timed<RawMidiEvent>> for NoteVoice {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+            let data = event.event.data();
+            // The state is not changed by a note off event, so that a voice that is
+            // released twice would be noticed.
+            if data[0] & 0xF0 == 0x90 {
+                self.state = SimpleVoiceState::Active(ToneIdentifier(data[1]));
+            }
+            self.notes.push(data[1]);
+        }
+    }
+
+    fn note_on(note: u8) -> Timed<RawMidiEvent> {
+        Timed::new(0, RawMidiEvent::new(&[0x90, note, 100]))
+    }
+
+    #[test]
+    fn steal_oldest_steals_the_voice_that_was_assigned_first() {
+        let mut voices = vec![NoteVoice::idle(), NoteVoice::idle()];
+        let mut dispatcher = SimpleEventDispatcher::with_policy(
+            RawMidiEventToneIdentifierDispatchClassifier,
+            StealOldest,
+        )
+        .with_voices(voices.len());
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_on(62), &mut voices);
+        dispatcher.dispatch_event(note_on(64), &mut voices);
+        dispatcher.dispatch_event(note_on(65), &mut voices);
+        assert_eq!(voices[0].notes, vec![60, 64]);
+        assert_eq!(voices[1].notes, vec![62, 65]);
+    }
+
+    #[test]
+    fn steal_quietest_steals_the_quietest_voice() {
+        let mut voices = vec![NoteVoice::idle(), NoteVoice::idle(), NoteVoice::idle()];
+        for (index, voice) in voices.iter_mut().enumerate() {
+            voice.handle_event(note_on(60 + index as u8));
+        }
+        voices[0].loudness = 0.5;
+        voices[1].loudness = 0.1;
+        voices[2].loudness = 0.8;
+        let mut dispatcher = SimpleEventDispatcher::with_policy(
+            RawMidiEventToneIdentifierDispatchClassifier,
+            StealQuietest,
+        );
+        dispatcher.dispatch_event(note_on(70), &mut voices);
+        assert_eq!(voices[1].notes, vec![61, 70]);
+    }
+
+    #[test]
+    fn never_steal_ignores_new_notes_when_no_voice_is_idle() {
+        let mut voices = vec![NoteVoice::idle()];
+        let mut dispatcher = SimpleEventDispatcher::with_policy(
+            RawMidiEventToneIdentifierDispatchClassifier,
+            NeverSteal,
+        );
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_on(62), &mut voices);
+        assert_eq!(voices[0].notes, vec![60]);
+    }
+
+    #[test]
+    fn dispatcher_keeps_track_of_the_order_in_which_voices_are_assigned() {
+        let mut voices = vec![NoteVoice::idle(), NoteVoice::idle(), NoteVoice::idle()];
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier)
+                .with_voices(voices.len());
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_on(62), &mut voices);
+        let ages = dispatcher.voice_ages();
+        assert!(ages.assignment_order(0) < ages.assignment_order(1));
+        assert_eq!(ages.assignment_order(2), None);
+        assert_eq!(ages.oldest(voices.len()), Some(2));
+        assert_eq!(ages.oldest(2), Some(0));
+    }
+
+    #[test]
+    fn voices_that_have_not_been_reserved_are_not_tracked() {
+        let mut voices = vec![NoteVoice::idle(), NoteVoice::idle()];
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier).with_voices(1);
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_on(62), &mut voices);
+        assert_eq!(voices[1].notes, vec![62]);
+        let ages = dispatcher.voice_ages();
+        assert_eq!(ages.assignment_order(0), Some(1));
+        assert_eq!(ages.assignment_order(1), None);
+    }
+
+    #[test]
+    fn note_off_is_sent_to_the_voice_that_received_the_note_on() {
+        let mut voices = vec![NoteVoice::idle(), NoteVoice::idle()];
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier)
+                .with_voices(voices.len());
+        let note_off = Timed::new(0, RawMidiEvent::new(&[0x80, 60, 0]));
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_on(60), &mut voices);
+        dispatcher.dispatch_event(note_off, &mut voices);
+        assert_eq!(voices[0].notes, vec![60, 60]);
+        assert_eq!(voices[1].notes, vec![60]);
+        dispatcher.dispatch_event(note_off, &mut voices);
+        assert_eq!(voices[1].notes, vec![60, 60]);
+    }
+
+    struct MonoVoice {
+        received: Vec<[u8; 3]>,
+    }
+
+    impl EventHandler<Timed<RawMidiEvent>> for MonoVoice {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+            self.received.push(*event.event.data());
+        }
+    }
+
+    fn mono_legato(events: &[[u8; 3]]) -> Vec<[u8; 3]> {
+        let mut voices = vec![MonoVoice {
+            received: Vec::new(),
+        }];
+        let mut dispatcher =
+            MonoLegatoDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier);
+        for data in events {
+            dispatcher.dispatch_event(Timed::new(0, RawMidiEvent::new(data)), &mut voices);
+        }
+        voices.remove(0).received
+    }
+
+    #[test]
+    fn mono_legato_goes_back_to_the_previous_note_and_releases_when_no_note_is_held() {
+        let observed = mono_legato(&[
+            [0x90, 60, 100],
+            [0x90, 64, 90],
+            [0x80, 64, 0],
+            [0x80, 60, 0],
+        ]);
+        assert_eq!(
+            observed,
+            vec![
+                [0x90, 60, 100],
+                [0x90, 64, 90],
+                [0x90, 60, 100],
+                [0x80, 60, 0]
+            ]
+        );
+    }
+
+    #[test]
+    fn mono_legato_ignores_note_off_for_a_note_that_is_not_on_top_of_the_stack() {
+        let observed = mono_legato(&[
+            [0x90, 60, 100],
+            [0x90, 64, 90],
+            [0x80, 60, 0],
+            [0x80, 60, 0],
+            [0x80, 64, 0],
+        ]);
+        assert_eq!(
+            observed,
+            vec![[0x90, 60, 100], [0x90, 64, 90], [0x80, 64, 0]]
+        );
+    }
+
+    struct PressureVoice {
+        state: SimpleVoiceState<ToneIdentifier>,
+        pressure: KeyPressure,
+        number_of_events: usize,
+    }
+
+    impl Voice<SimpleVoiceState<ToneIdentifier>> for PressureVoice {
+        fn state(&self) -> SimpleVoiceState<ToneIdentifier> {
+            self.state
+        }
+    }
+
+    impl EventHandler<Timed<RawMidiEvent>> for PressureVoice {
+        fn handle_event(&mut self, event: Timed<RawMidiEvent>) {
+            let data = event.event.data();
+            if data[0] & 0xF0 == 0x90 {
+                self.state = SimpleVoiceState::Active(ToneIdentifier(data[1]));
+            }
+            self.pressure.observe(&event.event);
+            self.number_of_events += 1;
+        }
+    }
+
+    #[test]
+    fn polyphonic_key_pressure_only_reaches_the_voice_playing_the_note() {
+        let mut voices: Vec<_> = (0..3)
+            .map(|_| PressureVoice {
+                state: SimpleVoiceState::Idle,
+                pressure: KeyPressure::new(),
+                number_of_events: 0,
+            })
+            .collect();
+        let mut dispatcher =
+            SimpleEventDispatcher::new(RawMidiEventToneIdentifierDispatchClassifier)
+                .with_voices(voices.len());
+        for note in &[62, 60, 64] {
+            dispatcher.dispatch_event(note_on(*note), &mut voices);
+        }
+        dispatcher.dispatch_event(
+            Timed::new(0, RawMidiEvent::new(&[0xA0, 60, 127])),
+            &mut voices,
+        );
+        assert!(voices[1].state == SimpleVoiceState::Active(ToneIdentifier(60)));
+        assert_eq!(voices[1].pressure.pressure(), 1.0);
+        assert_eq!(voices[1].number_of_events, 2);
+        for index in &[0, 2] {
+            assert_eq!(voices[*index].pressure.pressure(), 0.0);
+            assert_eq!(voices[*index].number_of_events, 1);
+        }
     }
 }